@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::core::frame::{
-    BoneFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
+    BoneFrame, InnerPoseFrame, PoseEvent, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
 };
 
 pub trait Chainable {
@@ -13,6 +13,9 @@ impl<T: TypePath + FromReflect + Clone> Chainable for ValueFrame<T> {
         // Note that self and other are queried at the same (relative) time
         // i.e. self is queried at `time`, whereas other is queried at `time - duration_first`
         // That means that it is possible to have the time query be out of range of timestamps
+        //
+        // Frames spanning the seam between both animations keep step interpolation from the
+        // frame providing `prev`, cubic tangents do not carry over the seam.
 
         if time < duration_first {
             match (self.prev_is_wrapped, self.next_is_wrapped) {
@@ -24,6 +27,7 @@ impl<T: TypePath + FromReflect + Clone> Chainable for ValueFrame<T> {
                     prev_is_wrapped: true,
                     // next_is_wrapped should never be true when prev_is_wrapped is true
                     next_is_wrapped: false,
+                    interpolation: other.interpolation.without_tangents(),
                 },
                 (false, true) => Self {
                     prev: self.prev.clone(),
@@ -32,6 +36,7 @@ impl<T: TypePath + FromReflect + Clone> Chainable for ValueFrame<T> {
                     next_timestamp: other.next_timestamp + duration_first,
                     prev_is_wrapped: false,
                     next_is_wrapped: false,
+                    interpolation: self.interpolation.without_tangents(),
                 },
                 (false, false) => self.clone(),
                 (true, true) => {
@@ -47,6 +52,7 @@ impl<T: TypePath + FromReflect + Clone> Chainable for ValueFrame<T> {
                     next_timestamp: other.next_timestamp + duration_first,
                     prev_is_wrapped: false,
                     next_is_wrapped: false,
+                    interpolation: self.interpolation.without_tangents(),
                 },
                 (false, true) => Self {
                     prev: other.prev.clone(),
@@ -56,6 +62,7 @@ impl<T: TypePath + FromReflect + Clone> Chainable for ValueFrame<T> {
                     // prev_is_wrapped should never be true when next_is_wrapped is true
                     prev_is_wrapped: false,
                     next_is_wrapped: true,
+                    interpolation: other.interpolation.without_tangents(),
                 },
                 (false, false) => {
                     let mut out = other.clone();
@@ -121,19 +128,19 @@ impl Chainable for InnerPoseFrame {
     fn chain(&self, other: &Self, duration_first: f32, duration_second: f32, time: f32) -> Self {
         let mut result = InnerPoseFrame::default();
 
-        for (path, bone_id) in self.paths.iter() {
-            let Some(other_bone_id) = other.paths.get(path) else {
+        for (bone_id, idx) in self.ids.iter() {
+            let Some(other_idx) = other.ids.get(bone_id) else {
                 continue;
             };
 
             result.add_bone(
-                self.bones[*bone_id].chain(
-                    &other.bones[*other_bone_id],
+                self.bones[*idx].chain(
+                    &other.bones[*other_idx],
                     duration_first,
                     duration_second,
                     time,
                 ),
-                path.clone(),
+                *bone_id,
             );
         }
 
@@ -165,6 +172,11 @@ impl Chainable for PoseFrameData {
                         .into(),
                 )
             }
+            (PoseFrameData::Additive(f1), PoseFrameData::Additive(f2)) => PoseFrameData::Additive(
+                f1.inner_ref()
+                    .chain(f2.inner_ref(), duration_first, duration_second, time)
+                    .into(),
+            ),
             _ => panic!(
                 "Tried to chain {:?} with {:?}",
                 PoseSpec::from(self),
@@ -181,6 +193,15 @@ impl Chainable for PoseFrame {
                 .data
                 .chain(&other.data, duration_first, duration_second, time),
             timestamp: time,
+            events: self
+                .events
+                .iter()
+                .cloned()
+                .chain(other.events.iter().map(|e| PoseEvent {
+                    timestamp: e.timestamp + duration_first,
+                    ..e.clone()
+                }))
+                .collect(),
         }
     }
 }
@@ -1,7 +1,14 @@
+use super::{pose::BoneId, systems::get_keyframe};
+use crate::interpolation::{cubic::InterpolateCubicSpline, linear::InterpolateLinear};
 use bevy::{
-    asset::prelude::*, core::prelude::*, math::prelude::*, reflect::prelude::*, utils::HashMap,
+    asset::prelude::*,
+    core::prelude::*,
+    math::prelude::*,
+    reflect::prelude::*,
+    utils::{FixedState, HashMap},
 };
 use serde::{Deserialize, Serialize};
+use std::hash::{BuildHasher, Hash, Hasher};
 
 /// List of keyframes for one of the attribute of a [`Transform`].
 ///
@@ -25,9 +32,31 @@ pub enum Keyframes {
     Weights(Vec<f32>),
 }
 
+/// How values are interpolated between the keyframes of a [`VariableCurve`]. Follows the
+/// [glTF interpolation modes].
+///
+/// [glTF interpolation modes]: https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#interpolation
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[reflect(Default)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// The value of a keyframe is held until the next keyframe.
+    Step,
+    /// Cubic Hermite spline. Each keyframe is stored as three consecutive values: the in-tangent,
+    /// the value and the out-tangent.
+    ///
+    /// The cubic shape is kept by nodes that only remap time or transform values linearly (e.g.
+    /// mirroring), but blends and space conversions combine the curves of several inputs, and
+    /// interpolate linearly between the combined samples. Keyframes are sampled exactly, so
+    /// blended cubic clips are only approximated between keyframes.
+    CubicSpline,
+}
+
 /// Describes how an attribute of a [`Transform`] or morph weights should be animated.
 ///
-/// `keyframe_timestamps` and `keyframes` should have the same length.
+/// `keyframe_timestamps` and `keyframes` should have the same length, except for
+/// [`Interpolation::CubicSpline`] curves, which have three keyframes per timestamp.
 ///
 /// [`Transform`]: bevy::transform::prelude::Transform
 #[derive(Reflect, Clone, Debug)]
@@ -36,6 +65,108 @@ pub struct VariableCurve {
     pub keyframe_timestamps: Vec<f32>,
     /// List of the keyframes.
     pub keyframes: Keyframes,
+    pub interpolation: Interpolation,
+}
+
+impl From<bevy::animation::VariableCurve> for VariableCurve {
+    fn from(value: bevy::animation::VariableCurve) -> Self {
+        let keyframes = match value.keyframes {
+            bevy::animation::Keyframes::Rotation(k) => Keyframes::Rotation(k),
+            bevy::animation::Keyframes::Translation(k) => Keyframes::Translation(k),
+            bevy::animation::Keyframes::Scale(k) => Keyframes::Scale(k),
+            bevy::animation::Keyframes::Weights(k) => Keyframes::Weights(k),
+        };
+        Self {
+            keyframe_timestamps: value.keyframe_timestamps,
+            keyframes,
+            // Bevy's glTF loader discards the interpolation mode, it has to be set separately
+            interpolation: Interpolation::Linear,
+        }
+    }
+}
+
+impl VariableCurve {
+    /// Samples the curve at each of the given source times, optionally blending each sample
+    /// towards the value at a second source time with the given weight.
+    fn resample(&self, samples: &[(f32, Option<(f32, f32)>)]) -> Keyframes {
+        match &self.keyframes {
+            Keyframes::Rotation(keyframes) => Keyframes::Rotation(self.resample_values(
+                samples,
+                |i| keyframes[i],
+                |tangent, f| *tangent * f,
+            )),
+            Keyframes::Translation(keyframes) => Keyframes::Translation(self.resample_values(
+                samples,
+                |i| keyframes[i],
+                |tangent, f| *tangent * f,
+            )),
+            Keyframes::Scale(keyframes) => Keyframes::Scale(self.resample_values(
+                samples,
+                |i| keyframes[i],
+                |tangent, f| *tangent * f,
+            )),
+            Keyframes::Weights(keyframes) => {
+                let per_timestamp = match self.interpolation {
+                    Interpolation::CubicSpline => 3,
+                    Interpolation::Linear | Interpolation::Step => 1,
+                };
+                let target_count = keyframes.len() / self.keyframe_timestamps.len() / per_timestamp;
+                Keyframes::Weights(
+                    self.resample_values(
+                        samples,
+                        |i| get_keyframe(target_count, keyframes, i).to_vec(),
+                        |tangent: &Vec<f32>, f| tangent.iter().map(|w| w * f).collect(),
+                    )
+                    .concat(),
+                )
+            }
+        }
+    }
+
+    fn resample_values<T>(
+        &self,
+        samples: &[(f32, Option<(f32, f32)>)],
+        keyframe: impl Fn(usize) -> T,
+        scale: impl Fn(&T, f32) -> T,
+    ) -> Vec<T>
+    where
+        T: InterpolateLinear + InterpolateCubicSpline,
+    {
+        let sample_at = |time: f32| -> T {
+            let timestamps = &self.keyframe_timestamps;
+            let next = timestamps.partition_point(|t| *t <= time);
+            let (prev, next) = (next.saturating_sub(1), next.min(timestamps.len() - 1));
+            let interval = timestamps[next] - timestamps[prev];
+            let f = if interval > 0. {
+                ((time - timestamps[prev]) / interval).clamp(0., 1.)
+            } else {
+                0.
+            };
+            match self.interpolation {
+                Interpolation::Linear => keyframe(prev).interpolate_linear(&keyframe(next), f),
+                Interpolation::Step => keyframe(prev),
+                Interpolation::CubicSpline => keyframe(3 * prev + 1).interpolate_cubic_spline(
+                    &scale(&keyframe(3 * prev + 2), interval),
+                    &keyframe(3 * next + 1),
+                    &scale(&keyframe(3 * next), interval),
+                    f,
+                ),
+            }
+        };
+
+        samples
+            .iter()
+            .map(|(time, blend)| {
+                let value = sample_at(*time);
+                match blend {
+                    Some((blend_time, weight)) => {
+                        value.interpolate_linear(&sample_at(*blend_time), *weight)
+                    }
+                    None => value,
+                }
+            })
+            .collect()
+    }
 }
 
 /// Path to an entity, with [`Name`]s. Each entity in a path must have a name.
@@ -54,6 +185,15 @@ impl EntityPath {
         new_path
     }
 
+    /// Integer id of the bone at this path, used to key pose data
+    pub fn id(&self) -> BoneId {
+        let mut hasher = FixedState.build_hasher();
+        for part in &self.parts {
+            part.as_str().hash(&mut hasher);
+        }
+        BoneId(hasher.finish())
+    }
+
     pub fn parent(&self) -> Option<Self> {
         let mut parent = self.clone();
         if parent.parts.len() > 1 {
@@ -148,12 +288,52 @@ impl<'de> Deserialize<'de> for EntityPath {
     }
 }
 
+/// Named event at a given time of a [`GraphClip`], e.g. a footstep or a sound cue.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[reflect(Default)]
+pub struct ClipEvent {
+    /// Time in the clip at which the event happens, in seconds
+    pub time: f32,
+    pub name: String,
+}
+
+/// Named marker at a given time of a [`GraphClip`], used to align clips in synchronized blends
+/// (e.g. the moments each foot touches the ground).
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[reflect(Default)]
+pub struct SyncMarker {
+    /// Time in the clip at which the marker is placed, in seconds
+    pub time: f32,
+    pub name: String,
+}
+
 /// A list of [`VariableCurve`], and the [`EntityPath`] to which they apply.
 #[derive(Asset, Reflect, Clone, Debug, Default)]
 pub struct GraphClip {
     pub(crate) curves: Vec<Vec<VariableCurve>>,
     pub(crate) paths: HashMap<EntityPath, usize>,
+    /// Same mapping as `paths`, keyed by the [`BoneId`] of each path
+    pub(crate) ids: HashMap<BoneId, usize>,
     pub(crate) duration: f32,
+    pub(crate) events: Vec<ClipEvent>,
+    /// Sorted by time
+    pub(crate) sync_markers: Vec<SyncMarker>,
+}
+
+/// Options for deriving a clip from a section of another clip, see [`GraphClip::sub_clip`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubClip {
+    /// Start of the section in the source clip, in seconds
+    pub start: f32,
+    /// End of the section in the source clip, in seconds. `None` for the end of the source clip
+    pub end: Option<f32>,
+    /// Play the section backwards
+    pub reverse: bool,
+    /// Duration at the end of the new clip that is blended towards the motion leading into its
+    /// first frame, so that it loops without a visible seam. Zero disables blending.
+    pub loop_blend: f32,
+    /// Keyframes per second of the new clip
+    pub sample_rate: f32,
 }
 
 impl GraphClip {
@@ -185,6 +365,35 @@ impl GraphClip {
         self.duration
     }
 
+    /// Events declared on this clip
+    #[inline]
+    pub fn events(&self) -> &[ClipEvent] {
+        &self.events
+    }
+
+    /// Add a named event at the given time of the clip.
+    pub fn add_event(&mut self, time: f32, name: impl Into<String>) {
+        self.events.push(ClipEvent {
+            time,
+            name: name.into(),
+        });
+    }
+
+    /// Sync markers declared on this clip, sorted by time
+    #[inline]
+    pub fn sync_markers(&self) -> &[SyncMarker] {
+        &self.sync_markers
+    }
+
+    /// Add a named sync marker at the given time of the clip.
+    pub fn add_sync_marker(&mut self, time: f32, name: impl Into<String>) {
+        self.sync_markers.push(SyncMarker {
+            time,
+            name: name.into(),
+        });
+        self.sync_markers.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
     /// Add a [`VariableCurve`] to an [`EntityPath`].
     pub fn add_curve_to_path(&mut self, path: EntityPath, curve: VariableCurve) {
         // Update the duration of the animation by this curve duration if it's longer
@@ -196,10 +405,121 @@ impl GraphClip {
         } else {
             let idx = self.curves.len();
             self.curves.push(vec![curve]);
+            self.ids.insert(path.id(), idx);
             self.paths.insert(path, idx);
         }
     }
 
+    /// Finds two different paths of the clip that share the same [`BoneId`], in which case their
+    /// curves cannot be told apart in pose data.
+    pub(crate) fn id_collision(&self) -> Option<(&EntityPath, &EntityPath)> {
+        let mut seen: HashMap<BoneId, &EntityPath> = HashMap::new();
+        for path in self.paths.keys() {
+            if let Some(other) = seen.insert(path.id(), path) {
+                return Some((other, path));
+            }
+        }
+        None
+    }
+
+    /// Gets the curves for a bone by its [`BoneId`].
+    ///
+    /// Returns `None` if the bone is invalid.
+    #[inline]
+    pub fn get_curves_by_id(&self, bone_id: BoneId) -> Option<&'_ Vec<VariableCurve>> {
+        self.ids.get(&bone_id).and_then(|idx| self.curves.get(*idx))
+    }
+
+    /// Sets the interpolation mode of the curves of a bone, in the order in which they were
+    /// added. Extra modes are ignored.
+    pub fn set_interpolations(
+        &mut self,
+        path: &EntityPath,
+        interpolations: impl IntoIterator<Item = Interpolation>,
+    ) {
+        let Some(idx) = self.paths.get(path) else {
+            return;
+        };
+        for (curve, interpolation) in self.curves[*idx].iter_mut().zip(interpolations) {
+            curve.interpolation = interpolation;
+        }
+    }
+
+    /// Builds a new clip from a section of this one. All curves are resampled with linear
+    /// interpolation at the given rate, and events and sync markers in the section are kept.
+    pub fn sub_clip(&self, settings: &SubClip) -> GraphClip {
+        let end = settings.end.unwrap_or(self.duration).min(self.duration);
+        let start = settings.start.clamp(0., end);
+        let duration = end - start;
+        // Also defined outside of the section, where it extends into the motion before its
+        // first frame
+        let source_time = |time: f32| {
+            if settings.reverse {
+                end - time
+            } else {
+                start + time
+            }
+        };
+
+        let sample_count = ((duration * settings.sample_rate).ceil() as usize).max(1);
+        let keyframe_timestamps: Vec<f32> = (0..=sample_count)
+            .map(|i| duration * i as f32 / sample_count as f32)
+            .collect();
+        let loop_blend = settings.loop_blend.clamp(0., duration);
+        let samples: Vec<(f32, Option<(f32, f32)>)> = keyframe_timestamps
+            .iter()
+            .map(|time| {
+                let blend_start = duration - loop_blend;
+                let blend = (loop_blend > 0. && *time > blend_start).then(|| {
+                    (
+                        source_time(*time - duration),
+                        (*time - blend_start) / loop_blend,
+                    )
+                });
+                (source_time(*time), blend)
+            })
+            .collect();
+
+        let mut clip = GraphClip::default();
+        for (path, idx) in &self.paths {
+            for curve in &self.curves[*idx] {
+                if curve.keyframe_timestamps.is_empty() {
+                    continue;
+                }
+                clip.add_curve_to_path(
+                    path.clone(),
+                    VariableCurve {
+                        keyframe_timestamps: keyframe_timestamps.clone(),
+                        keyframes: curve.resample(&samples),
+                        interpolation: Interpolation::Linear,
+                    },
+                );
+            }
+        }
+        clip.duration = duration;
+
+        let clip_time = |time: f32| {
+            let clip_time = if settings.reverse {
+                end - time
+            } else {
+                time - start
+            };
+            (start..=end).contains(&time).then_some(clip_time)
+        };
+        for event in &self.events {
+            if let Some(time) = clip_time(event.time) {
+                clip.add_event(time, event.name.clone());
+            }
+        }
+        for marker in &self.sync_markers {
+            if let Some(time) = clip_time(marker.time) {
+                clip.add_sync_marker(time, marker.name.clone());
+            }
+        }
+
+        clip
+    }
+
     /// Whether this animation clip can run on entity with given [`Name`].
     pub fn compatible_with(&self, name: &Name) -> bool {
         self.paths.keys().any(|path| &path.parts[0] == name)
@@ -208,9 +528,27 @@ impl GraphClip {
 
 impl From<bevy::animation::AnimationClip> for GraphClip {
     fn from(value: bevy::animation::AnimationClip) -> Self {
-        // HACK: to get the corret type, since bevy's AnimationClip
-        // does not expose its internals
-        unsafe { std::mem::transmute(value) }
+        // Bevy's AnimationClip does not expose its paths, so they are read through reflection
+        let paths = value
+            .field("paths")
+            .and_then(|paths| paths.downcast_ref::<HashMap<bevy::animation::EntityPath, usize>>())
+            .expect("bevy's AnimationClip should reflect its paths");
+
+        // Paths are added in the order of their curves, so that curve indices are preserved
+        let mut paths: Vec<_> = paths.iter().collect();
+        paths.sort_by_key(|(_, idx)| **idx);
+
+        let mut clip = Self::default();
+        for (path, idx) in paths {
+            let path = EntityPath {
+                parts: path.parts.clone(),
+            };
+            for curve in &value.curves()[*idx] {
+                clip.add_curve_to_path(path.clone(), VariableCurve::from(curve.clone()));
+            }
+        }
+        clip.duration = value.duration();
+        clip
     }
 }
 
@@ -245,4 +583,103 @@ mod tests {
         assert_eq!(entity_path, EntityPath::from_slashed_string(path.clone()));
         assert_eq!(path, entity_path.to_slashed_string());
     }
+
+    #[test]
+    fn bone_ids_follow_paths() {
+        let path = EntityPath::from_slashed_string("root/spine/head".to_string());
+        assert_eq!(path.id(), path.clone().id());
+        assert_eq!(
+            path.parent().unwrap().id(),
+            EntityPath::from_slashed_string("root/spine".to_string()).id()
+        );
+        // Part boundaries are part of the id
+        assert_ne!(
+            EntityPath::from_slashed_string("ab/c".to_string()).id(),
+            EntityPath::from_slashed_string("a/bc".to_string()).id()
+        );
+    }
+
+    #[test]
+    fn from_bevy_clip() {
+        let mut bevy_clip = bevy::animation::AnimationClip::default();
+        for (name, end) in [("root", 1.), ("root/spine", 2.)] {
+            bevy_clip.add_curve_to_path(
+                bevy::animation::EntityPath {
+                    parts: name.split('/').map(Name::new).collect(),
+                },
+                bevy::animation::VariableCurve {
+                    keyframe_timestamps: vec![0., end],
+                    keyframes: bevy::animation::Keyframes::Translation(vec![Vec3::ZERO, Vec3::X]),
+                },
+            );
+        }
+
+        let clip = GraphClip::from(bevy_clip);
+        assert_eq!(clip.duration(), 2.);
+        let spine = EntityPath::from_slashed_string("root/spine".to_string());
+        let curves = clip.get_curves_by_id(spine.id()).unwrap();
+        assert_eq!(curves[0].keyframe_timestamps, vec![0., 2.]);
+    }
+
+    #[test]
+    fn sync_markers_are_sorted() {
+        let mut clip = GraphClip::default();
+        clip.add_sync_marker(0.5, "right");
+        clip.add_sync_marker(f32::NAN, "broken");
+        clip.add_sync_marker(0., "left");
+        let names: Vec<_> = clip
+            .sync_markers()
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names, ["left", "right", "broken"]);
+    }
+
+    #[test]
+    fn sub_clip_trims_reverses_and_blends_the_seam() {
+        let path = EntityPath::from_slashed_string("root".to_string());
+        let mut clip = GraphClip::default();
+        clip.add_curve_to_path(
+            path.clone(),
+            VariableCurve {
+                keyframe_timestamps: vec![0., 2.],
+                keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::new(2., 0., 0.)]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_event(0.75, "inside");
+        clip.add_event(1.75, "outside");
+
+        let settings = SubClip {
+            start: 0.5,
+            end: Some(1.5),
+            reverse: true,
+            loop_blend: 0.,
+            sample_rate: 4.,
+        };
+        let sub_clip = clip.sub_clip(&settings);
+        assert_eq!(sub_clip.duration(), 1.);
+        assert_eq!(sub_clip.events().len(), 1);
+        assert_eq!(sub_clip.events()[0].time, 0.75);
+        let curve = &sub_clip.get_curves_by_path(&path).unwrap()[0];
+        assert_eq!(curve.keyframe_timestamps, vec![0., 0.25, 0.5, 0.75, 1.]);
+        let Keyframes::Translation(keyframes) = &curve.keyframes else {
+            panic!("Expected translation keyframes");
+        };
+        assert_eq!(keyframes[0].x, 1.5);
+        assert_eq!(keyframes[4].x, 0.5);
+
+        // The last keyframe is fully blended into the frame that leads into the first one
+        let sub_clip = clip.sub_clip(&SubClip {
+            loop_blend: 0.5,
+            ..settings
+        });
+        let Keyframes::Translation(keyframes) =
+            &sub_clip.get_curves_by_path(&path).unwrap()[0].keyframes
+        else {
+            panic!("Expected translation keyframes");
+        };
+        assert_eq!(keyframes[2].x, 1.);
+        assert_eq!(keyframes[4].x, 1.5);
+    }
 }
@@ -1,15 +1,17 @@
 use super::pin;
 use crate::{
     core::{
+        animation_clip::SyncMarker,
         animation_node::{AnimationNode, NodeLike},
         duration_data::DurationData,
         errors::{GraphError, GraphValidationError},
-        frame::{BonePoseFrame, PoseFrame, PoseSpec},
-        pose::{BoneId, Pose},
+        frame::{BonePoseFrame, PoseEvent, PoseFrame, PoseSpec},
+        pose::Pose,
+        skeleton::Skeleton,
     },
     prelude::{
-        DeferredGizmos, GraphContext, OptParamSpec, ParamSpec, ParamValue, PassContext,
-        SampleLinearAt, SpecContext, SystemResources,
+        DeferredGizmos, GraphContext, ParamSpec, ParamValue, PassContext, SampleLinearAt,
+        SpecContext, SystemResources,
     },
     utils::{ordered_map::OrderedMap, unwrap::Unwrap},
 };
@@ -270,8 +272,6 @@ impl AnimationGraph {
     /// Sets the value for a default parameter, registering it if it wasn't yet done
     pub fn set_default_parameter(&mut self, parameter_name: impl Into<String>, value: ParamValue) {
         let parameter_name = parameter_name.into();
-        let mut spec = OptParamSpec::from(&value);
-        spec.optional = true;
         self.default_parameters
             .insert(parameter_name.clone(), value);
     }
@@ -672,14 +672,20 @@ impl AnimationGraph {
                 outputs[pin_id].clone()
             }
             SourcePin::InputParameter(pin_id) => {
-                let out = if ctx.has_parent() {
-                    ctx.parent().parameter_back(pin_id).ok()
+                // Parameters connected to the node evaluating this graph take precedence, then
+                // the overlay and finally the default values
+                if ctx.has_parent() && ctx.parent().parameter_connected(pin_id) {
+                    ctx.parent().parameter_back(pin_id)?
+                } else if let Some(value) = ctx
+                    .overlay
+                    .parameters
+                    .get(pin_id)
+                    .or_else(|| self.default_parameters.get(pin_id))
+                {
+                    value.clone()
                 } else {
-                    None
+                    return Err(GraphError::MissingInputParameter(pin_id.clone()));
                 }
-                .or_else(|| ctx.overlay.parameters.get(pin_id).cloned())
-                .or_else(|| self.default_parameters.get(pin_id).cloned());
-                out.unwrap()
             }
             SourcePin::NodePose(_) => {
                 panic!("Incompatible pins connected: {source_pin:?} --> {target_pin:?}")
@@ -728,8 +734,10 @@ impl AnimationGraph {
             SourcePin::InputPose(pin_id) => {
                 if let Some(v) = ctx.overlay.durations.get(pin_id) {
                     *v
-                } else {
+                } else if ctx.has_parent() && ctx.parent().pose_connected(pin_id) {
                     ctx.parent().duration_back(pin_id)?
+                } else {
+                    return Err(GraphError::MissingInputPose(pin_id.clone()));
                 }
             }
         };
@@ -737,6 +745,37 @@ impl AnimationGraph {
         Ok(source_value)
     }
 
+    pub fn get_sync_markers(
+        &self,
+        target_pin: TargetPin,
+        ctx: PassContext,
+    ) -> Result<Vec<SyncMarker>, GraphError> {
+        let Some(source_pin) = self.edges.get(&target_pin) else {
+            return Err(GraphError::MissingInputEdge(target_pin));
+        };
+
+        match source_pin {
+            SourcePin::NodeParameter(_, _) => {
+                panic!("Incompatible pins connected: {source_pin:?} --> {target_pin:?}")
+            }
+            SourcePin::InputParameter(_) => {
+                panic!("Incompatible pins connected: {source_pin:?} --> {target_pin:?}")
+            }
+            SourcePin::NodePose(node_id) => {
+                let node = &self.nodes[node_id];
+                let should_debug = node.should_debug;
+                node.sync_markers_pass(ctx.with_node(node_id, self).with_debugging(should_debug))
+            }
+            SourcePin::InputPose(pin_id) => {
+                if ctx.has_parent() && ctx.parent().pose_connected(pin_id) {
+                    ctx.parent().sync_markers_back(pin_id)
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+        }
+    }
+
     pub fn get_pose(
         &self,
         time_update: TimeUpdate,
@@ -776,8 +815,10 @@ impl AnimationGraph {
             SourcePin::InputPose(pin_id) => {
                 if let Some(v) = ctx.overlay.poses.get(pin_id) {
                     v.clone()
-                } else {
+                } else if ctx.has_parent() && ctx.parent().pose_connected(pin_id) {
                     ctx.parent().pose_back(pin_id, time_update)?
+                } else {
+                    return Err(GraphError::MissingInputPose(pin_id.clone()));
                 }
             }
         };
@@ -791,7 +832,7 @@ impl AnimationGraph {
         context: &mut GraphContext,
         resources: &SystemResources,
         root_entity: Entity,
-        entity_map: &HashMap<BoneId, Entity>,
+        skeleton: &Skeleton,
         deferred_gizmos: &mut DeferredGizmos,
     ) -> Result<Pose, GraphError> {
         self.query_with_overlay(
@@ -800,7 +841,7 @@ impl AnimationGraph {
             resources,
             &InputOverlay::default(),
             root_entity,
-            entity_map,
+            skeleton,
             deferred_gizmos,
         )
     }
@@ -813,7 +854,7 @@ impl AnimationGraph {
         resources: &SystemResources,
         overlay: &InputOverlay,
         root_entity: Entity,
-        entity_map: &HashMap<BoneId, Entity>,
+        skeleton: &Skeleton,
         deferred_gizmos: &mut DeferredGizmos,
     ) -> Result<Pose, GraphError> {
         context.push_caches();
@@ -825,14 +866,45 @@ impl AnimationGraph {
                 resources,
                 overlay,
                 root_entity,
-                entity_map,
+                skeleton,
                 deferred_gizmos,
-            ),
+            )
+            .with_frame_update(time_update),
         )?;
         let time = out.timestamp;
         let bone_frame: BonePoseFrame = out.data.unwrap();
 
         Ok(bone_frame.sample_linear_at(time))
     }
+
+    /// Returns the events of the output pose that were passed by playback in the last query,
+    /// that is, events with timestamps between the previous and the current output timestamp.
+    /// Absolute time updates are treated as jumps and do not pass any events.
+    pub fn passed_events(&self, time_update: TimeUpdate, context: &GraphContext) -> Vec<PoseEvent> {
+        let TimeUpdate::Delta(_) = time_update else {
+            return Vec::new();
+        };
+        let Some(source_pin) = self.edges.get(&TargetPin::OutputPose) else {
+            return Vec::new();
+        };
+        let Some(pose) = context.get_pose(source_pin) else {
+            return Vec::new();
+        };
+
+        let prev_time = context.get_prev_time(source_pin);
+        let curr_time = pose.timestamp;
+
+        pose.events
+            .iter()
+            .filter(|e| {
+                if curr_time >= prev_time {
+                    prev_time < e.timestamp && e.timestamp <= curr_time
+                } else {
+                    curr_time <= e.timestamp && e.timestamp < prev_time
+                }
+            })
+            .cloned()
+            .collect()
+    }
     // ----------------------------------------------------------------------------------------
 }
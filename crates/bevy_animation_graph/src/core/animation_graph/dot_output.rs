@@ -135,6 +135,9 @@ fn write_col(
                 ParamSpec::Quat => String::from("󰑵"),
                 ParamSpec::Vec3 => String::from("󰵉"),
                 ParamSpec::EntityPath => String::from("EntityPath"),
+                ParamSpec::Bool => String::from("Bool"),
+                ParamSpec::I32 => String::from("I32"),
+                ParamSpec::Enum => String::from("Enum"),
             };
 
             write!(
@@ -222,6 +225,9 @@ impl AsDotLabel for ParamValue {
             ParamValue::BoneMask(_) => "Bone Mask".to_string(),
             ParamValue::Vec3(v) => format!("{}", v),
             ParamValue::EntityPath(_) => "EntityPath".to_string(),
+            ParamValue::Bool(b) => format!("{}", b),
+            ParamValue::I32(i) => format!("{}", i),
+            ParamValue::Enum(variant) => variant.clone(),
         }
     }
 }
@@ -1,23 +1,37 @@
 use super::{
-    serial::{AnimationGraphSerial, AnimationNodeTypeSerial},
+    serial::{AnimationGraphSerial, AnimationNodeTypeSerial, StateSourceSerial},
     AnimationGraph,
 };
 use crate::{
-    core::{animation_clip::GraphClip, errors::AssetLoaderError},
+    core::{
+        animation_clip::{ClipEvent, EntityPath, GraphClip, Interpolation, SubClip, SyncMarker},
+        errors::AssetLoaderError,
+        node_registry::CustomNodeRegistry,
+    },
     nodes::{
         blend_node::BlendNode, chain_node::ChainNode, clip_node::ClipNode,
         flip_lr_node::FlipLRNode, loop_node::LoopNode, speed_node::SpeedNode, AbsF32, AddF32,
-        ClampF32, DivF32, GraphNode, MulF32,
+        AddVec3, AndBool, BlendSpace1DNode, BlendSpace2DNode, BuildVec3, ClampF32, CompareF32,
+        CompareI32, CrossVec3, DecomposeVec3, DivF32, DotVec3, EqualEnum, ExpressionNode,
+        GraphNode, InvertQuat, LengthVec3, LerpVec3, MulF32, MulQuat, NormalizeVec3, NotBool,
+        OrBool, QuatFromAxisAngle, QuatFromEuler, ScaleVec3, SelectNode, SlerpQuat, SmoothF32,
+        SmoothQuat, SmoothVec3, SubVec3, SwitchNode,
     },
     prelude::{
-        DummyNode, ExtendSkeleton, IntoBoneSpaceNode, IntoCharacterSpaceNode, IntoGlobalSpaceNode,
-        RotationArcNode, RotationNode, SubF32, TwoBoneIKNode,
+        AnimationNode, AnimationNodeType, AnimationState, ApplyAdditiveNode, DummyNode,
+        ExtendSkeleton, FootPlacementNode, IkChainNode, IntoBoneSpaceNode, IntoCharacterSpaceNode,
+        IntoGlobalSpaceNode, LayeredBlendNode, LookAtNode, MakeAdditiveNode, RootMotionNode,
+        RotationArcNode, RotationNode, StateMachineNode, StateSource, SubF32, TwoBoneIKNode,
     },
+    retargeting::RetargetMap,
+    utils::bvh::{Bvh, BvhError},
 };
 use bevy::{
-    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    core::Name,
+    ecs::world::{FromWorld, World},
     gltf::Gltf,
-    utils::BoxedFuture,
+    utils::{BoxedFuture, HashMap},
 };
 use serde::{Deserialize, Serialize};
 
@@ -27,11 +41,62 @@ pub enum GraphClipSource {
         path: String,
         animation_name: String,
     },
+    /// Motion capture data in a BVH file. Joints are mapped to entity paths by name, with the
+    /// BVH root joint placed under `root`.
+    Bvh {
+        path: String,
+        /// Path of the entity the BVH root joint is a child of
+        #[serde(default)]
+        root: Vec<String>,
+        /// Factor applied to joint positions, e.g. `0.01` for mocap recorded in centimeters
+        #[serde(default = "default_bvh_scale")]
+        scale: f32,
+    },
+    /// Another `*.anim.ron` clip, including its events and sync markers. A clip referencing
+    /// itself is rejected, but longer reference cycles between clips are not detected and must
+    /// be avoided.
+    Clip { path: String },
+    /// A section of another clip, resampled into a new clip
+    SubClip {
+        source: Box<GraphClipSource>,
+        /// Start of the section, in seconds
+        #[serde(default)]
+        start: f32,
+        /// End of the section, in seconds. Defaults to the end of the source clip
+        #[serde(default)]
+        end: Option<f32>,
+        #[serde(default)]
+        reverse: bool,
+        /// Duration at the end of the clip blended into the motion leading to its start, so
+        /// that it loops seamlessly. Zero disables blending
+        #[serde(default)]
+        loop_blend: f32,
+        /// Keyframes per second of the new clip
+        #[serde(default = "default_sample_rate")]
+        sample_rate: f32,
+    },
+    /// Another clip converted to a different skeleton with a `*.retarget.ron` retarget map
+    Retargeted {
+        source: Box<GraphClipSource>,
+        retarget_map: String,
+    },
+}
+
+fn default_bvh_scale() -> f32 {
+    1.
+}
+
+fn default_sample_rate() -> f32 {
+    30.
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GraphClipSerial {
     source: GraphClipSource,
+    #[serde(default)]
+    events: Vec<ClipEvent>,
+    #[serde(default)]
+    sync_markers: Vec<SyncMarker>,
 }
 
 #[derive(Default)]
@@ -53,34 +118,17 @@ impl AssetLoader for GraphClipLoader {
             reader.read_to_end(&mut bytes).await?;
             let serial: GraphClipSerial = ron::de::from_bytes(&bytes)?;
 
-            let bevy_clip = match serial.source {
-                GraphClipSource::GltfNamed {
-                    path,
-                    animation_name,
-                } => {
-                    let gltf_loaded_asset = load_context.load_direct(path).await?;
-                    let gltf: &Gltf = gltf_loaded_asset.get().unwrap();
-
-                    let Some(clip_handle) = gltf.named_animations.get(&animation_name) else {
-                        return Err(AssetLoaderError::GltfMissingLabel(animation_name));
-                    };
-
-                    let Some(clip_path) = clip_handle.path() else {
-                        return Err(AssetLoaderError::GltfMissingLabel(animation_name));
-                    };
-
-                    let clip_bevy: bevy::animation::AnimationClip = gltf_loaded_asset
-                        .get_labeled(clip_path.label_cow().unwrap())
-                        .unwrap()
-                        .get::<bevy::animation::AnimationClip>()
-                        .unwrap()
-                        .clone();
-
-                    clip_bevy
-                }
-            };
-
-            let clip_mine = GraphClip::from(bevy_clip);
+            let mut clip_mine = load_clip_source(serial.source, load_context).await?;
+            clip_mine.events.extend(serial.events);
+            for marker in serial.sync_markers {
+                clip_mine.add_sync_marker(marker.time, marker.name);
+            }
+            if let Some((a, b)) = clip_mine.id_collision() {
+                return Err(AssetLoaderError::BoneIdCollision(
+                    a.to_slashed_string(),
+                    b.to_slashed_string(),
+                ));
+            }
 
             Ok(clip_mine)
         })
@@ -91,8 +139,171 @@ impl AssetLoader for GraphClipLoader {
     }
 }
 
-#[derive(Default)]
-pub struct AnimationGraphLoader;
+/// Loads the clip described by a [`GraphClipSource`], loading any clips it depends on first.
+fn load_clip_source<'a>(
+    source: GraphClipSource,
+    load_context: &'a mut LoadContext,
+) -> BoxedFuture<'a, Result<GraphClip, AssetLoaderError>> {
+    Box::pin(async move {
+        let clip = match source {
+            GraphClipSource::GltfNamed {
+                path,
+                animation_name,
+            } => {
+                // Bevy's glTF loader discards the interpolation mode of animation samplers,
+                // so it is read from the glTF document directly
+                let gltf_bytes = load_context.read_asset_bytes(path.clone()).await?;
+                let document = gltf::Gltf::from_slice(&gltf_bytes)?;
+                let interpolations = gltf_interpolations(&document, &animation_name);
+
+                let gltf_loaded_asset = load_context.load_direct(path).await?;
+                let gltf: &Gltf = gltf_loaded_asset.get().unwrap();
+
+                let Some(clip_handle) = gltf.named_animations.get(&animation_name) else {
+                    return Err(AssetLoaderError::GltfMissingLabel(animation_name));
+                };
+
+                let Some(clip_path) = clip_handle.path() else {
+                    return Err(AssetLoaderError::GltfMissingLabel(animation_name));
+                };
+
+                let clip_bevy: bevy::animation::AnimationClip = gltf_loaded_asset
+                    .get_labeled(clip_path.label_cow().unwrap())
+                    .unwrap()
+                    .get::<bevy::animation::AnimationClip>()
+                    .unwrap()
+                    .clone();
+
+                let mut clip = GraphClip::from(clip_bevy);
+                for (path, interpolations) in interpolations {
+                    clip.set_interpolations(&path, interpolations);
+                }
+                clip
+            }
+            GraphClipSource::Bvh { path, root, scale } => {
+                let bvh_bytes = load_context.read_asset_bytes(path).await?;
+                let source = std::str::from_utf8(&bvh_bytes).map_err(|_| BvhError::InvalidUtf8)?;
+                let root = EntityPath {
+                    parts: root.into_iter().map(Name::new).collect(),
+                };
+                Bvh::parse(source)?.to_graph_clip(&root, scale)
+            }
+            GraphClipSource::Clip { path } => {
+                if AssetPath::parse(&path).path() == load_context.path() {
+                    return Err(AssetLoaderError::RecursiveClip(path));
+                }
+                let loaded_asset = load_context.load_direct(path.clone()).await?;
+                let Some(clip) = loaded_asset.get::<GraphClip>() else {
+                    return Err(AssetLoaderError::UnexpectedAssetType(path));
+                };
+                clip.clone()
+            }
+            GraphClipSource::SubClip {
+                source,
+                start,
+                end,
+                reverse,
+                loop_blend,
+                sample_rate,
+            } => load_clip_source(*source, load_context)
+                .await?
+                .sub_clip(&SubClip {
+                    start,
+                    end,
+                    reverse,
+                    loop_blend,
+                    sample_rate,
+                }),
+            GraphClipSource::Retargeted {
+                source,
+                retarget_map,
+            } => {
+                let clip = load_clip_source(*source, load_context).await?;
+                let loaded_asset = load_context.load_direct(retarget_map.clone()).await?;
+                let Some(retarget_map) = loaded_asset.get::<RetargetMap>() else {
+                    return Err(AssetLoaderError::UnexpectedAssetType(retarget_map));
+                };
+                retarget_map.retarget_clip(&clip)
+            }
+        };
+
+        Ok(clip)
+    })
+}
+
+/// Interpolation modes of the channels of a named glTF animation, grouped by target path in the
+/// same order in which Bevy's glTF loader adds them as curves.
+fn gltf_interpolations(
+    gltf: &gltf::Gltf,
+    animation_name: &str,
+) -> HashMap<EntityPath, Vec<Interpolation>> {
+    fn paths_recur(
+        node: gltf::Node,
+        current_path: &[Name],
+        paths: &mut HashMap<usize, EntityPath>,
+    ) {
+        let mut path = current_path.to_owned();
+        // Same naming as Bevy's glTF loader
+        path.push(Name::new(
+            node.name()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("GltfNode{}", node.index())),
+        ));
+        for child in node.children() {
+            paths_recur(child, &path, paths);
+        }
+        paths.insert(node.index(), EntityPath { parts: path });
+    }
+
+    let mut paths = HashMap::new();
+    for scene in gltf.scenes() {
+        for node in scene.nodes() {
+            paths_recur(node, &[], &mut paths);
+        }
+    }
+
+    let mut interpolations: HashMap<EntityPath, Vec<Interpolation>> = HashMap::new();
+    let Some(animation) = gltf
+        .animations()
+        .find(|animation| animation.name() == Some(animation_name))
+    else {
+        return interpolations;
+    };
+    for channel in animation.channels() {
+        // Bevy skips channels with sparse timestamps
+        if channel.sampler().input().sparse().is_some() {
+            continue;
+        }
+        let Some(path) = paths.get(&channel.target().node().index()) else {
+            continue;
+        };
+        let interpolation = match channel.sampler().interpolation() {
+            gltf::animation::Interpolation::Linear => Interpolation::Linear,
+            gltf::animation::Interpolation::Step => Interpolation::Step,
+            gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+        };
+        interpolations
+            .entry(path.clone())
+            .or_default()
+            .push(interpolation);
+    }
+
+    interpolations
+}
+
+pub struct AnimationGraphLoader {
+    custom_nodes: CustomNodeRegistry,
+}
+
+impl FromWorld for AnimationGraphLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            custom_nodes: world
+                .get_resource_or_insert_with(CustomNodeRegistry::default)
+                .clone(),
+        }
+    }
+}
 
 impl AssetLoader for AnimationGraphLoader {
     type Asset = AnimationGraph;
@@ -128,6 +339,18 @@ impl AssetLoader for AnimationGraphLoader {
                             .wrapped(&serial_node.name)
                     }
                     AnimationNodeTypeSerial::Blend => BlendNode::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::SyncedBlend(mode) => BlendNode::new()
+                        .with_sync_mode(*mode)
+                        .wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::LayeredBlend { propagate_weights } => {
+                        LayeredBlendNode::new(*propagate_weights).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::BlendSpace1D(samples) => {
+                        BlendSpace1DNode::new(samples.clone()).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::BlendSpace2D { samples, plane } => {
+                        BlendSpace2DNode::new(samples.clone(), *plane).wrapped(&serial_node.name)
+                    }
                     AnimationNodeTypeSerial::Chain => ChainNode::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::FlipLR { config } => {
                         FlipLRNode::new(config.clone()).wrapped(&serial_node.name)
@@ -138,15 +361,93 @@ impl AssetLoader for AnimationGraphLoader {
                         RotationNode::new(*mode, *space, *decay, *length, *base_weight)
                             .wrapped(&serial_node.name)
                     }
+                    AnimationNodeTypeSerial::MakeAdditive { reference_time } => {
+                        MakeAdditiveNode::new(*reference_time).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::ApplyAdditive => {
+                        ApplyAdditiveNode::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::RootMotion { translation, yaw } => {
+                        RootMotionNode::new(*translation, *yaw).wrapped(&serial_node.name)
+                    }
                     AnimationNodeTypeSerial::AddF32 => AddF32::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::SubF32 => SubF32::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::MulF32 => MulF32::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::DivF32 => DivF32::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::ClampF32 => ClampF32::new().wrapped(&serial_node.name),
                     AnimationNodeTypeSerial::AbsF32 => AbsF32::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::Expression(expression) => {
+                        ExpressionNode::new(expression.clone())
+                            .map_err(|err| {
+                                AssetLoaderError::InvalidExpression(serial_node.name.clone(), err)
+                            })?
+                            .wrapped(&serial_node.name)
+                    }
                     AnimationNodeTypeSerial::RotationArc => {
                         RotationArcNode::new().wrapped(&serial_node.name)
                     }
+                    AnimationNodeTypeSerial::AddVec3 => AddVec3::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::SubVec3 => SubVec3::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::ScaleVec3 => {
+                        ScaleVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::DotVec3 => DotVec3::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::CrossVec3 => {
+                        CrossVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::NormalizeVec3 => {
+                        NormalizeVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::LengthVec3 => {
+                        LengthVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::LerpVec3 => LerpVec3::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::BuildVec3 => {
+                        BuildVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::DecomposeVec3 => {
+                        DecomposeVec3::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::MulQuat => MulQuat::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::InvertQuat => {
+                        InvertQuat::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::SlerpQuat => {
+                        SlerpQuat::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::QuatFromAxisAngle => {
+                        QuatFromAxisAngle::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::QuatFromEuler(mode) => {
+                        QuatFromEuler::new(*mode).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::SmoothF32(smoothing) => {
+                        SmoothF32::new(*smoothing).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::SmoothVec3(smoothing) => {
+                        SmoothVec3::new(*smoothing).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::SmoothQuat(smoothing) => {
+                        SmoothQuat::new(*smoothing).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::AndBool => AndBool::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::OrBool => OrBool::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::NotBool => NotBool::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::CompareF32(comparison) => {
+                        CompareF32::new(*comparison).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::CompareI32(comparison) => {
+                        CompareI32::new(*comparison).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::EqualEnum => {
+                        EqualEnum::new().wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::Select(spec) => {
+                        SelectNode::new(*spec).wrapped(&serial_node.name)
+                    }
+                    AnimationNodeTypeSerial::Switch { spec, variants } => {
+                        SwitchNode::new(*spec, variants.clone()).wrapped(&serial_node.name)
+                    }
                     AnimationNodeTypeSerial::Graph(graph_name) => {
                         GraphNode::new(load_context.load(graph_name)).wrapped(&serial_node.name)
                     }
@@ -162,10 +463,58 @@ impl AssetLoader for AnimationGraphLoader {
                     AnimationNodeTypeSerial::TwoBoneIK => {
                         TwoBoneIKNode::new().wrapped(&serial_node.name)
                     }
+                    AnimationNodeTypeSerial::IkChain {
+                        solver,
+                        chain_length,
+                        max_iterations,
+                        tolerance,
+                    } => IkChainNode::new(*solver, *chain_length, *max_iterations, *tolerance)
+                        .wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::LookAt {
+                        axis,
+                        bone_weights,
+                        max_angle,
+                    } => LookAtNode::new(*axis, bone_weights.clone(), *max_angle)
+                        .wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::FootPlacement { max_pelvis_offset } => {
+                        FootPlacementNode::new(*max_pelvis_offset).wrapped(&serial_node.name)
+                    }
                     AnimationNodeTypeSerial::IntoGlobalSpace => {
                         IntoGlobalSpaceNode::new().wrapped(&serial_node.name)
                     }
                     AnimationNodeTypeSerial::Dummy => DummyNode::new().wrapped(&serial_node.name),
+                    AnimationNodeTypeSerial::Custom { type_path, data } => {
+                        let Some(registration) = self.custom_nodes.get(type_path) else {
+                            return Err(AssetLoaderError::UnregisteredCustomNode(
+                                type_path.clone(),
+                            ));
+                        };
+                        AnimationNode::new_from_nodetype(
+                            serial_node.name.clone(),
+                            AnimationNodeType::Custom(registration.deserialize(data)?),
+                        )
+                    }
+                    AnimationNodeTypeSerial::StateMachine {
+                        states,
+                        transitions,
+                        start_state,
+                    } => StateMachineNode::new(
+                        states
+                            .iter()
+                            .map(|s| AnimationState {
+                                id: s.id.clone(),
+                                source: match &s.source {
+                                    StateSourceSerial::PoseInput => StateSource::PoseInput,
+                                    StateSourceSerial::Graph(graph_name) => {
+                                        StateSource::Graph(load_context.load(graph_name))
+                                    }
+                                },
+                            })
+                            .collect(),
+                        transitions.clone(),
+                        start_state.clone(),
+                    )
+                    .wrapped(&serial_node.name),
                 };
                 graph.add_node(node);
             }
@@ -12,10 +12,8 @@ pub enum TargetPin<NodeId, PinId> {
 impl<NodeId: Eq, PinId> TargetPin<NodeId, PinId> {
     pub fn node_rename(&mut self, old_id: NodeId, new_id: NodeId) {
         match self {
-            Self::NodeParameter(id, _) | Self::NodePose(id, _) => {
-                if *id == old_id {
-                    *id = new_id;
-                }
+            Self::NodeParameter(id, _) | Self::NodePose(id, _) if *id == old_id => {
+                *id = new_id;
             }
             _ => (),
         }
@@ -38,10 +36,8 @@ pub enum SourcePin<NodeId, PinId> {
 impl<NodeId: Eq, PinId> SourcePin<NodeId, PinId> {
     pub fn node_rename(&mut self, old_id: NodeId, new_id: NodeId) {
         match self {
-            Self::NodeParameter(id, _) | Self::NodePose(id) => {
-                if *id == old_id {
-                    *id = new_id;
-                }
+            Self::NodeParameter(id, _) | Self::NodePose(id) if *id == old_id => {
+                *id = new_id;
             }
             _ => (),
         }
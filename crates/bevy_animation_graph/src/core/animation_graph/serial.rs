@@ -1,14 +1,19 @@
 use super::{pin, AnimationGraph, Extra};
 use crate::{
-    core::frame::PoseSpec,
+    core::{errors::GraphSerializationError, frame::PoseSpec},
     prelude::{
-        config::FlipConfig, AnimationNode, AnimationNodeType, ChainDecay, ParamSpec, ParamValue,
-        RotationMode, RotationSpace,
+        config::FlipConfig, AnimationNode, AnimationNodeType, BlendSample1D, BlendSample2D,
+        BlendSpacePlane, BlendSyncMode, ChainDecay, Comparison, IkSolver, ParamSpec, ParamValue,
+        RotationMode, RotationSpace, Smoothing, StateSource, Transition,
     },
     utils::ordered_map::OrderedMap,
 };
-use bevy::utils::HashMap;
+use bevy::{
+    math::{EulerRot, Vec3},
+    utils::HashMap,
+};
 use serde::{Deserialize, Serialize};
+use std::ops::Deref;
 //     pub nodes: HashMap<String, AnimationNode>,
 //     /// Inverted, indexed by output node name.
 //     pub edges: HashMap<TargetPin, SourcePin>,
@@ -57,6 +62,17 @@ pub struct AnimationNodeSerial {
 pub enum AnimationNodeTypeSerial {
     Clip(String, Option<f32>),
     Blend,
+    SyncedBlend(BlendSyncMode),
+    LayeredBlend {
+        #[serde(default)]
+        propagate_weights: bool,
+    },
+    BlendSpace1D(Vec<BlendSample1D>),
+    BlendSpace2D {
+        samples: Vec<BlendSample2D>,
+        #[serde(default)]
+        plane: BlendSpacePlane,
+    },
     Chain,
     FlipLR {
         #[serde(default)]
@@ -71,26 +87,113 @@ pub enum AnimationNodeTypeSerial {
         usize,
         #[serde(default)] f32,
     ),
+    MakeAdditive {
+        #[serde(default)]
+        reference_time: f32,
+    },
+    ApplyAdditive,
+    RootMotion {
+        translation: bool,
+        yaw: bool,
+    },
     AddF32,
     SubF32,
     MulF32,
     DivF32,
     ClampF32,
     AbsF32,
+    Expression(String),
     RotationArc,
+    AddVec3,
+    SubVec3,
+    ScaleVec3,
+    DotVec3,
+    CrossVec3,
+    NormalizeVec3,
+    LengthVec3,
+    LerpVec3,
+    BuildVec3,
+    DecomposeVec3,
+    MulQuat,
+    InvertQuat,
+    SlerpQuat,
+    QuatFromAxisAngle,
+    QuatFromEuler(EulerRot),
+    SmoothF32(Smoothing),
+    SmoothVec3(Smoothing),
+    SmoothQuat(Smoothing),
+    AndBool,
+    OrBool,
+    NotBool,
+    CompareF32(Comparison),
+    CompareI32(Comparison),
+    EqualEnum,
+    Select(ParamSpec),
+    Switch {
+        spec: ParamSpec,
+        variants: Vec<String>,
+    },
     IntoBoneSpace,
     IntoCharacterSpace,
     IntoGlobalSpace,
     ExtendSkeleton,
     TwoBoneIK,
+    IkChain {
+        #[serde(default)]
+        solver: IkSolver,
+        chain_length: usize,
+        max_iterations: usize,
+        tolerance: f32,
+    },
+    LookAt {
+        axis: Vec3,
+        bone_weights: Vec<f32>,
+        max_angle: f32,
+    },
+    FootPlacement {
+        max_pelvis_offset: f32,
+    },
     Dummy,
     Graph(String),
+    /// Node registered in the
+    /// [`CustomNodeRegistry`](crate::core::node_registry::CustomNodeRegistry), with its data
+    /// serialized as a RON string
+    Custom {
+        type_path: String,
+        data: String,
+    },
+    StateMachine {
+        states: Vec<StateSerial>,
+        #[serde(default)]
+        transitions: Vec<Transition>,
+        start_state: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StateSerial {
+    pub id: String,
+    #[serde(default)]
+    pub source: StateSourceSerial,
 }
 
-impl From<&AnimationGraph> for AnimationGraphSerial {
-    fn from(value: &AnimationGraph) -> Self {
-        Self {
-            nodes: value.nodes.values().map(|v| v.into()).collect(),
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub enum StateSourceSerial {
+    #[default]
+    PoseInput,
+    Graph(String),
+}
+
+impl TryFrom<&AnimationGraph> for AnimationGraphSerial {
+    type Error = GraphSerializationError;
+
+    fn try_from(value: &AnimationGraph) -> Result<Self, Self::Error> {
+        Ok(Self {
+            nodes: value
+                .nodes
+                .values()
+                .map(|v| v.try_into())
+                .collect::<Result<_, _>>()?,
             edges_inverted: value
                 .edges
                 .clone()
@@ -114,27 +217,46 @@ impl From<&AnimationGraph> for AnimationGraphSerial {
                 .collect(),
             output_pose: value.output_pose,
             extra: value.extra.clone(),
-        }
+        })
     }
 }
 
-impl From<&AnimationNode> for AnimationNodeSerial {
-    fn from(value: &AnimationNode) -> Self {
-        Self {
+impl TryFrom<&AnimationNode> for AnimationNodeSerial {
+    type Error = GraphSerializationError;
+
+    fn try_from(value: &AnimationNode) -> Result<Self, Self::Error> {
+        Ok(Self {
             name: value.name.clone(),
-            node: (&value.node).into(),
-        }
+            node: (&value.node).try_into().map_err(|err| {
+                GraphSerializationError::NodeError(value.name.clone(), Box::new(err))
+            })?,
+        })
     }
 }
 
-impl From<&AnimationNodeType> for AnimationNodeTypeSerial {
-    fn from(value: &AnimationNodeType) -> Self {
-        match value {
+impl TryFrom<&AnimationNodeType> for AnimationNodeTypeSerial {
+    type Error = GraphSerializationError;
+
+    fn try_from(value: &AnimationNodeType) -> Result<Self, Self::Error> {
+        Ok(match value {
             AnimationNodeType::Clip(n) => AnimationNodeTypeSerial::Clip(
                 n.clip.path().unwrap().to_string(),
                 n.override_duration,
             ),
-            AnimationNodeType::Blend(_) => AnimationNodeTypeSerial::Blend,
+            AnimationNodeType::Blend(n) => match n.sync_mode {
+                BlendSyncMode::Disabled => AnimationNodeTypeSerial::Blend,
+                mode => AnimationNodeTypeSerial::SyncedBlend(mode),
+            },
+            AnimationNodeType::LayeredBlend(n) => AnimationNodeTypeSerial::LayeredBlend {
+                propagate_weights: n.propagate_weights,
+            },
+            AnimationNodeType::BlendSpace1D(n) => {
+                AnimationNodeTypeSerial::BlendSpace1D(n.samples.clone())
+            }
+            AnimationNodeType::BlendSpace2D(n) => AnimationNodeTypeSerial::BlendSpace2D {
+                samples: n.samples.clone(),
+                plane: n.plane,
+            },
             AnimationNodeType::Chain(_) => AnimationNodeTypeSerial::Chain,
             AnimationNodeType::FlipLR(n) => AnimationNodeTypeSerial::FlipLR {
                 config: n.config.clone(),
@@ -148,23 +270,102 @@ impl From<&AnimationNodeType> for AnimationNodeTypeSerial {
                 n.chain_length,
                 n.base_weight,
             ),
+            AnimationNodeType::MakeAdditive(n) => AnimationNodeTypeSerial::MakeAdditive {
+                reference_time: n.reference_time,
+            },
+            AnimationNodeType::ApplyAdditive(_) => AnimationNodeTypeSerial::ApplyAdditive,
+            AnimationNodeType::RootMotion(n) => AnimationNodeTypeSerial::RootMotion {
+                translation: n.translation,
+                yaw: n.yaw,
+            },
             AnimationNodeType::IntoBoneSpace(_) => AnimationNodeTypeSerial::IntoBoneSpace,
             AnimationNodeType::IntoCharacterSpace(_) => AnimationNodeTypeSerial::IntoCharacterSpace,
             AnimationNodeType::IntoGlobalSpace(_) => AnimationNodeTypeSerial::IntoGlobalSpace,
             AnimationNodeType::ExtendSkeleton(_) => AnimationNodeTypeSerial::ExtendSkeleton,
             AnimationNodeType::TwoBoneIK(_) => AnimationNodeTypeSerial::TwoBoneIK,
+            AnimationNodeType::IkChain(n) => AnimationNodeTypeSerial::IkChain {
+                solver: n.solver,
+                chain_length: n.chain_length,
+                max_iterations: n.max_iterations,
+                tolerance: n.tolerance,
+            },
+            AnimationNodeType::LookAt(n) => AnimationNodeTypeSerial::LookAt {
+                axis: n.axis,
+                bone_weights: n.bone_weights.clone(),
+                max_angle: n.max_angle,
+            },
+            AnimationNodeType::FootPlacement(n) => AnimationNodeTypeSerial::FootPlacement {
+                max_pelvis_offset: n.max_pelvis_offset,
+            },
             AnimationNodeType::AddF32(_) => AnimationNodeTypeSerial::AddF32,
             AnimationNodeType::MulF32(_) => AnimationNodeTypeSerial::MulF32,
             AnimationNodeType::DivF32(_) => AnimationNodeTypeSerial::DivF32,
             AnimationNodeType::SubF32(_) => AnimationNodeTypeSerial::SubF32,
             AnimationNodeType::ClampF32(_) => AnimationNodeTypeSerial::ClampF32,
             AnimationNodeType::AbsF32(_) => AnimationNodeTypeSerial::AbsF32,
+            AnimationNodeType::Expression(n) => {
+                AnimationNodeTypeSerial::Expression(n.expression.clone())
+            }
             AnimationNodeType::RotationArc(_) => AnimationNodeTypeSerial::RotationArc,
+            AnimationNodeType::AddVec3(_) => AnimationNodeTypeSerial::AddVec3,
+            AnimationNodeType::SubVec3(_) => AnimationNodeTypeSerial::SubVec3,
+            AnimationNodeType::ScaleVec3(_) => AnimationNodeTypeSerial::ScaleVec3,
+            AnimationNodeType::DotVec3(_) => AnimationNodeTypeSerial::DotVec3,
+            AnimationNodeType::CrossVec3(_) => AnimationNodeTypeSerial::CrossVec3,
+            AnimationNodeType::NormalizeVec3(_) => AnimationNodeTypeSerial::NormalizeVec3,
+            AnimationNodeType::LengthVec3(_) => AnimationNodeTypeSerial::LengthVec3,
+            AnimationNodeType::LerpVec3(_) => AnimationNodeTypeSerial::LerpVec3,
+            AnimationNodeType::BuildVec3(_) => AnimationNodeTypeSerial::BuildVec3,
+            AnimationNodeType::DecomposeVec3(_) => AnimationNodeTypeSerial::DecomposeVec3,
+            AnimationNodeType::MulQuat(_) => AnimationNodeTypeSerial::MulQuat,
+            AnimationNodeType::InvertQuat(_) => AnimationNodeTypeSerial::InvertQuat,
+            AnimationNodeType::SlerpQuat(_) => AnimationNodeTypeSerial::SlerpQuat,
+            AnimationNodeType::QuatFromAxisAngle(_) => AnimationNodeTypeSerial::QuatFromAxisAngle,
+            AnimationNodeType::QuatFromEuler(n) => AnimationNodeTypeSerial::QuatFromEuler(n.mode),
+            AnimationNodeType::SmoothF32(n) => AnimationNodeTypeSerial::SmoothF32(n.smoothing),
+            AnimationNodeType::SmoothVec3(n) => AnimationNodeTypeSerial::SmoothVec3(n.smoothing),
+            AnimationNodeType::SmoothQuat(n) => AnimationNodeTypeSerial::SmoothQuat(n.smoothing),
+            AnimationNodeType::AndBool(_) => AnimationNodeTypeSerial::AndBool,
+            AnimationNodeType::OrBool(_) => AnimationNodeTypeSerial::OrBool,
+            AnimationNodeType::NotBool(_) => AnimationNodeTypeSerial::NotBool,
+            AnimationNodeType::CompareF32(n) => AnimationNodeTypeSerial::CompareF32(n.comparison),
+            AnimationNodeType::CompareI32(n) => AnimationNodeTypeSerial::CompareI32(n.comparison),
+            AnimationNodeType::EqualEnum(_) => AnimationNodeTypeSerial::EqualEnum,
+            AnimationNodeType::Select(n) => AnimationNodeTypeSerial::Select(n.spec),
+            AnimationNodeType::Switch(n) => AnimationNodeTypeSerial::Switch {
+                spec: n.spec,
+                variants: n.variants.clone(),
+            },
             AnimationNodeType::Dummy(_) => AnimationNodeTypeSerial::Dummy,
             AnimationNodeType::Graph(n) => {
                 AnimationNodeTypeSerial::Graph(n.graph.path().unwrap().to_string())
             }
-            AnimationNodeType::Custom(_) => panic!("Cannot serialize custom node!"),
-        }
+            AnimationNodeType::StateMachine(n) => AnimationNodeTypeSerial::StateMachine {
+                states: n
+                    .states
+                    .iter()
+                    .map(|s| StateSerial {
+                        id: s.id.clone(),
+                        source: match &s.source {
+                            StateSource::PoseInput => StateSourceSerial::PoseInput,
+                            StateSource::Graph(graph) => {
+                                StateSourceSerial::Graph(graph.path().unwrap().to_string())
+                            }
+                        },
+                    })
+                    .collect(),
+                transitions: n.transitions.clone(),
+                start_state: n.start_state.clone(),
+            },
+            AnimationNodeType::Custom(n) => {
+                let registration = n
+                    .registration
+                    .ok_or(GraphSerializationError::UnregisteredCustomNode)?;
+                AnimationNodeTypeSerial::Custom {
+                    type_path: registration.type_path().into(),
+                    data: registration.serialize(n.node.lock().unwrap().deref())?,
+                }
+            }
+        })
     }
 }
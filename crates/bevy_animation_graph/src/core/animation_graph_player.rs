@@ -1,15 +1,33 @@
 use super::{
+    animation_clip::EntityPath,
     animation_graph::{AnimationGraph, InputOverlay, TimeState, TimeUpdate},
-    context::{BoneDebugGizmos, DeferredGizmos, PassContext},
+    context::{compose_motion, BoneDebugGizmos, DeferredGizmos, PassContext},
     errors::GraphError,
+    frame::PoseEvent,
     parameters::ParamValue,
     pose::{BoneId, Pose},
+    skeleton::Skeleton,
+};
+use crate::{
+    interpolation::inertialization::Inertialization,
+    prelude::{GraphContext, StateMachineState, SystemResources},
 };
-use crate::prelude::{GraphContext, SystemResources};
 use bevy::{
-    asset::prelude::*, ecs::prelude::*, reflect::prelude::*, render::color::Color, utils::HashMap,
+    asset::prelude::*, ecs::prelude::*, reflect::prelude::*, render::color::Color,
+    transform::prelude::*,
 };
 
+/// Event sent by the animation player whenever playback passes an event declared in an
+/// animation clip.
+#[derive(Event, Clone, Debug)]
+pub struct AnimationEvent {
+    /// Entity with the [`AnimationGraphPlayer`] that played the animation
+    pub entity: Entity,
+    pub name: String,
+    /// Path of nodes (from the outermost graph inwards) leading to the node that produced the event
+    pub node_path: Vec<String>,
+}
+
 /// Animation controls
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
@@ -21,9 +39,34 @@ pub struct AnimationGraphPlayer {
     pub(crate) context: GraphContext,
     pub(crate) deferred_gizmos: DeferredGizmos,
     pub(crate) debug_draw_bones: Vec<BoneId>,
-    pub(crate) entity_map: HashMap<BoneId, Entity>,
+    /// Bone hierarchy under the player, rebuilt only when it changes
+    #[reflect(ignore)]
+    pub(crate) skeleton: Skeleton,
+    pub(crate) skeleton_up_to_date: bool,
+    /// Events passed by playback in the last frame, waiting to be sent
+    pub(crate) passed_events: Vec<PoseEvent>,
+    /// Duration of the inertialization used when starting a new animation graph
+    pub(crate) start_inertialization: f32,
+    /// Whether the player keeps its last output poses so that [`AnimationGraphPlayer::inertialize`]
+    /// can be used at any time
+    pub(crate) inertialization_enabled: bool,
+    /// Whether the extracted root motion is applied to the `Transform` of the player entity
+    pub(crate) apply_root_motion: bool,
+    /// Root motion extracted in the last frame
+    pub(crate) root_motion: Transform,
+    /// Root motion accumulated since the last call to [`AnimationGraphPlayer::take_root_motion`]
+    accumulated_root_motion: Transform,
 
     input_overlay: InputOverlay,
+    /// Inertialization requested from outside of the graph, applied in the next frame
+    pending_inertialization: Option<f32>,
+    #[reflect(ignore)]
+    inertialization: Option<Inertialization>,
+    /// Last two output poses, used to compute the offsets when inertialization starts. Only kept
+    /// while inertialization is enabled on the player or in the graph.
+    last_pose: Option<Pose>,
+    prev_last_pose: Option<Pose>,
+    last_delta: f32,
     /// Error that ocurred during graph evaluation in the last frame
     #[reflect(ignore)]
     error: Option<GraphError>,
@@ -43,6 +86,28 @@ impl AnimationGraphPlayer {
         self
     }
 
+    /// Use inertialization over the given duration (in seconds) when starting a new animation
+    /// graph with [`AnimationGraphPlayer::start`]. A duration of zero disables it.
+    pub fn with_start_inertialization(mut self, duration: f32) -> Self {
+        self.start_inertialization = duration;
+        self
+    }
+
+    /// Keep the last output poses, so that [`AnimationGraphPlayer::inertialize`] can be used at
+    /// any time. This is not needed for start inertialization nor for state machine transitions
+    /// using inertialization.
+    pub fn with_inertialization_enabled(mut self, enabled: bool) -> Self {
+        self.inertialization_enabled = enabled;
+        self
+    }
+
+    /// Apply the root motion extracted by the graph to the `Transform` of the entity with the
+    /// player every frame.
+    pub fn with_root_motion_applied(mut self, apply: bool) -> Self {
+        self.apply_root_motion = apply;
+        self
+    }
+
     /// Clear all input parameters for the animation graph
     pub fn clear_input_parameters(&mut self) {
         self.input_overlay.clear();
@@ -61,11 +126,29 @@ impl AnimationGraphPlayer {
     }
 
     /// Start playing an animation, resetting state of the player.
-    /// This will use a linear blending between the previous and the new animation to make a smooth transition.
+    /// If a start inertialization duration was configured, the transition from the previous
+    /// animation will be smoothed using inertialization.
     pub fn start(&mut self, handle: Handle<AnimationGraph>) -> &mut Self {
         self.animation = Some(handle);
         self.elapsed = TimeState::default();
+        self.context = GraphContext::default();
         self.paused = false;
+        if self.start_inertialization > 0. {
+            self.inertialize(self.start_inertialization);
+        }
+        self
+    }
+
+    /// Smooth out the next change in the output pose (e.g. after switching an input parameter
+    /// that selects a different animation) using inertialization over the given duration.
+    ///
+    /// This has no effect unless the player keeps its last output poses, see
+    /// [`AnimationGraphPlayer::with_inertialization_enabled`].
+    pub fn inertialize(&mut self, duration: f32) -> &mut Self {
+        self.pending_inertialization = Some(
+            self.pending_inertialization
+                .map_or(duration, |current| current.max(duration)),
+        );
         self
     }
 
@@ -75,25 +158,23 @@ impl AnimationGraphPlayer {
         system_resources: &SystemResources,
         root_entity: Entity,
     ) -> Option<Pose> {
-        let Some(graph_handle) = &self.animation else {
-            return None;
-        };
+        let graph_handle = self.animation.as_ref()?;
 
-        let Some(graph) = system_resources.animation_graph_assets.get(graph_handle) else {
-            return None;
-        };
+        let graph = system_resources.animation_graph_assets.get(graph_handle)?;
 
-        let pose = match graph.query_with_overlay(
+        let mut pose = match graph.query_with_overlay(
             self.elapsed.update,
             &mut self.context,
             system_resources,
             &self.input_overlay,
             root_entity,
-            &self.entity_map,
+            &self.skeleton,
             &mut self.deferred_gizmos,
         ) {
             Ok(pose) => {
                 self.error = None;
+                self.passed_events
+                    .extend(graph.passed_events(self.elapsed.update, &self.context));
                 pose
             }
             Err(error) => {
@@ -102,9 +183,69 @@ impl AnimationGraphPlayer {
             }
         };
 
+        self.update_inertialization(&mut pose);
+
+        self.root_motion = self
+            .context
+            .take_root_motion()
+            .unwrap_or(Transform::IDENTITY);
+        self.accumulated_root_motion =
+            compose_motion(self.accumulated_root_motion, self.root_motion);
+
         Some(pose)
     }
 
+    /// Starts inertialization if it was requested either by the graph or by the user, and applies
+    /// the current inertialization offsets to the output pose.
+    fn update_inertialization(&mut self, pose: &mut Pose) {
+        let delta = match self.elapsed.update {
+            TimeUpdate::Delta(dt) => dt,
+            TimeUpdate::Absolute(_) => 0.,
+        };
+
+        let enabled = self.inertialization_enabled
+            | (self.start_inertialization > 0.)
+            | self.context.take_inertialization_enabled();
+
+        let request = self
+            .pending_inertialization
+            .take()
+            .into_iter()
+            .chain(self.context.take_inertialization_request())
+            .reduce(f32::max);
+
+        if let (Some(duration), Some(last_pose)) = (request, &self.last_pose) {
+            self.inertialization = Some(Inertialization::new(
+                last_pose,
+                self.prev_last_pose.as_ref(),
+                self.last_delta,
+                pose,
+                duration,
+            ));
+        } else if let Some(inertialization) = &mut self.inertialization {
+            inertialization.advance(delta);
+        }
+
+        if let Some(inertialization) = &self.inertialization {
+            inertialization.apply(pose);
+            if inertialization.is_finished() {
+                self.inertialization = None;
+            }
+        }
+
+        if enabled {
+            std::mem::swap(&mut self.last_pose, &mut self.prev_last_pose);
+            match &mut self.last_pose {
+                Some(last_pose) => last_pose.clone_from(pose),
+                None => self.last_pose = Some(pose.clone()),
+            }
+        } else {
+            self.last_pose = None;
+            self.prev_last_pose = None;
+        }
+        self.last_delta = delta;
+    }
+
     pub fn get_pass_context<'a>(
         &'a mut self,
         system_resources: &'a SystemResources,
@@ -115,13 +256,14 @@ impl AnimationGraphPlayer {
             system_resources,
             &self.input_overlay,
             root_entity,
-            &self.entity_map,
+            &self.skeleton,
             &mut self.deferred_gizmos,
         )
     }
 
-    pub fn gizmo_for_bones(&mut self, bones: impl IntoIterator<Item = BoneId>) {
-        self.debug_draw_bones.extend(bones);
+    pub fn gizmo_for_bones(&mut self, bones: impl IntoIterator<Item = EntityPath>) {
+        self.debug_draw_bones
+            .extend(bones.into_iter().map(|path| path.id()));
     }
 
     pub(crate) fn debug_draw_bones(
@@ -165,6 +307,34 @@ impl AnimationGraphPlayer {
         self.animation.clone()
     }
 
+    /// Return the active state of the state machine node with the given name in the animation
+    /// graph, or `None` if there is no such node or it has not been evaluated yet.
+    pub fn get_active_state(&self, node_id: &str) -> Option<&str> {
+        self.context
+            .get_node_state::<StateMachineState>(node_id)
+            .map(|s| s.active_state.as_str())
+    }
+
+    /// Root motion extracted by the graph in the last frame, relative to the character.
+    pub fn root_motion(&self) -> Transform {
+        self.root_motion
+    }
+
+    /// Returns the root motion accumulated since the last call to this function, and resets it.
+    pub fn take_root_motion(&mut self) -> Transform {
+        std::mem::replace(&mut self.accumulated_root_motion, Transform::IDENTITY)
+    }
+
+    pub fn set_apply_root_motion(&mut self, apply: bool) -> &mut Self {
+        self.apply_root_motion = apply;
+        self
+    }
+
+    pub fn set_inertialization_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.inertialization_enabled = enabled;
+        self
+    }
+
     /// If graph evaluation produced an error in the last frame return the error, otherwise return
     /// `None`.
     pub fn get_error(&self) -> Option<GraphError> {
@@ -1,18 +1,28 @@
 use super::{
+    animation_clip::SyncMarker,
     animation_graph::{PinId, PinMap, TimeUpdate},
     duration_data::DurationData,
     errors::GraphError,
     frame::{PoseFrame, PoseSpec},
+    node_registry::{CustomNodeRegistration, SerializableNode},
     parameters::{OptParamSpec, ParamSpec, ParamValue},
 };
 use crate::{
     nodes::{
         blend_node::BlendNode, chain_node::ChainNode, clip_node::ClipNode, dummy_node::DummyNode,
         flip_lr_node::FlipLRNode, loop_node::LoopNode, speed_node::SpeedNode, AbsF32, AddF32,
-        ClampF32, DivF32, ExtendSkeleton, GraphNode, IntoCharacterSpaceNode, MulF32,
-        RotationArcNode, RotationNode, SubF32,
+        AddVec3, AndBool, ApplyAdditiveNode, BlendSpace1DNode, BlendSpace2DNode, BuildVec3,
+        ClampF32, CompareF32, CompareI32, CrossVec3, DecomposeVec3, DivF32, DotVec3, EqualEnum,
+        ExpressionNode, ExtendSkeleton, GraphNode, IntoCharacterSpaceNode, InvertQuat,
+        LayeredBlendNode, LengthVec3, LerpVec3, MakeAdditiveNode, MulF32, MulQuat, NormalizeVec3,
+        NotBool, OrBool, QuatFromAxisAngle, QuatFromEuler, RootMotionNode, RotationArcNode,
+        RotationNode, ScaleVec3, SelectNode, SlerpQuat, SmoothF32, SmoothQuat, SmoothVec3,
+        StateMachineNode, SubF32, SubVec3, SwitchNode,
+    },
+    prelude::{
+        FootPlacementNode, IkChainNode, IntoBoneSpaceNode, IntoGlobalSpaceNode, LookAtNode,
+        PassContext, SpecContext, TwoBoneIKNode,
     },
-    prelude::{IntoBoneSpaceNode, IntoGlobalSpaceNode, PassContext, SpecContext, TwoBoneIKNode},
 };
 use bevy::{reflect::prelude::*, utils::HashMap};
 use std::{
@@ -37,6 +47,12 @@ pub trait NodeLike: Send + Sync + Reflect {
         Ok(None)
     }
 
+    /// Sync markers of the output pose, in the output pose's timeline. Used to align the inputs
+    /// of synchronized blends. Nodes that don't know about sync markers return none.
+    fn sync_markers_pass(&self, _ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        Ok(Vec::new())
+    }
+
     fn parameter_input_spec(&self, _ctx: SpecContext) -> PinMap<OptParamSpec> {
         PinMap::new()
     }
@@ -58,23 +74,55 @@ pub trait NodeLike: Send + Sync + Reflect {
     fn display_name(&self) -> String;
 }
 
-#[derive(Clone)]
 pub struct CustomNode {
     pub node: Arc<Mutex<dyn NodeLike>>,
+    /// Registration of the node type, needed to save the node and to inspect it. Only available
+    /// for nodes created with [`CustomNode::registered`].
+    pub registration: Option<CustomNodeRegistration>,
 }
 
 impl CustomNode {
     pub fn new(node: impl NodeLike + 'static) -> Self {
         Self {
             node: Arc::new(Mutex::new(node)),
+            registration: None,
         }
     }
+
+    /// Create a custom node that can be saved to animation graph files. The node type should also
+    /// be registered in the [`CustomNodeRegistry`](super::node_registry::CustomNodeRegistry) so
+    /// that it can be loaded back.
+    pub fn registered<T: SerializableNode>(node: T) -> Self {
+        Self {
+            node: Arc::new(Mutex::new(node)),
+            registration: Some(CustomNodeRegistration::of::<T>()),
+        }
+    }
+
+    /// Returns the inner node for editing, or `None` if it is shared with other graphs and
+    /// cannot be mutated on its own.
+    pub fn inner_reflect(&mut self) -> Option<&mut dyn Reflect> {
+        Arc::get_mut(&mut self.node).map(|node| node.get_mut().unwrap().as_reflect_mut())
+    }
+}
+
+impl Clone for CustomNode {
+    /// Registered custom nodes are deep copied, other custom nodes share the inner node.
+    fn clone(&self) -> Self {
+        self.registration
+            .and_then(|registration| registration.clone_node(self.node.lock().unwrap().deref()))
+            .unwrap_or_else(|| Self {
+                node: self.node.clone(),
+                registration: self.registration,
+            })
+    }
 }
 
 impl Default for CustomNode {
     fn default() -> Self {
         Self {
             node: Arc::new(Mutex::new(DummyNode::new())),
+            registration: None,
         }
     }
 }
@@ -120,6 +168,10 @@ impl NodeLike for AnimationNode {
         self.node.map(|n| n.pose_pass(input, ctx))
     }
 
+    fn sync_markers_pass(&self, ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        self.node.map(|n| n.sync_markers_pass(ctx))
+    }
+
     fn parameter_input_spec(&self, ctx: SpecContext) -> PinMap<OptParamSpec> {
         self.node.map(|n| n.parameter_input_spec(ctx))
     }
@@ -153,11 +205,17 @@ pub enum AnimationNodeType {
     // ------------------------------------------------
     Clip(ClipNode),
     Blend(BlendNode),
+    LayeredBlend(LayeredBlendNode),
+    BlendSpace1D(BlendSpace1DNode),
+    BlendSpace2D(BlendSpace2DNode),
     Chain(ChainNode),
     FlipLR(FlipLRNode),
     Loop(LoopNode),
     Speed(SpeedNode),
     Rotation(RotationNode),
+    MakeAdditive(MakeAdditiveNode),
+    ApplyAdditive(ApplyAdditiveNode),
+    RootMotion(RootMotionNode),
     // ------------------------------------------------
 
     // --- Pose space conversion
@@ -171,6 +229,9 @@ pub enum AnimationNodeType {
     // --- IK space conversion
     // ------------------------------------------------
     TwoBoneIK(TwoBoneIKNode),
+    IkChain(IkChainNode),
+    LookAt(LookAtNode),
+    FootPlacement(FootPlacementNode),
     // ------------------------------------------------
 
     // --- F32 arithmetic nodes
@@ -181,16 +242,56 @@ pub enum AnimationNodeType {
     SubF32(SubF32),
     ClampF32(ClampF32),
     AbsF32(AbsF32),
+    Expression(ExpressionNode),
     // ------------------------------------------------
 
     // --- Vec3 arithmetic nodes
     // ------------------------------------------------
     RotationArc(RotationArcNode),
+    AddVec3(AddVec3),
+    SubVec3(SubVec3),
+    ScaleVec3(ScaleVec3),
+    DotVec3(DotVec3),
+    CrossVec3(CrossVec3),
+    NormalizeVec3(NormalizeVec3),
+    LengthVec3(LengthVec3),
+    LerpVec3(LerpVec3),
+    BuildVec3(BuildVec3),
+    DecomposeVec3(DecomposeVec3),
+    // ------------------------------------------------
+
+    // --- Quat arithmetic nodes
+    // ------------------------------------------------
+    MulQuat(MulQuat),
+    InvertQuat(InvertQuat),
+    SlerpQuat(SlerpQuat),
+    QuatFromAxisAngle(QuatFromAxisAngle),
+    QuatFromEuler(QuatFromEuler),
+    // ------------------------------------------------
+
+    // --- Logic nodes
+    // ------------------------------------------------
+    AndBool(AndBool),
+    OrBool(OrBool),
+    NotBool(NotBool),
+    CompareF32(CompareF32),
+    CompareI32(CompareI32),
+    EqualEnum(EqualEnum),
+    Select(SelectNode),
+    Switch(SwitchNode),
+    // ------------------------------------------------
+
+    // --- Smoothing nodes
+    // ------------------------------------------------
+    SmoothF32(SmoothF32),
+    SmoothVec3(SmoothVec3),
+    SmoothQuat(SmoothQuat),
     // ------------------------------------------------
     // HACK: needs to be ignored for now due to:
     // https://github.com/bevyengine/bevy/issues/8965
     // Recursive reference causes reflection to fail
     Graph(#[reflect(ignore)] GraphNode),
+    StateMachine(#[reflect(ignore)] StateMachineNode),
     Custom(#[reflect(ignore)] CustomNode),
 }
 
@@ -208,24 +309,61 @@ impl AnimationNodeType {
         match self {
             AnimationNodeType::Clip(n) => f(n),
             AnimationNodeType::Blend(n) => f(n),
+            AnimationNodeType::LayeredBlend(n) => f(n),
+            AnimationNodeType::BlendSpace1D(n) => f(n),
+            AnimationNodeType::BlendSpace2D(n) => f(n),
             AnimationNodeType::Chain(n) => f(n),
             AnimationNodeType::FlipLR(n) => f(n),
             AnimationNodeType::Loop(n) => f(n),
             AnimationNodeType::Speed(n) => f(n),
             AnimationNodeType::Rotation(n) => f(n),
+            AnimationNodeType::MakeAdditive(n) => f(n),
+            AnimationNodeType::ApplyAdditive(n) => f(n),
+            AnimationNodeType::RootMotion(n) => f(n),
             AnimationNodeType::AddF32(n) => f(n),
             AnimationNodeType::MulF32(n) => f(n),
             AnimationNodeType::DivF32(n) => f(n),
             AnimationNodeType::SubF32(n) => f(n),
             AnimationNodeType::ClampF32(n) => f(n),
             AnimationNodeType::AbsF32(n) => f(n),
+            AnimationNodeType::Expression(n) => f(n),
             AnimationNodeType::RotationArc(n) => f(n),
+            AnimationNodeType::AddVec3(n) => f(n),
+            AnimationNodeType::SubVec3(n) => f(n),
+            AnimationNodeType::ScaleVec3(n) => f(n),
+            AnimationNodeType::DotVec3(n) => f(n),
+            AnimationNodeType::CrossVec3(n) => f(n),
+            AnimationNodeType::NormalizeVec3(n) => f(n),
+            AnimationNodeType::LengthVec3(n) => f(n),
+            AnimationNodeType::LerpVec3(n) => f(n),
+            AnimationNodeType::BuildVec3(n) => f(n),
+            AnimationNodeType::DecomposeVec3(n) => f(n),
+            AnimationNodeType::MulQuat(n) => f(n),
+            AnimationNodeType::InvertQuat(n) => f(n),
+            AnimationNodeType::SlerpQuat(n) => f(n),
+            AnimationNodeType::QuatFromAxisAngle(n) => f(n),
+            AnimationNodeType::QuatFromEuler(n) => f(n),
+            AnimationNodeType::SmoothF32(n) => f(n),
+            AnimationNodeType::SmoothVec3(n) => f(n),
+            AnimationNodeType::SmoothQuat(n) => f(n),
+            AnimationNodeType::AndBool(n) => f(n),
+            AnimationNodeType::OrBool(n) => f(n),
+            AnimationNodeType::NotBool(n) => f(n),
+            AnimationNodeType::CompareF32(n) => f(n),
+            AnimationNodeType::CompareI32(n) => f(n),
+            AnimationNodeType::EqualEnum(n) => f(n),
+            AnimationNodeType::Select(n) => f(n),
+            AnimationNodeType::Switch(n) => f(n),
             AnimationNodeType::Graph(n) => f(n),
+            AnimationNodeType::StateMachine(n) => f(n),
             AnimationNodeType::IntoBoneSpace(n) => f(n),
             AnimationNodeType::IntoCharacterSpace(n) => f(n),
             AnimationNodeType::IntoGlobalSpace(n) => f(n),
             AnimationNodeType::ExtendSkeleton(n) => f(n),
             AnimationNodeType::TwoBoneIK(n) => f(n),
+            AnimationNodeType::IkChain(n) => f(n),
+            AnimationNodeType::LookAt(n) => f(n),
+            AnimationNodeType::FootPlacement(n) => f(n),
             AnimationNodeType::Dummy(n) => f(n),
             AnimationNodeType::Custom(n) => f(n.node.lock().unwrap().deref()),
         }
@@ -238,24 +376,61 @@ impl AnimationNodeType {
         match self {
             AnimationNodeType::Clip(n) => f(n),
             AnimationNodeType::Blend(n) => f(n),
+            AnimationNodeType::LayeredBlend(n) => f(n),
+            AnimationNodeType::BlendSpace1D(n) => f(n),
+            AnimationNodeType::BlendSpace2D(n) => f(n),
             AnimationNodeType::Chain(n) => f(n),
             AnimationNodeType::FlipLR(n) => f(n),
             AnimationNodeType::Loop(n) => f(n),
             AnimationNodeType::Speed(n) => f(n),
             AnimationNodeType::Rotation(n) => f(n),
+            AnimationNodeType::MakeAdditive(n) => f(n),
+            AnimationNodeType::ApplyAdditive(n) => f(n),
+            AnimationNodeType::RootMotion(n) => f(n),
             AnimationNodeType::AddF32(n) => f(n),
             AnimationNodeType::MulF32(n) => f(n),
             AnimationNodeType::DivF32(n) => f(n),
             AnimationNodeType::SubF32(n) => f(n),
             AnimationNodeType::ClampF32(n) => f(n),
             AnimationNodeType::AbsF32(n) => f(n),
+            AnimationNodeType::Expression(n) => f(n),
             AnimationNodeType::RotationArc(n) => f(n),
+            AnimationNodeType::AddVec3(n) => f(n),
+            AnimationNodeType::SubVec3(n) => f(n),
+            AnimationNodeType::ScaleVec3(n) => f(n),
+            AnimationNodeType::DotVec3(n) => f(n),
+            AnimationNodeType::CrossVec3(n) => f(n),
+            AnimationNodeType::NormalizeVec3(n) => f(n),
+            AnimationNodeType::LengthVec3(n) => f(n),
+            AnimationNodeType::LerpVec3(n) => f(n),
+            AnimationNodeType::BuildVec3(n) => f(n),
+            AnimationNodeType::DecomposeVec3(n) => f(n),
+            AnimationNodeType::MulQuat(n) => f(n),
+            AnimationNodeType::InvertQuat(n) => f(n),
+            AnimationNodeType::SlerpQuat(n) => f(n),
+            AnimationNodeType::QuatFromAxisAngle(n) => f(n),
+            AnimationNodeType::QuatFromEuler(n) => f(n),
+            AnimationNodeType::SmoothF32(n) => f(n),
+            AnimationNodeType::SmoothVec3(n) => f(n),
+            AnimationNodeType::SmoothQuat(n) => f(n),
+            AnimationNodeType::AndBool(n) => f(n),
+            AnimationNodeType::OrBool(n) => f(n),
+            AnimationNodeType::NotBool(n) => f(n),
+            AnimationNodeType::CompareF32(n) => f(n),
+            AnimationNodeType::CompareI32(n) => f(n),
+            AnimationNodeType::EqualEnum(n) => f(n),
+            AnimationNodeType::Select(n) => f(n),
+            AnimationNodeType::Switch(n) => f(n),
             AnimationNodeType::Graph(n) => f(n),
+            AnimationNodeType::StateMachine(n) => f(n),
             AnimationNodeType::IntoBoneSpace(n) => f(n),
             AnimationNodeType::IntoCharacterSpace(n) => f(n),
             AnimationNodeType::IntoGlobalSpace(n) => f(n),
             AnimationNodeType::ExtendSkeleton(n) => f(n),
             AnimationNodeType::TwoBoneIK(n) => f(n),
+            AnimationNodeType::IkChain(n) => f(n),
+            AnimationNodeType::LookAt(n) => f(n),
+            AnimationNodeType::FootPlacement(n) => f(n),
             AnimationNodeType::Dummy(n) => f(n),
             AnimationNodeType::Custom(n) => {
                 let mut nod = n.node.lock().unwrap();
@@ -264,30 +439,70 @@ impl AnimationNodeType {
         }
     }
 
-    pub fn inner_reflect(&mut self) -> &mut dyn Reflect {
-        match self {
+    /// Returns the inner node for editing, or `None` if it cannot be mutated. See
+    /// [`CustomNode::inner_reflect`].
+    pub fn inner_reflect(&mut self) -> Option<&mut dyn Reflect> {
+        let inner: &mut dyn Reflect = match self {
             AnimationNodeType::Clip(n) => n,
             AnimationNodeType::Blend(n) => n,
+            AnimationNodeType::LayeredBlend(n) => n,
+            AnimationNodeType::BlendSpace1D(n) => n,
+            AnimationNodeType::BlendSpace2D(n) => n,
             AnimationNodeType::Chain(n) => n,
             AnimationNodeType::FlipLR(n) => n,
             AnimationNodeType::Loop(n) => n,
             AnimationNodeType::Speed(n) => n,
             AnimationNodeType::Rotation(n) => n,
+            AnimationNodeType::MakeAdditive(n) => n,
+            AnimationNodeType::ApplyAdditive(n) => n,
+            AnimationNodeType::RootMotion(n) => n,
             AnimationNodeType::IntoBoneSpace(n) => n,
             AnimationNodeType::IntoCharacterSpace(n) => n,
             AnimationNodeType::IntoGlobalSpace(n) => n,
             AnimationNodeType::ExtendSkeleton(n) => n,
             AnimationNodeType::TwoBoneIK(n) => n,
+            AnimationNodeType::IkChain(n) => n,
+            AnimationNodeType::LookAt(n) => n,
+            AnimationNodeType::FootPlacement(n) => n,
             AnimationNodeType::AddF32(n) => n,
             AnimationNodeType::MulF32(n) => n,
             AnimationNodeType::DivF32(n) => n,
             AnimationNodeType::SubF32(n) => n,
             AnimationNodeType::ClampF32(n) => n,
             AnimationNodeType::AbsF32(n) => n,
+            AnimationNodeType::Expression(n) => n,
             AnimationNodeType::RotationArc(n) => n,
+            AnimationNodeType::AddVec3(n) => n,
+            AnimationNodeType::SubVec3(n) => n,
+            AnimationNodeType::ScaleVec3(n) => n,
+            AnimationNodeType::DotVec3(n) => n,
+            AnimationNodeType::CrossVec3(n) => n,
+            AnimationNodeType::NormalizeVec3(n) => n,
+            AnimationNodeType::LengthVec3(n) => n,
+            AnimationNodeType::LerpVec3(n) => n,
+            AnimationNodeType::BuildVec3(n) => n,
+            AnimationNodeType::DecomposeVec3(n) => n,
+            AnimationNodeType::MulQuat(n) => n,
+            AnimationNodeType::InvertQuat(n) => n,
+            AnimationNodeType::SlerpQuat(n) => n,
+            AnimationNodeType::QuatFromAxisAngle(n) => n,
+            AnimationNodeType::QuatFromEuler(n) => n,
+            AnimationNodeType::SmoothF32(n) => n,
+            AnimationNodeType::SmoothVec3(n) => n,
+            AnimationNodeType::SmoothQuat(n) => n,
+            AnimationNodeType::AndBool(n) => n,
+            AnimationNodeType::OrBool(n) => n,
+            AnimationNodeType::NotBool(n) => n,
+            AnimationNodeType::CompareF32(n) => n,
+            AnimationNodeType::CompareI32(n) => n,
+            AnimationNodeType::EqualEnum(n) => n,
+            AnimationNodeType::Select(n) => n,
+            AnimationNodeType::Switch(n) => n,
             AnimationNodeType::Graph(n) => n,
+            AnimationNodeType::StateMachine(n) => n,
             AnimationNodeType::Dummy(n) => n,
-            AnimationNodeType::Custom(_) => todo!(),
-        }
+            AnimationNodeType::Custom(n) => return n.inner_reflect(),
+        };
+        Some(inner)
     }
 }
@@ -146,8 +146,8 @@ impl BoneDebugGizmos for PassContext<'_> {
             return;
         }
 
-        for bone_path in inner_pose.paths.keys() {
-            self.bone_gizmo(bone_path.clone(), color, Some((inner_pose, timestamp)));
+        for bone_id in inner_pose.ids.keys() {
+            self.bone_gizmo(*bone_id, color, Some((inner_pose, timestamp)));
         }
     }
 
@@ -167,7 +167,7 @@ impl BoneDebugGizmos for PassContext<'_> {
             None => (&default_pose, 0.),
         };
 
-        let Some(parent_id) = bone_id.parent() else {
+        let Some(parent_id) = self.skeleton.parent(bone_id) else {
             return;
         };
         let global_bone_transform = self.global_transform_of_bone(inner_pose, bone_id, timestamp);
@@ -183,11 +183,11 @@ impl BoneDebugGizmos for PassContext<'_> {
         if !self.will_draw() {
             return;
         }
-        let entity = self.entity_map.get(&bone_id).unwrap();
+        let entity = self.skeleton.entity(bone_id).unwrap();
         let global_transform = self
             .resources
             .transform_query
-            .get(*entity)
+            .get(entity)
             .unwrap()
             .1
             .compute_transform();
@@ -203,11 +203,11 @@ impl BoneDebugGizmos for PassContext<'_> {
         if !self.will_draw() {
             return;
         }
-        let entity = self.entity_map.get(&bone_id).unwrap();
+        let entity = self.skeleton.entity(bone_id).unwrap();
         let global_transform = self
             .resources
             .transform_query
-            .get(*entity)
+            .get(entity)
             .unwrap()
             .1
             .compute_transform();
@@ -239,12 +239,12 @@ impl BoneDebugGizmos for PassContext<'_> {
         if !self.will_draw() {
             return;
         }
-        let parent_bone_id = bone_id.parent().unwrap();
-        let entity = self.entity_map.get(&parent_bone_id).unwrap();
+        let parent_bone_id = self.skeleton.parent(bone_id).unwrap();
+        let entity = self.skeleton.entity(parent_bone_id).unwrap();
         let global_transform = self
             .resources
             .transform_query
-            .get(*entity)
+            .get(entity)
             .unwrap()
             .1
             .compute_transform();
@@ -266,12 +266,12 @@ impl BoneDebugGizmos for PassContext<'_> {
         if !self.will_draw() {
             return;
         }
-        let parent_bone_id = bone_id.parent().unwrap();
-        let entity = self.entity_map.get(&parent_bone_id).unwrap();
+        let parent_bone_id = self.skeleton.parent(bone_id).unwrap();
+        let entity = self.skeleton.entity(parent_bone_id).unwrap();
         let global_transform = self
             .resources
             .transform_query
-            .get(*entity)
+            .get(entity)
             .unwrap()
             .1
             .compute_transform();
@@ -1,6 +1,6 @@
 use crate::{
     core::{
-        animation_graph::{SourcePin, TimeUpdate},
+        animation_graph::{NodeId, SourcePin, TimeUpdate},
         duration_data::DurationData,
         frame::PoseFrame,
     },
@@ -8,7 +8,8 @@ use crate::{
 };
 
 use super::pass_context::GraphContextRef;
-use bevy::{reflect::prelude::*, utils::HashMap};
+use bevy::{math::Quat, reflect::prelude::*, transform::components::Transform, utils::HashMap};
+use std::any::{Any, TypeId};
 
 #[derive(Reflect, Debug, Default)]
 pub struct OutputCache {
@@ -156,10 +157,83 @@ impl OutputCaches {
     }
 }
 
+/// Arbitrary state kept by nodes across frames, such as the active state of a state machine or
+/// the current value of a smoothed parameter. Each node can store one value of each type.
+#[derive(Default)]
+pub struct NodeStates {
+    states: HashMap<NodeId, HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl NodeStates {
+    pub fn get<T: Any + Send + Sync>(&self, node_id: &str) -> Option<&T> {
+        self.states
+            .get(node_id)?
+            .get(&TypeId::of::<T>())?
+            .downcast_ref()
+    }
+
+    pub fn get_mut<T: Any + Send + Sync>(&mut self, node_id: &str) -> Option<&mut T> {
+        self.states
+            .get_mut(node_id)?
+            .get_mut(&TypeId::of::<T>())?
+            .downcast_mut()
+    }
+
+    pub fn get_or_insert_with<T: Any + Send + Sync>(
+        &mut self,
+        node_id: &str,
+        default: impl FnOnce() -> T,
+    ) -> &mut T {
+        if !self.states.contains_key(node_id) {
+            self.states.insert(node_id.into(), HashMap::default());
+        }
+        self.states
+            .get_mut(node_id)
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(default()))
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Stores the state, returning the previous state of the same type if any.
+    pub fn insert<T: Any + Send + Sync>(&mut self, node_id: NodeId, value: T) -> Option<T> {
+        self.states
+            .entry(node_id)
+            .or_default()
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&mut self, node_id: &str) -> Option<T> {
+        self.states
+            .get_mut(node_id)?
+            .remove(&TypeId::of::<T>())
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+}
+
+impl std::fmt::Debug for NodeStates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.states.keys()).finish()
+    }
+}
+
 #[derive(Debug, Default, Reflect)]
 pub struct GraphContext {
     outputs: OutputCaches,
     times: TimeCaches,
+    /// Per-node state persisted across frames
+    #[reflect(ignore)]
+    node_states: NodeStates,
+    /// Duration of the inertialization requested by a node during the last update, if any
+    inertialization_request: Option<f32>,
+    /// Whether a node may request inertialization in a later update
+    inertialization_enabled: bool,
+    /// Root motion extracted by nodes during the last update, if any
+    root_motion: Option<Transform>,
     #[reflect(ignore)]
     subgraph_contexts: HashMap<String, GraphContext>,
 }
@@ -226,6 +300,67 @@ impl GraphContext {
         self.outputs.set_pose(source_pin, value)
     }
 
+    pub fn get_node_state<T: Any + Send + Sync>(&self, node_id: &str) -> Option<&T> {
+        self.node_states.get(node_id)
+    }
+
+    pub fn node_states(&mut self) -> &mut NodeStates {
+        &mut self.node_states
+    }
+
+    /// Requests the player to inertialize from the previous output pose into the current one,
+    /// over the given duration. If several requests happen at once, the longest one wins.
+    pub fn request_inertialization(&mut self, duration: f32) {
+        self.inertialization_request = Some(
+            self.inertialization_request
+                .map_or(duration, |current| current.max(duration)),
+        );
+    }
+
+    /// Lets the player know that a node may request inertialization in a later update, so that it
+    /// keeps the output poses needed to start it. Must be called on every update.
+    pub fn enable_inertialization(&mut self) {
+        self.inertialization_enabled = true;
+    }
+
+    /// Returns and clears whether inertialization was enabled in this context or any of its
+    /// subgraph contexts.
+    pub fn take_inertialization_enabled(&mut self) -> bool {
+        let mut enabled = std::mem::take(&mut self.inertialization_enabled);
+        for ctx in self.subgraph_contexts.values_mut() {
+            enabled |= ctx.take_inertialization_enabled();
+        }
+        enabled
+    }
+
+    /// Returns and clears the longest inertialization request in this context or any of its
+    /// subgraph contexts.
+    pub fn take_inertialization_request(&mut self) -> Option<f32> {
+        self.subgraph_contexts
+            .values_mut()
+            .filter_map(|ctx| ctx.take_inertialization_request())
+            .chain(self.inertialization_request.take())
+            .reduce(f32::max)
+    }
+
+    /// Adds the motion extracted from a root bone during the current update.
+    pub fn add_root_motion(&mut self, motion: Transform) {
+        self.root_motion = Some(match self.root_motion {
+            Some(current) => compose_motion(current, motion),
+            None => motion,
+        });
+    }
+
+    /// Returns and clears the root motion extracted in this context and any of its subgraph
+    /// contexts.
+    pub fn take_root_motion(&mut self) -> Option<Transform> {
+        self.subgraph_contexts
+            .values_mut()
+            .filter_map(|ctx| ctx.take_root_motion())
+            .chain(self.root_motion.take())
+            .reduce(compose_motion)
+    }
+
     pub(super) fn context_for_subgraph_or_insert_default(&mut self, node: &str) -> GraphContextRef {
         if !self.subgraph_contexts.contains_key(node) {
             self.subgraph_contexts
@@ -235,3 +370,44 @@ impl GraphContext {
         self.subgraph_contexts.get_mut(node).unwrap().into()
     }
 }
+
+/// Applies the root motion `second` after `first`, with `second` relative to the heading reached
+/// after `first`.
+pub fn compose_motion(first: Transform, second: Transform) -> Transform {
+    Transform {
+        translation: first.translation + first.rotation * second.translation,
+        rotation: first.rotation * second.rotation,
+        scale: first.scale,
+    }
+}
+
+/// Scales the root motion `motion` by `weight`, so that motions blended with weights adding up to
+/// one add up to a single motion when composed.
+pub fn scale_motion(motion: Transform, weight: f32) -> Transform {
+    Transform {
+        translation: motion.translation * weight,
+        rotation: Quat::IDENTITY.slerp(motion.rotation, weight),
+        scale: motion.scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_states_are_typed_per_node() {
+        let mut states = NodeStates::default();
+        *states.get_or_insert_with("a", || 1_u32) += 1;
+        states.insert("a".into(), 0.5_f32);
+        states.insert("b".into(), 7_u32);
+
+        assert_eq!(states.get::<u32>("a"), Some(&2));
+        assert_eq!(states.get::<f32>("a"), Some(&0.5));
+        assert_eq!(states.get::<u32>("b"), Some(&7));
+        assert_eq!(states.get::<f32>("b"), None);
+        assert_eq!(states.insert("b".into(), 8_u32), Some(7));
+        assert_eq!(states.remove::<u32>("b"), Some(8));
+        assert_eq!(states.get::<u32>("b"), None);
+    }
+}
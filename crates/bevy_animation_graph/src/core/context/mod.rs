@@ -4,8 +4,8 @@ mod pass_context;
 mod spec_context;
 mod system_resources;
 
-pub use deferred_gizmos::{BoneDebugGizmos, DeferredGizmos};
-pub use graph_context::GraphContext;
+pub use deferred_gizmos::{BoneDebugGizmos, DeferredGizmoCommand, DeferredGizmos};
+pub use graph_context::{compose_motion, scale_motion, GraphContext, NodeStates};
 pub use pass_context::PassContext;
 pub use spec_context::SpecContext;
 pub use system_resources::SystemResources;
@@ -1,17 +1,21 @@
-use bevy::{ecs::entity::Entity, utils::HashMap};
+use bevy::{ecs::entity::Entity, transform::components::Transform};
+use std::any::Any;
 
 use crate::{
     core::{
+        animation_clip::SyncMarker,
         animation_graph::{InputOverlay, NodeId, PinId, SourcePin, TargetPin, TimeUpdate},
         duration_data::DurationData,
         errors::GraphError,
         frame::PoseFrame,
-        pose::BoneId,
+        skeleton::Skeleton,
     },
     prelude::{AnimationGraph, ParamValue},
 };
 
-use super::{deferred_gizmos::DeferredGizmoRef, GraphContext, SystemResources};
+use super::{
+    deferred_gizmos::DeferredGizmoRef, graph_context::scale_motion, GraphContext, SystemResources,
+};
 
 #[derive(Clone, Copy)]
 pub struct NodeContext<'a> {
@@ -27,9 +31,16 @@ pub struct PassContext<'a> {
     pub node_context: Option<NodeContext<'a>>,
     pub parent: Option<PassContextRef<'a>>,
     pub root_entity: Entity,
-    pub entity_map: &'a HashMap<BoneId, Entity>,
+    pub skeleton: &'a Skeleton,
     pub deferred_gizmos: DeferredGizmoRef,
     pub should_debug: bool,
+    /// Time update received by the graph player this frame, unaffected by any time changes
+    /// applied by nodes (e.g. speed or loop nodes)
+    pub frame_update: TimeUpdate,
+    /// Weight of the poses requested through this context in the output of the graph. Blend nodes
+    /// multiply it by the weight of each of their inputs, and root motion extracted by nodes is
+    /// scaled by it.
+    pub root_motion_weight: f32,
 }
 
 impl<'a> PassContext<'a> {
@@ -39,7 +50,7 @@ impl<'a> PassContext<'a> {
         resources: &'a SystemResources,
         overlay: &'a InputOverlay,
         root_entity: Entity,
-        entity_map: &'a HashMap<BoneId, Entity>,
+        skeleton: &'a Skeleton,
         deferred_gizmos: impl Into<DeferredGizmoRef>,
     ) -> Self {
         Self {
@@ -49,9 +60,11 @@ impl<'a> PassContext<'a> {
             node_context: None,
             parent: None,
             root_entity,
-            entity_map,
+            skeleton,
             deferred_gizmos: deferred_gizmos.into(),
             should_debug: false,
+            frame_update: TimeUpdate::Delta(0.),
+            root_motion_weight: 1.,
         }
     }
 
@@ -65,9 +78,11 @@ impl<'a> PassContext<'a> {
             node_context: Some(NodeContext { node_id, graph }),
             parent: self.parent.clone(),
             root_entity: self.root_entity,
-            entity_map: self.entity_map,
+            skeleton: self.skeleton,
             deferred_gizmos: self.deferred_gizmos.clone(),
             should_debug: self.should_debug,
+            frame_update: self.frame_update,
+            root_motion_weight: self.root_motion_weight,
         }
     }
 
@@ -81,9 +96,11 @@ impl<'a> PassContext<'a> {
             node_context: None,
             parent: self.parent.clone(),
             root_entity: self.root_entity,
-            entity_map: self.entity_map,
+            skeleton: self.skeleton,
             deferred_gizmos: self.deferred_gizmos.clone(),
             should_debug: self.should_debug,
+            frame_update: self.frame_update,
+            root_motion_weight: self.root_motion_weight,
         }
     }
 
@@ -96,28 +113,55 @@ impl<'a> PassContext<'a> {
             node_context: self.node_context,
             parent: self.parent.clone(),
             root_entity: self.root_entity,
-            entity_map: self.entity_map,
+            skeleton: self.skeleton,
             deferred_gizmos: self.deferred_gizmos.clone(),
             should_debug,
+            frame_update: self.frame_update,
+            root_motion_weight: self.root_motion_weight,
+        }
+    }
+
+    /// Returns a pass context with updated `frame_update`
+    pub fn with_frame_update(&self, frame_update: TimeUpdate) -> Self {
+        Self {
+            frame_update,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a pass context whose requested poses contribute to the output with the given
+    /// weight, relative to the poses requested through `self`.
+    pub fn with_root_motion_weight(&self, weight: f32) -> Self {
+        Self {
+            root_motion_weight: self.root_motion_weight * weight,
+            ..self.clone()
         }
     }
 
     /// Returns a new pass context decorated with `self` as the parent context.
     /// Used when passing the context down to a subgraph.
     pub fn child(&'a self, overlay: &'a InputOverlay) -> Self {
+        self.child_with_key(overlay, self.node_context.unwrap().node_id)
+    }
+
+    /// Same as [`PassContext::child`], but the subgraph context is stored under the given key
+    /// instead of the node id. Used by nodes that evaluate more than one subgraph.
+    pub fn child_with_key(&'a self, overlay: &'a InputOverlay, key: &str) -> Self {
         Self {
             context: self
                 .context
                 .as_mut()
-                .context_for_subgraph_or_insert_default(self.node_context.unwrap().node_id),
+                .context_for_subgraph_or_insert_default(key),
             resources: self.resources,
             overlay,
             node_context: self.node_context,
             parent: Some(self.into()),
             root_entity: self.root_entity,
-            entity_map: self.entity_map,
+            skeleton: self.skeleton,
             deferred_gizmos: self.deferred_gizmos.clone(),
             should_debug: self.should_debug,
+            frame_update: self.frame_update,
+            root_motion_weight: self.root_motion_weight,
         }
     }
 
@@ -137,6 +181,27 @@ impl<'a> PassContext<'a> {
         self.context.as_mut()
     }
 
+    /// Verify whether an input parameter pin of the current node is connected in its graph.
+    pub fn parameter_connected(&self, pin_id: impl Into<PinId>) -> bool {
+        let node_ctx = self.node_context.unwrap();
+        let target_pin = TargetPin::NodeParameter(node_ctx.node_id.clone(), pin_id.into());
+        node_ctx.graph.edges.contains_key(&target_pin)
+    }
+
+    /// Verify whether an input pose pin of the current node is connected in its graph.
+    pub fn pose_connected(&self, pin_id: impl Into<PinId>) -> bool {
+        let node_ctx = self.node_context.unwrap();
+        let target_pin = TargetPin::NodePose(node_ctx.node_id.clone(), pin_id.into());
+        node_ctx.graph.edges.contains_key(&target_pin)
+    }
+
+    /// Adds root motion extracted during the current update, scaled by the weight of the current
+    /// node in the output of the graph.
+    pub fn add_root_motion(&mut self, motion: Transform) {
+        let weight = self.root_motion_weight;
+        self.context().add_root_motion(scale_motion(motion, weight));
+    }
+
     /// Request an input parameter from the graph
     pub fn parameter_back(&mut self, pin_id: impl Into<PinId>) -> Result<ParamValue, GraphError> {
         let node_ctx = self.node_context.unwrap();
@@ -153,6 +218,18 @@ impl<'a> PassContext<'a> {
         node_ctx.graph.get_duration(target_pin, self.without_node())
     }
 
+    /// Request the sync markers of an input pose pin.
+    pub fn sync_markers_back(
+        &mut self,
+        pin_id: impl Into<PinId>,
+    ) -> Result<Vec<SyncMarker>, GraphError> {
+        let node_ctx = self.node_context.unwrap();
+        let target_pin = TargetPin::NodePose(node_ctx.node_id.clone(), pin_id.into());
+        node_ctx
+            .graph
+            .get_sync_markers(target_pin, self.without_node())
+    }
+
     /// Request an input pose.
     pub fn pose_back(
         &mut self,
@@ -166,6 +243,32 @@ impl<'a> PassContext<'a> {
             .get_pose(time_update, target_pin, self.without_node())
     }
 
+    /// Returns the state of type `T` stored by the current node in a previous frame, if any.
+    pub fn node_state<T: Any + Send + Sync>(&self) -> Option<&T> {
+        let node_ctx = self.node_context.unwrap();
+        self.context.as_mut().get_node_state(node_ctx.node_id)
+    }
+
+    /// Returns a mutable reference to the state of type `T` of the current node, inserting the
+    /// default value if there is none yet. The state is kept across frames.
+    pub fn node_state_mut<T: Any + Send + Sync + Default>(&mut self) -> &mut T {
+        let node_ctx = self.node_context.unwrap();
+        self.context
+            .as_mut()
+            .node_states()
+            .get_or_insert_with(node_ctx.node_id, T::default)
+    }
+
+    /// Stores state of type `T` for the current node, to be retrieved in later frames. Returns
+    /// the previously stored state of the same type, if any.
+    pub fn set_node_state<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        let node_ctx = self.node_context.unwrap();
+        self.context
+            .as_mut()
+            .node_states()
+            .insert(node_ctx.node_id.clone(), value)
+    }
+
     /// Request the cached time update query from the current frame
     pub fn time_update_fwd(&self) -> TimeUpdate {
         let node_ctx = self.node_context.unwrap();
@@ -184,6 +287,19 @@ impl<'a> PassContext<'a> {
         let source_pin = SourcePin::NodePose(node_ctx.node_id.clone());
         self.context.as_mut().get_prev_time(&source_pin)
     }
+
+    /// Request the cached timestamp of an input pose in the last frame
+    pub fn prev_time_back(&self, pin_id: impl Into<PinId>) -> f32 {
+        let node_ctx = self.node_context.unwrap();
+        let target_pin = TargetPin::NodePose(node_ctx.node_id.clone(), pin_id.into());
+        node_ctx
+            .graph
+            .edges
+            .get(&target_pin)
+            .map_or(0., |source_pin| {
+                self.context.as_mut().get_prev_time(source_pin)
+            })
+    }
 }
 
 #[derive(Clone)]
@@ -1,7 +1,7 @@
-use bevy::prelude::*;
 use thiserror::Error;
 
 use super::GraphValidationError;
+use crate::utils::{bvh::BvhError, expression::ExpressionError};
 
 /// Possible errors that can be produced by a custom asset loader
 #[non_exhaustive]
@@ -19,8 +19,28 @@ pub enum AssetLoaderError {
     GltfMissingLabel(String),
     #[error("Could not complete direct asset load: {0}")]
     LoadDirectError(#[from] bevy::asset::LoadDirectError),
+    #[error("Could not read asset bytes: {0}")]
+    ReadAssetBytesError(#[from] bevy::asset::ReadAssetBytesError),
+    #[error("Could not parse gltf document: {0}")]
+    GltfDocumentError(#[from] gltf::Error),
+    #[error("Could not load BVH file: {0}")]
+    BvhError(#[from] BvhError),
+    #[error("Asset does not have the expected type: {0}")]
+    UnexpectedAssetType(String),
+    #[error("Clip cannot be loaded from itself: {0}")]
+    RecursiveClip(String),
+    #[error("Retarget map scene is not a scene asset: {0}")]
+    RetargetSceneMissing(String),
+    #[error("Retarget map bone not found in its rest pose scene: {0}")]
+    RetargetBoneMissing(String),
     #[error("Animated scene path is incorrect: {0}")]
     AnimatedSceneMissingName(String),
+    #[error("Custom node type is not registered: {0}")]
+    UnregisteredCustomNode(String),
+    #[error("Invalid expression in node {0}: {1}")]
+    InvalidExpression(String, ExpressionError),
+    #[error("Bone paths {0} and {1} have the same bone id")]
+    BoneIdCollision(String, String),
     #[error("Graph does not satisfy constraints: {0}")]
     InconsistentGraphError(#[from] GraphValidationError),
 }
@@ -1,4 +1,7 @@
-use crate::core::animation_graph::TargetPin;
+use crate::core::{
+    animation_graph::{PinId, TargetPin},
+    frame::PoseSpec,
+};
 use bevy::prelude::*;
 use thiserror::Error;
 
@@ -8,4 +11,12 @@ use thiserror::Error;
 pub enum GraphError {
     #[error("Expected an edge connected to {0:?}")]
     MissingInputEdge(TargetPin),
+    #[error("No value available for graph input parameter {0:?}")]
+    MissingInputParameter(PinId),
+    #[error("No value available for graph input pose {0:?}")]
+    MissingInputPose(PinId),
+    #[error("Pose in unexpected space: {0:?}")]
+    UnexpectedPoseSpace(PoseSpec),
+    #[error("Could not parse expression: {0}")]
+    InvalidExpression(String),
 }
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// Possible errors when converting an animation graph into its serializable form
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum GraphSerializationError {
+    #[error("Custom node type was not registered")]
+    UnregisteredCustomNode,
+    #[error("Could not serialize custom node: {0}")]
+    CustomNodeError(#[from] ron::Error),
+    #[error("Could not save node {0}: {1}")]
+    NodeError(String, Box<GraphSerializationError>),
+}
@@ -1,6 +1,6 @@
-use super::animation_clip::EntityPath;
+use super::{animation_graph::NodeId, pose::BoneId};
 use crate::{
-    prelude::{InterpolateLinear, SampleLinearAt},
+    prelude::{InterpolateCubicSpline, InterpolateLinear, SampleLinearAt},
     utils::unwrap::Unwrap,
 };
 use bevy::{
@@ -9,6 +9,52 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
+/// How a [`ValueFrame`] is interpolated between `prev` and `next`.
+#[derive(Reflect, Clone, Default, PartialEq, Debug)]
+pub enum FrameInterpolation<T: FromReflect + TypePath> {
+    #[default]
+    Linear,
+    /// `prev` is held over the whole frame, `next` only takes effect in the following frame
+    Step,
+    /// Cubic Hermite spline. Tangents are already multiplied by the duration of the source
+    /// keyframe interval, so they remain valid when the timestamps of the frame are remapped.
+    CubicSpline {
+        prev_out_tangent: T,
+        next_in_tangent: T,
+    },
+}
+
+impl<T: FromReflect + TypePath> FrameInterpolation<T> {
+    /// Maps the tangents of a cubic spline with the given function, which should be linear for
+    /// the result to be meaningful.
+    pub fn map_linear<Q, F>(&self, f: F) -> FrameInterpolation<Q>
+    where
+        Q: FromReflect + TypePath,
+        F: Fn(&T) -> Q,
+    {
+        match self {
+            Self::Linear => FrameInterpolation::Linear,
+            Self::Step => FrameInterpolation::Step,
+            Self::CubicSpline {
+                prev_out_tangent,
+                next_in_tangent,
+            } => FrameInterpolation::CubicSpline {
+                prev_out_tangent: f(prev_out_tangent),
+                next_in_tangent: f(next_in_tangent),
+            },
+        }
+    }
+
+    /// Keeps step interpolation and falls back to linear interpolation for cubic splines, whose
+    /// tangents cannot be carried through arbitrary functions.
+    pub(crate) fn without_tangents<Q: FromReflect + TypePath>(&self) -> FrameInterpolation<Q> {
+        match self {
+            Self::Step => FrameInterpolation::Step,
+            Self::Linear | Self::CubicSpline { .. } => FrameInterpolation::Linear,
+        }
+    }
+}
+
 #[derive(Asset, Reflect, Clone, Default, PartialEq)]
 pub struct ValueFrame<T: FromReflect + TypePath> {
     pub(crate) prev: T,
@@ -17,6 +63,7 @@ pub struct ValueFrame<T: FromReflect + TypePath> {
     pub(crate) next_timestamp: f32,
     pub(crate) prev_is_wrapped: bool,
     pub(crate) next_is_wrapped: bool,
+    pub(crate) interpolation: FrameInterpolation<T>,
 }
 
 impl<T: FromReflect + TypePath> ValueFrame<T> {
@@ -29,7 +76,7 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
     }
 
     /// Maps the `prev` and `next` values of the frame
-    /// using the given function
+    /// using the given function. Cubic spline frames fall back to linear interpolation.
     pub fn map<Q, F>(&self, f: F) -> ValueFrame<Q>
     where
         Q: FromReflect + TypePath,
@@ -42,23 +89,58 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
             next_timestamp: self.next_timestamp,
             prev_is_wrapped: self.prev_is_wrapped,
             next_is_wrapped: self.next_is_wrapped,
+            interpolation: self.interpolation.without_tangents(),
+        }
+    }
+
+    /// Maps the `prev` and `next` values of the frame, as well as cubic spline tangents, using
+    /// the given linear function (e.g. extracting a component or mirroring).
+    pub fn map_linear<Q, F>(&self, f: F) -> ValueFrame<Q>
+    where
+        Q: FromReflect + TypePath,
+        F: Fn(&T) -> Q,
+    {
+        ValueFrame {
+            prev: f(&self.prev),
+            prev_timestamp: self.prev_timestamp,
+            next: f(&self.next),
+            next_timestamp: self.next_timestamp,
+            prev_is_wrapped: self.prev_is_wrapped,
+            next_is_wrapped: self.next_is_wrapped,
+            interpolation: self.interpolation.map_linear(&f),
         }
     }
 
     /// Mutates the `prev` and `next` values of the frame
-    /// using the given function
+    /// using the given function. Cubic spline frames fall back to linear interpolation.
     pub fn map_mut<F>(&mut self, f: F)
     where
         F: Fn(&T) -> T,
     {
         self.prev = f(&self.prev);
         self.next = f(&self.next);
+        self.interpolation = self.interpolation.without_tangents();
+    }
+
+    /// Value that the frame holds right before `next_timestamp`, which is `prev` for step
+    /// interpolation.
+    fn value_before_next(&self) -> &T {
+        match self.interpolation {
+            FrameInterpolation::Step => &self.prev,
+            _ => &self.next,
+        }
     }
 
     /// Returns a new frame where `prev_timestamp` is the maximum of `self.prev_timestamp`
     /// and `other.prev_timestamp`, and `next_timestamp` is the minimum of `self.next_timestamp`
     /// and `other.next_timestamp`. Both frames are sampled at the chosen timestamps for either
     /// end using the given sampler and combined using the given combiner function.
+    ///
+    /// Step frames are constant over the merged range, so they are combined using the value they
+    /// hold before their next keyframe. The result is interpolated linearly, unless both frames
+    /// use step interpolation. In particular, the tangents of cubic spline frames cannot be
+    /// carried through an arbitrary combiner, so the curve between the merged keyframes loses
+    /// its cubic shape.
     pub fn merge<B, C, SLeft, SRight, F>(
         &self,
         other: &ValueFrame<B>,
@@ -98,7 +180,7 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
             let other_next = sampler_right(other, ts);
             (
                 ts,
-                combiner(&self.next, &other_next),
+                combiner(self.value_before_next(), &other_next),
                 self.next_is_wrapped && other.next_is_wrapped,
             )
         } else {
@@ -106,11 +188,16 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
             let self_next = sampler_left(self, ts);
             (
                 ts,
-                combiner(&self_next, &other.next),
+                combiner(&self_next, other.value_before_next()),
                 self.next_is_wrapped && other.next_is_wrapped,
             )
         };
 
+        let interpolation = match (&self.interpolation, &other.interpolation) {
+            (FrameInterpolation::Step, FrameInterpolation::Step) => FrameInterpolation::Step,
+            _ => FrameInterpolation::Linear,
+        };
+
         ValueFrame {
             prev,
             prev_timestamp,
@@ -118,6 +205,7 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
             next_timestamp,
             prev_is_wrapped,
             next_is_wrapped,
+            interpolation,
         }
     }
 
@@ -127,8 +215,8 @@ impl<T: FromReflect + TypePath> ValueFrame<T> {
     /// end and combined using the given combiner function.
     pub fn merge_linear<B, C, F>(&self, other: &ValueFrame<B>, combiner: F) -> ValueFrame<C>
     where
-        T: InterpolateLinear,
-        B: FromReflect + TypePath + InterpolateLinear,
+        T: InterpolateLinear + InterpolateCubicSpline + Clone,
+        B: FromReflect + TypePath + InterpolateLinear + InterpolateCubicSpline + Clone,
         C: FromReflect + TypePath,
         F: Fn(&T, &B) -> C,
     {
@@ -176,6 +264,7 @@ impl BoneFrame {
             next_timestamp: f32::MAX,
             prev_is_wrapped: true,
             next_is_wrapped: true,
+            interpolation: FrameInterpolation::Linear,
         };
 
         self.to_transform_frame_linear_with_base_frame(transform_frame)
@@ -223,6 +312,7 @@ impl BoneFrame {
             next_timestamp: f32::MAX,
             prev_is_wrapped: true,
             next_is_wrapped: true,
+            interpolation: FrameInterpolation::Linear,
         };
 
         self.to_transform_frame_linear_with_base_frame(transform_frame)
@@ -247,7 +337,7 @@ impl BoneFrame {
 #[derive(Asset, Reflect, Clone, Default)]
 pub struct InnerPoseFrame {
     pub(crate) bones: Vec<BoneFrame>,
-    pub(crate) paths: HashMap<EntityPath, usize>,
+    pub(crate) ids: HashMap<BoneId, usize>,
 }
 
 /// Pose frame where each transform is local with respect to the parent bone
@@ -260,6 +350,10 @@ pub struct CharacterPoseFrame(pub(crate) InnerPoseFrame);
 /// Pose frame where each transform is in world/global space
 #[derive(Reflect, Clone, Default, Debug)]
 pub struct GlobalPoseFrame(pub(crate) InnerPoseFrame);
+/// Pose frame where each transform is a bone space offset with respect to a reference pose,
+/// meant to be layered on top of another pose
+#[derive(Reflect, Clone, Default, Debug)]
+pub struct AdditivePoseFrame(pub(crate) InnerPoseFrame);
 
 impl From<InnerPoseFrame> for BonePoseFrame {
     fn from(value: InnerPoseFrame) -> Self {
@@ -279,6 +373,12 @@ impl From<InnerPoseFrame> for GlobalPoseFrame {
     }
 }
 
+impl From<InnerPoseFrame> for AdditivePoseFrame {
+    fn from(value: InnerPoseFrame) -> Self {
+        Self(value)
+    }
+}
+
 impl BonePoseFrame {
     pub fn inner_ref(&self) -> &InnerPoseFrame {
         &self.0
@@ -342,12 +442,36 @@ impl GlobalPoseFrame {
     }
 }
 
+impl AdditivePoseFrame {
+    pub fn inner_ref(&self) -> &InnerPoseFrame {
+        &self.0
+    }
+
+    pub fn inner_mut(&mut self) -> &mut InnerPoseFrame {
+        &mut self.0
+    }
+
+    pub fn inner(self) -> InnerPoseFrame {
+        self.0
+    }
+
+    pub fn map_ts<F>(&mut self, f: F)
+    where
+        F: Fn(f32) -> f32,
+    {
+        self.inner_mut().map_ts(f)
+    }
+}
+
+/// Half the width of the frames created when overriding a bone that has no keyframes
+const OVERRIDE_FRAME_HALF_WIDTH: f32 = 0.1;
+
 impl InnerPoseFrame {
     /// Adds a new bone frame to the pose frame, possibly replacing an existing bone frame.
-    pub(crate) fn add_bone(&mut self, frame: BoneFrame, path: EntityPath) {
-        let id = self.bones.len();
-        self.bones.insert(id, frame);
-        self.paths.insert(path, id);
+    pub(crate) fn add_bone(&mut self, frame: BoneFrame, bone_id: BoneId) {
+        let idx = self.bones.len();
+        self.bones.insert(idx, frame);
+        self.ids.insert(bone_id, idx);
     }
 
     pub fn map_ts<F>(&mut self, f: F)
@@ -357,6 +481,58 @@ impl InnerPoseFrame {
         self.bones.iter_mut().for_each(|v| v.map_ts(&f));
     }
 
+    /// Overrides the translation of a bone with a constant value, adding the bone to the pose
+    /// frame if needed. `timestamp` is the timestamp of the pose, used to give new frames
+    /// finite bounds that survive time scaling.
+    pub(crate) fn set_bone_translation(
+        &mut self,
+        bone_id: BoneId,
+        translation: Vec3,
+        timestamp: f32,
+    ) {
+        if !self.ids.contains_key(&bone_id) {
+            self.add_bone(BoneFrame::default(), bone_id);
+        }
+        let bone = &mut self.bones[self.ids[&bone_id]];
+
+        if let Some(frame) = bone.translation.as_mut() {
+            frame.map_mut(|_| translation);
+        } else {
+            bone.translation = Some(ValueFrame {
+                prev: translation,
+                prev_timestamp: timestamp - OVERRIDE_FRAME_HALF_WIDTH,
+                next: translation,
+                next_timestamp: timestamp + OVERRIDE_FRAME_HALF_WIDTH,
+                prev_is_wrapped: false,
+                next_is_wrapped: false,
+                interpolation: FrameInterpolation::Linear,
+            });
+        }
+    }
+
+    /// Overrides the rotation of a bone with a constant value, adding the bone to the pose frame
+    /// if needed. See [`InnerPoseFrame::set_bone_translation`].
+    pub(crate) fn set_bone_rotation(&mut self, bone_id: BoneId, rotation: Quat, timestamp: f32) {
+        if !self.ids.contains_key(&bone_id) {
+            self.add_bone(BoneFrame::default(), bone_id);
+        }
+        let bone = &mut self.bones[self.ids[&bone_id]];
+
+        if let Some(frame) = bone.rotation.as_mut() {
+            frame.map_mut(|_| rotation);
+        } else {
+            bone.rotation = Some(ValueFrame {
+                prev: rotation,
+                prev_timestamp: timestamp - OVERRIDE_FRAME_HALF_WIDTH,
+                next: rotation,
+                next_timestamp: timestamp + OVERRIDE_FRAME_HALF_WIDTH,
+                prev_is_wrapped: false,
+                next_is_wrapped: false,
+                interpolation: FrameInterpolation::Linear,
+            });
+        }
+    }
+
     pub(crate) fn verify_timestamp_in_range(&self, timestamp: f32) -> bool {
         let mut failed = false;
 
@@ -455,10 +631,22 @@ impl std::fmt::Debug for InnerPoseFrame {
     }
 }
 
+/// Named event carried alongside a [`PoseFrame`]. The timestamp is in the same timeline as the
+/// pose frame, so it gets remapped together with it.
+#[derive(Clone, Reflect, Debug, Default, PartialEq)]
+pub struct PoseEvent {
+    pub timestamp: f32,
+    pub name: String,
+    /// Path of nodes (from the outermost graph inwards) leading to the node that produced the event
+    pub node_path: Vec<NodeId>,
+}
+
 #[derive(Clone, Reflect, Debug, Default)]
 pub struct PoseFrame {
     pub data: PoseFrameData,
     pub timestamp: f32,
+    /// Events in the animation that produced this pose, used to detect when playback passes them
+    pub events: Vec<PoseEvent>,
 }
 
 #[derive(Clone, Reflect, Debug)]
@@ -466,6 +654,7 @@ pub enum PoseFrameData {
     BoneSpace(BonePoseFrame),
     CharacterSpace(CharacterPoseFrame),
     GlobalSpace(GlobalPoseFrame),
+    Additive(AdditivePoseFrame),
 }
 
 impl Default for PoseFrameData {
@@ -483,6 +672,7 @@ impl PoseFrameData {
             PoseFrameData::BoneSpace(data) => data.map_ts(f),
             PoseFrameData::CharacterSpace(data) => data.map_ts(f),
             PoseFrameData::GlobalSpace(data) => data.map_ts(f),
+            PoseFrameData::Additive(data) => data.map_ts(f),
         }
     }
 }
@@ -493,6 +683,9 @@ impl PoseFrame {
         F: Fn(f32) -> f32,
     {
         self.data.map_ts(&f);
+        self.events
+            .iter_mut()
+            .for_each(|e| e.timestamp = f(e.timestamp));
         self.timestamp = f(self.timestamp);
     }
 
@@ -501,6 +694,7 @@ impl PoseFrame {
             PoseFrameData::BoneSpace(data) => data.inner_ref(),
             PoseFrameData::CharacterSpace(data) => data.inner_ref(),
             PoseFrameData::GlobalSpace(data) => data.inner_ref(),
+            PoseFrameData::Additive(data) => data.inner_ref(),
         };
 
         inner.verify_timestamp_in_range(self.timestamp)
@@ -511,6 +705,7 @@ impl PoseFrame {
             PoseFrameData::BoneSpace(data) => data.inner_ref(),
             PoseFrameData::CharacterSpace(data) => data.inner_ref(),
             PoseFrameData::GlobalSpace(data) => data.inner_ref(),
+            PoseFrameData::Additive(data) => data.inner_ref(),
         };
 
         inner.verify_timestamps_in_order()
@@ -524,6 +719,7 @@ pub enum PoseSpec {
     BoneSpace,
     CharacterSpace,
     GlobalSpace,
+    Additive,
     Any,
 }
 
@@ -543,6 +739,7 @@ impl From<&PoseFrameData> for PoseSpec {
             PoseFrameData::BoneSpace(_) => PoseSpec::BoneSpace,
             PoseFrameData::CharacterSpace(_) => PoseSpec::CharacterSpace,
             PoseFrameData::GlobalSpace(_) => PoseSpec::GlobalSpace,
+            PoseFrameData::Additive(_) => PoseSpec::Additive,
         }
     }
 }
@@ -588,3 +785,50 @@ impl Unwrap<GlobalPoseFrame> for PoseFrameData {
         }
     }
 }
+
+impl Unwrap<AdditivePoseFrame> for PoseFrameData {
+    fn unwrap(self) -> AdditivePoseFrame {
+        match self {
+            PoseFrameData::Additive(b) => b,
+            x => panic!("Found {:?}, expected additive pose", PoseSpec::from(&x)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cubic_frame(prev: (f32, Vec3), next: (f32, Vec3), tangent: Vec3) -> ValueFrame<Vec3> {
+        ValueFrame {
+            prev: prev.1,
+            prev_timestamp: prev.0,
+            next: next.1,
+            next_timestamp: next.0,
+            prev_is_wrapped: false,
+            next_is_wrapped: false,
+            interpolation: FrameInterpolation::CubicSpline {
+                prev_out_tangent: tangent,
+                next_in_tangent: tangent,
+            },
+        }
+    }
+
+    #[test]
+    fn test_blending_cubic_frames_samples_their_curves() {
+        let left = cubic_frame((0., Vec3::ZERO), (1., Vec3::X), Vec3::X * 3.);
+        let right = cubic_frame((0.5, Vec3::Y * 2.), (2., Vec3::Y * 4.), Vec3::Y);
+
+        let blended = left.interpolate_linear(&right, 0.5);
+
+        assert_eq!(blended.prev_timestamp, 0.5);
+        assert_eq!(blended.next_timestamp, 1.);
+        // Ends of the merged range are sampled on the cubic curves of both frames
+        let expected_prev = left.sample_linear_at(0.5).lerp(right.prev, 0.5);
+        let expected_next = left.next.lerp(right.sample_linear_at(1.), 0.5);
+        assert!(blended.prev.abs_diff_eq(expected_prev, 1e-6));
+        assert!(blended.next.abs_diff_eq(expected_next, 1e-6));
+        // but the blended frame is interpolated linearly in between
+        assert_eq!(blended.interpolation, FrameInterpolation::Linear);
+    }
+}
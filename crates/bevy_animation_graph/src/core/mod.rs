@@ -8,11 +8,15 @@ pub mod context;
 pub mod duration_data;
 pub mod errors;
 pub mod frame;
+pub mod node_registry;
 pub mod parameters;
 pub mod plugin;
 pub mod pose;
+pub mod skeleton;
 pub mod space_conversion;
 pub mod systems;
+#[cfg(test)]
+pub(crate) mod testing;
 
 pub mod prelude {
     use super::*;
@@ -22,6 +26,7 @@ pub mod prelude {
     pub use animation_graph_player::*;
     pub use animation_node::*;
     pub use context::*;
+    pub use node_registry::*;
     pub use parameters::OptParamSpec;
     pub use parameters::ParamSpec;
     pub use parameters::ParamValue;
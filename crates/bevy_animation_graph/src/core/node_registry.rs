@@ -0,0 +1,163 @@
+use super::animation_node::{CustomNode, NodeLike};
+use bevy::{
+    ecs::system::Resource,
+    reflect::{FromReflect, GetTypeRegistration, Reflect, TypePath},
+    utils::HashMap,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Node types that can be registered in the [`CustomNodeRegistry`], so that they can be saved
+/// to and loaded from animation graph files.
+pub trait SerializableNode:
+    NodeLike + FromReflect + TypePath + GetTypeRegistration + Default + Serialize + DeserializeOwned
+{
+}
+
+impl<T> SerializableNode for T where
+    T: NodeLike
+        + FromReflect
+        + TypePath
+        + GetTypeRegistration
+        + Default
+        + Serialize
+        + DeserializeOwned
+{
+}
+
+/// Functions used to create, clone, serialize and deserialize custom nodes of a given type.
+#[derive(Clone, Copy)]
+pub struct CustomNodeRegistration {
+    type_path: &'static str,
+    default: fn() -> CustomNode,
+    serialize: fn(&dyn NodeLike) -> Result<String, ron::Error>,
+    deserialize: fn(&str) -> Result<CustomNode, ron::error::SpannedError>,
+    from_reflect: fn(&dyn Reflect) -> Option<CustomNode>,
+}
+
+impl CustomNodeRegistration {
+    pub fn of<T: SerializableNode>() -> Self {
+        Self {
+            type_path: T::type_path(),
+            default: || CustomNode::registered(T::default()),
+            serialize: serialize_node::<T>,
+            deserialize: |data| Ok(CustomNode::registered(ron::de::from_str::<T>(data)?)),
+            from_reflect: |value| T::from_reflect(value).map(CustomNode::registered),
+        }
+    }
+
+    /// Name used to identify the node type in animation graph files
+    pub fn type_path(&self) -> &'static str {
+        self.type_path
+    }
+
+    pub fn create_default(&self) -> CustomNode {
+        (self.default)()
+    }
+
+    /// Serializes the node into a RON string. Fails if the node is not of the registered type.
+    pub fn serialize(&self, node: &dyn NodeLike) -> Result<String, ron::Error> {
+        (self.serialize)(node)
+    }
+
+    pub fn deserialize(&self, data: &str) -> Result<CustomNode, ron::error::SpannedError> {
+        (self.deserialize)(data)
+    }
+
+    /// Creates a new custom node with a copy of the given node's data.
+    pub fn clone_node(&self, node: &dyn NodeLike) -> Option<CustomNode> {
+        (self.from_reflect)(node.as_reflect())
+    }
+}
+
+fn serialize_node<T: SerializableNode>(node: &dyn NodeLike) -> Result<String, ron::Error> {
+    let node = node.as_any().downcast_ref::<T>().ok_or_else(|| {
+        ron::Error::Message(format!(
+            "Node is not of the registered type {}",
+            T::type_path()
+        ))
+    })?;
+    ron::ser::to_string(node)
+}
+
+/// Registry of custom node types, needed to load animation graphs containing custom nodes.
+///
+/// Node types are registered with [`AnimationGraphAppExt::register_custom_node`], and custom
+/// nodes are created with [`CustomNode::registered`] so that they can be saved.
+///
+/// [`AnimationGraphAppExt::register_custom_node`]: crate::core::plugin::AnimationGraphAppExt::register_custom_node
+#[derive(Resource, Clone, Default)]
+pub struct CustomNodeRegistry {
+    registrations: Arc<RwLock<HashMap<String, CustomNodeRegistration>>>,
+}
+
+impl CustomNodeRegistry {
+    pub fn register<T: SerializableNode>(&self) {
+        let registration = CustomNodeRegistration::of::<T>();
+        self.registrations
+            .write()
+            .unwrap()
+            .insert(registration.type_path().into(), registration);
+    }
+
+    pub fn get(&self, type_path: &str) -> Option<CustomNodeRegistration> {
+        self.registrations.read().unwrap().get(type_path).copied()
+    }
+
+    /// Returns the type paths of all registered custom node types
+    pub fn type_paths(&self) -> Vec<String> {
+        self.registrations.read().unwrap().keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        animation_graph::serial::AnimationNodeTypeSerial, animation_node::AnimationNodeType,
+    };
+    use serde::Deserialize;
+
+    #[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+    enum TestMode {
+        #[default]
+        Off,
+        On(f32),
+    }
+
+    #[derive(Reflect, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+    struct TestNode {
+        mode: TestMode,
+        names: Vec<String>,
+    }
+
+    impl NodeLike for TestNode {
+        fn display_name(&self) -> String {
+            "Test".into()
+        }
+    }
+
+    #[test]
+    fn test_custom_node_round_trip() {
+        let registry = CustomNodeRegistry::default();
+        registry.register::<TestNode>();
+
+        let node = TestNode {
+            mode: TestMode::On(0.5),
+            names: vec!["a".into(), "b".into()],
+        };
+        let node_type = AnimationNodeType::Custom(CustomNode::registered(node.clone()));
+        let AnimationNodeTypeSerial::Custom { type_path, data } = (&node_type).try_into().unwrap()
+        else {
+            panic!("Custom node serialized into wrong variant");
+        };
+
+        let loaded = registry
+            .get(&type_path)
+            .unwrap()
+            .deserialize(&data)
+            .unwrap();
+        let loaded = loaded.node.lock().unwrap();
+        assert_eq!(loaded.as_any().downcast_ref::<TestNode>(), Some(&node));
+    }
+}
@@ -1,17 +1,17 @@
-use crate::core::pose::BoneId;
+use crate::core::{animation_clip::EntityPath, pose::BoneId, skeleton::Skeleton};
 use bevy::{
     reflect::{std_traits::ReflectDefault, Reflect},
     utils::HashMap,
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Clone, Debug)]
+#[derive(Reflect, Clone, Debug, PartialEq)]
 #[reflect(Default)]
 pub enum BoneMask {
     /// If a bone is in the bones map, weight is given. Otherwise, weight is zero
-    Positive { bones: HashMap<BoneId, f32> },
+    Positive { bones: HashMap<EntityPath, f32> },
     /// If a bone is not in bones map, weight is 1. Otherwise, weight is as given
-    Negative { bones: HashMap<BoneId, f32> },
+    Negative { bones: HashMap<EntityPath, f32> },
 }
 
 impl Default for BoneMask {
@@ -23,12 +23,74 @@ impl Default for BoneMask {
 }
 
 impl BoneMask {
-    pub fn bone_weight(&self, bone_id: &BoneId) -> f32 {
+    pub fn bone_weight(&self, path: &EntityPath) -> f32 {
         match self {
-            BoneMask::Positive { bones } => bones.get(bone_id).copied().unwrap_or(0.),
-            BoneMask::Negative { bones } => bones.get(bone_id).copied().unwrap_or(1.),
+            BoneMask::Positive { bones } => bones.get(path).copied().unwrap_or(0.),
+            BoneMask::Negative { bones } => bones.get(path).copied().unwrap_or(1.),
         }
     }
+
+    /// Like [`BoneMask::bone_weight`], but bones not in the bones map inherit the weight of their
+    /// closest ancestor that is.
+    pub fn bone_weight_propagated(&self, path: &EntityPath) -> f32 {
+        let bones = match self {
+            BoneMask::Positive { bones } | BoneMask::Negative { bones } => bones,
+        };
+
+        let mut current = Some(path.clone());
+        while let Some(path) = current {
+            if let Some(weight) = bones.get(&path) {
+                return *weight;
+            }
+            current = path.parent();
+        }
+
+        self.bone_weight(path)
+    }
+
+    /// Resolves the bones in the mask to [`BoneId`]s, for lookups during pose evaluation.
+    pub fn resolve(&self) -> ResolvedBoneMask {
+        let (bones, default_weight) = match self {
+            BoneMask::Positive { bones } => (bones, 0.),
+            BoneMask::Negative { bones } => (bones, 1.),
+        };
+
+        ResolvedBoneMask {
+            bones: bones.iter().map(|(path, w)| (path.id(), *w)).collect(),
+            default_weight,
+        }
+    }
+}
+
+/// A [`BoneMask`] keyed by [`BoneId`]
+#[derive(Clone, Debug, Default)]
+pub struct ResolvedBoneMask {
+    bones: HashMap<BoneId, f32>,
+    /// Weight of bones not in the mask
+    default_weight: f32,
+}
+
+impl ResolvedBoneMask {
+    pub fn bone_weight(&self, bone_id: BoneId) -> f32 {
+        self.bones
+            .get(&bone_id)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+
+    /// Like [`ResolvedBoneMask::bone_weight`], but bones not in the mask inherit the weight of
+    /// their closest ancestor in the skeleton that is.
+    pub fn bone_weight_propagated(&self, bone_id: BoneId, skeleton: &Skeleton) -> f32 {
+        let mut current = Some(bone_id);
+        while let Some(bone_id) = current {
+            if let Some(weight) = self.bones.get(&bone_id) {
+                return *weight;
+            }
+            current = skeleton.parent(bone_id);
+        }
+
+        self.default_weight
+    }
 }
 
 #[derive(Reflect, Clone, Serialize, Deserialize, Debug)]
@@ -37,11 +99,11 @@ pub enum BoneMaskSerial {
     Negative { bones: HashMap<Vec<String>, f32> },
 }
 
-fn deserialize_bone_map(map: HashMap<Vec<String>, f32>) -> HashMap<BoneId, f32> {
+fn deserialize_bone_map(map: HashMap<Vec<String>, f32>) -> HashMap<EntityPath, f32> {
     map.into_iter().map(|(k, v)| (k.into(), v)).collect()
 }
 
-fn serialize_bone_map(map: HashMap<BoneId, f32>) -> HashMap<Vec<String>, f32> {
+fn serialize_bone_map(map: HashMap<EntityPath, f32>) -> HashMap<Vec<String>, f32> {
     map.into_iter().map(|(k, v)| (k.into(), v)).collect()
 }
 
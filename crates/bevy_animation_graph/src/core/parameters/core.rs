@@ -38,6 +38,10 @@ pub enum ParamSpec {
     EntityPath,
     Quat,
     BoneMask,
+    Bool,
+    I32,
+    /// Variant name of an enumeration
+    Enum,
 }
 
 #[derive(Serialize, Deserialize, Reflect, Clone, Debug)]
@@ -47,6 +51,10 @@ pub enum ParamValue {
     EntityPath(EntityPath),
     Quat(Quat),
     BoneMask(BoneMask),
+    Bool(bool),
+    I32(i32),
+    /// Variant name of an enumeration
+    Enum(String),
 }
 
 impl Default for ParamValue {
@@ -100,6 +108,33 @@ impl Unwrap<Vec3> for ParamValue {
     }
 }
 
+impl Unwrap<bool> for ParamValue {
+    fn unwrap(self) -> bool {
+        match self {
+            ParamValue::Bool(b) => b,
+            _ => panic!("Expected Bool, found {:?}", ParamSpec::from(&self)),
+        }
+    }
+}
+
+impl Unwrap<i32> for ParamValue {
+    fn unwrap(self) -> i32 {
+        match self {
+            ParamValue::I32(i) => i,
+            _ => panic!("Expected I32, found {:?}", ParamSpec::from(&self)),
+        }
+    }
+}
+
+impl Unwrap<String> for ParamValue {
+    fn unwrap(self) -> String {
+        match self {
+            ParamValue::Enum(variant) => variant,
+            _ => panic!("Expected Enum, found {:?}", ParamSpec::from(&self)),
+        }
+    }
+}
+
 impl ParamValue {
     pub fn unwrap_f32(self) -> f32 {
         match self {
@@ -121,6 +156,18 @@ impl From<Vec3> for ParamValue {
     }
 }
 
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for ParamValue {
+    fn from(value: i32) -> Self {
+        Self::I32(value)
+    }
+}
+
 impl From<&ParamValue> for ParamSpec {
     fn from(value: &ParamValue) -> Self {
         match value {
@@ -129,6 +176,9 @@ impl From<&ParamValue> for ParamSpec {
             ParamValue::EntityPath(_) => ParamSpec::EntityPath,
             ParamValue::Quat(_) => ParamSpec::Quat,
             ParamValue::BoneMask(_) => ParamSpec::BoneMask,
+            ParamValue::Bool(_) => ParamSpec::Bool,
+            ParamValue::I32(_) => ParamSpec::I32,
+            ParamValue::Enum(_) => ParamSpec::Enum,
         }
     }
 }
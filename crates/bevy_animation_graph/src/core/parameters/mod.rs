@@ -1,7 +1,7 @@
 mod bone_mask;
 mod core;
 
-pub use bone_mask::BoneMask;
+pub use bone_mask::{BoneMask, ResolvedBoneMask};
 pub use core::OptParamSpec;
 pub use core::ParamSpec;
 pub use core::ParamValue;
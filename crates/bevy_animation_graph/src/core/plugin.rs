@@ -4,17 +4,32 @@ use super::{
     },
     animation_graph::loader::{AnimationGraphLoader, GraphClipLoader},
     frame::PoseSpec,
+    node_registry::{CustomNodeRegistry, SerializableNode},
     parameters::{BoneMask, ParamSpec, ParamValue},
-    systems::{animation_player, animation_player_deferred_gizmos},
+    systems::{animation_player, animation_player_deferred_gizmos, invalidate_player_skeletons},
 };
 use crate::prelude::{
     config::{FlipConfig, FlipNameMapper, PatternMapper, PatternMapperSerial},
-    AbsF32, AddF32, AnimationGraph, AnimationGraphPlayer, AnimationNodeType, BlendNode, ChainNode,
-    ClampF32, ClipNode, DivF32, DummyNode, ExtendSkeleton, FlipLRNode, GraphClip, GraphNode,
-    IntoBoneSpaceNode, IntoCharacterSpaceNode, IntoGlobalSpaceNode, LoopNode, MulF32,
-    RotationArcNode, RotationNode, SpeedNode, SubF32, TwoBoneIKNode,
+    AbsF32, AddF32, AddVec3, AndBool, AnimationEvent, AnimationGraph, AnimationGraphPlayer,
+    AnimationNodeType, ApplyAdditiveNode, BlendNode, BlendSample1D, BlendSample2D,
+    BlendSpace1DNode, BlendSpace2DNode, BlendSpacePlane, BlendSyncMode, BuildVec3, ChainNode,
+    ClampF32, ClipNode, CompareF32, CompareI32, Comparison, CrossVec3, DecomposeVec3, DivF32,
+    DotVec3, DummyNode, EqualEnum, ExpressionNode, ExtendSkeleton, FlipLRNode, FootPlacementNode,
+    GraphClip, GraphNode, IkChainNode, IkSolver, IntoBoneSpaceNode, IntoCharacterSpaceNode,
+    IntoGlobalSpaceNode, InvertQuat, LayeredBlendNode, LengthVec3, LerpVec3, LookAtNode, LoopNode,
+    MakeAdditiveNode, MulF32, MulQuat, NormalizeVec3, NotBool, OrBool, QuatFromAxisAngle,
+    QuatFromEuler, RootMotionNode, RotationArcNode, RotationNode, ScaleVec3, SelectNode, SlerpQuat,
+    SmoothF32, SmoothQuat, SmoothVec3, Smoothing, SpeedNode, StateMachineNode, SubF32, SubVec3,
+    SwitchNode, TransitionBlend, TwoBoneIKNode,
+};
+use crate::{
+    core::{
+        animation_clip::{EntityPath, Interpolation},
+        pose::BoneId,
+    },
+    prelude::AnimationNode,
+    retargeting::{RetargetBone, RetargetMap, RetargetMapLoader},
 };
-use crate::{core::animation_clip::EntityPath, prelude::AnimationNode};
 use bevy::{prelude::*, transform::TransformSystem};
 
 /// Adds animation support to an app
@@ -28,19 +43,45 @@ impl Plugin for AnimationGraphPlugin {
             .init_asset::<GraphClip>()
             .init_asset_loader::<GraphClipLoader>()
             .init_asset::<AnimationGraph>()
+            .init_resource::<CustomNodeRegistry>()
             .init_asset_loader::<AnimationGraphLoader>()
             .init_asset::<AnimatedScene>()
             .init_asset_loader::<AnimatedSceneLoader>()
+            .init_asset::<RetargetMap>()
+            .init_asset_loader::<RetargetMapLoader>()
+            .add_event::<AnimationEvent>()
             .add_systems(PreUpdate, (spawn_animated_scenes, process_animated_scenes))
             .add_systems(
                 PostUpdate,
-                (animation_player, animation_player_deferred_gizmos)
+                (
+                    invalidate_player_skeletons,
+                    animation_player,
+                    animation_player_deferred_gizmos,
+                )
                     .chain()
                     .before(TransformSystem::TransformPropagate),
             );
     }
 }
 
+/// Extension trait for registering custom node types in an [`App`]
+pub trait AnimationGraphAppExt {
+    /// Registers a custom node type, so that animation graphs containing nodes of this type can
+    /// be loaded and inspected in the editor. Nodes of this type should be created with
+    /// [`CustomNode::registered`](crate::core::animation_node::CustomNode::registered).
+    fn register_custom_node<T: SerializableNode>(&mut self) -> &mut Self;
+}
+
+impl AnimationGraphAppExt for App {
+    fn register_custom_node<T: SerializableNode>(&mut self) -> &mut Self {
+        self.register_type::<T>();
+        self.world
+            .get_resource_or_insert_with(CustomNodeRegistry::default)
+            .register::<T>();
+        self
+    }
+}
+
 impl AnimationGraphPlugin {
     fn register_types(&self, app: &mut App) {
         app //
@@ -50,8 +91,13 @@ impl AnimationGraphPlugin {
             .register_asset_reflect::<GraphClip>()
             .register_type::<AnimatedScene>()
             .register_asset_reflect::<AnimatedScene>()
+            .register_type::<RetargetMap>()
+            .register_type::<RetargetBone>()
+            .register_asset_reflect::<RetargetMap>()
             .register_type::<AnimationGraphPlayer>()
             .register_type::<EntityPath>()
+            .register_type::<Interpolation>()
+            .register_type::<BoneId>()
             .register_type::<BoneMask>()
             .register_type::<ParamValue>()
             .register_type::<ParamSpec>()
@@ -65,22 +111,67 @@ impl AnimationGraphPlugin {
         // --- Node registrations
         // ------------------------------------------
             .register_type::<BlendNode>()
+            .register_type::<BlendSyncMode>()
+            .register_type::<BlendSpace1DNode>()
+            .register_type::<BlendSample1D>()
+            .register_type::<BlendSpace2DNode>()
+            .register_type::<BlendSample2D>()
+            .register_type::<BlendSpacePlane>()
             .register_type::<ChainNode>()
             .register_type::<ClipNode>()
+            .register_type::<LayeredBlendNode>()
             .register_type::<DummyNode>()
             .register_type::<FlipLRNode>()
             .register_type::<GraphNode>()
             .register_type::<LoopNode>()
+            .register_type::<MakeAdditiveNode>()
+            .register_type::<ApplyAdditiveNode>()
+            .register_type::<RootMotionNode>()
             .register_type::<RotationNode>()
             .register_type::<SpeedNode>()
+            .register_type::<StateMachineNode>()
+            .register_type::<TransitionBlend>()
             .register_type::<TwoBoneIKNode>()
+            .register_type::<IkChainNode>()
+            .register_type::<IkSolver>()
+            .register_type::<LookAtNode>()
+            .register_type::<FootPlacementNode>()
             .register_type::<AbsF32>()
+            .register_type::<ExpressionNode>()
             .register_type::<AddF32>()
             .register_type::<ClampF32>()
             .register_type::<DivF32>()
             .register_type::<MulF32>()
             .register_type::<SubF32>()
             .register_type::<RotationArcNode>()
+            .register_type::<AddVec3>()
+            .register_type::<SubVec3>()
+            .register_type::<ScaleVec3>()
+            .register_type::<DotVec3>()
+            .register_type::<CrossVec3>()
+            .register_type::<NormalizeVec3>()
+            .register_type::<LengthVec3>()
+            .register_type::<LerpVec3>()
+            .register_type::<BuildVec3>()
+            .register_type::<DecomposeVec3>()
+            .register_type::<MulQuat>()
+            .register_type::<InvertQuat>()
+            .register_type::<SlerpQuat>()
+            .register_type::<QuatFromAxisAngle>()
+            .register_type::<QuatFromEuler>()
+            .register_type::<SmoothF32>()
+            .register_type::<SmoothVec3>()
+            .register_type::<SmoothQuat>()
+            .register_type::<Smoothing>()
+            .register_type::<AndBool>()
+            .register_type::<OrBool>()
+            .register_type::<NotBool>()
+            .register_type::<CompareF32>()
+            .register_type::<CompareI32>()
+            .register_type::<EqualEnum>()
+            .register_type::<SelectNode>()
+            .register_type::<SwitchNode>()
+            .register_type::<Comparison>()
             .register_type::<ExtendSkeleton>()
             .register_type::<IntoBoneSpaceNode>()
             .register_type::<IntoGlobalSpaceNode>()
@@ -1,9 +1,21 @@
-use bevy::{asset::prelude::*, math::prelude::*, reflect::prelude::*, utils::HashMap};
+use bevy::{
+    asset::prelude::*,
+    math::prelude::*,
+    reflect::{prelude::*, std_traits::ReflectDefault},
+    utils::HashMap,
+};
 
-use super::animation_clip::EntityPath;
-
-// PERF: Bone ids should become integers eventually
-pub type BoneId = EntityPath;
+/// Integer identifier of a bone, used as key in pose data instead of its
+/// [`EntityPath`](crate::core::animation_clip::EntityPath).
+///
+/// Ids are derived from the bone's path with a fixed hash (see
+/// [`EntityPath::id`](crate::core::animation_clip::EntityPath::id)), so the same bone gets the
+/// same id in every clip and skeleton without any shared lookup table. Paths whose ids collide
+/// are rejected when loading a clip and skipped with a warning when building a
+/// [`Skeleton`](crate::core::skeleton::Skeleton).
+#[derive(Reflect, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[reflect(Default)]
+pub struct BoneId(pub(crate) u64);
 
 /// Vertical slice of a [`Keyframes`] that represents an instant in an animation [`Transform`].
 ///
@@ -20,16 +32,31 @@ pub struct BonePose {
 /// Vertical slice of an [`GraphClip`]
 ///
 /// [`GraphClip`]: crate::prelude::GraphClip
-#[derive(Asset, Reflect, Clone, Debug, Default)]
+#[derive(Asset, Reflect, Debug, Default)]
 pub struct Pose {
     pub(crate) bones: Vec<BonePose>,
-    pub(crate) paths: HashMap<BoneId, usize>,
+    pub(crate) ids: HashMap<BoneId, usize>,
+}
+
+impl Clone for Pose {
+    fn clone(&self) -> Self {
+        Self {
+            bones: self.bones.clone(),
+            ids: self.ids.clone(),
+        }
+    }
+
+    /// Reuses the allocations of `self`, for poses that are overwritten every frame
+    fn clone_from(&mut self, source: &Self) {
+        self.bones.clone_from(&source.bones);
+        self.ids.clone_from(&source.ids);
+    }
 }
 
 impl Pose {
-    pub fn add_bone(&mut self, pose: BonePose, path: BoneId) {
-        let id = self.bones.len();
-        self.bones.insert(id, pose);
-        self.paths.insert(path, id);
+    pub fn add_bone(&mut self, pose: BonePose, bone_id: BoneId) {
+        let idx = self.bones.len();
+        self.bones.insert(idx, pose);
+        self.ids.insert(bone_id, idx);
     }
 }
@@ -0,0 +1,105 @@
+use super::{animation_clip::EntityPath, pose::BoneId};
+use crate::prelude::SystemResources;
+use bevy::{ecs::entity::Entity, log::prelude::*, utils::HashMap};
+use std::collections::VecDeque;
+
+/// A bone in a [`Skeleton`]
+#[derive(Clone, Debug)]
+pub struct SkeletonBone {
+    pub id: BoneId,
+    pub path: EntityPath,
+    /// `None` for the root bone
+    pub parent: Option<BoneId>,
+    pub entity: Entity,
+}
+
+/// Named entity hierarchy under an animation player, mapping between the [`EntityPath`]s used at
+/// the API edges and the [`BoneId`]s used in pose data.
+///
+/// Bones are stored in breadth-first order, so every bone comes after its parent.
+#[derive(Clone, Debug, Default)]
+pub struct Skeleton {
+    bones: Vec<SkeletonBone>,
+    indices: HashMap<BoneId, usize>,
+}
+
+impl Skeleton {
+    /// Builds the skeleton rooted at `root_entity`. Entities without a [`Name`] are skipped
+    /// together with their descendants, since they cannot be addressed by an [`EntityPath`].
+    /// Entities whose path has the same [`BoneId`] as an earlier bone are skipped the same way,
+    /// with a warning.
+    ///
+    /// [`Name`]: bevy::core::Name
+    pub fn build(root_entity: Entity, resources: &SystemResources) -> Self {
+        let mut skeleton = Self::default();
+
+        let Ok(root_name) = resources.names_query.get(root_entity) else {
+            return skeleton;
+        };
+        let root_path = EntityPath {
+            parts: vec![root_name.clone()],
+        };
+
+        let mut queue: VecDeque<(Entity, EntityPath, Option<BoneId>)> = VecDeque::new();
+        queue.push_back((root_entity, root_path, None));
+
+        while let Some((entity, path, parent)) = queue.pop_front() {
+            let id = path.id();
+            if let Some(other) = skeleton.bone(id) {
+                warn!(
+                    "Bone paths {} and {} have the same bone id, skipping the latter.",
+                    other.path.to_slashed_string(),
+                    path.to_slashed_string()
+                );
+                continue;
+            }
+            if let Ok(children) = resources.children_query.get(entity) {
+                for child in children {
+                    let Ok(name) = resources.names_query.get(*child) else {
+                        continue;
+                    };
+                    queue.push_back((*child, path.child(name.clone()), Some(id)));
+                }
+            }
+            skeleton.indices.insert(id, skeleton.bones.len());
+            skeleton.bones.push(SkeletonBone {
+                id,
+                path,
+                parent,
+                entity,
+            });
+        }
+
+        skeleton
+    }
+
+    /// All bones, parents before children. The first bone is the root.
+    pub fn bones(&self) -> &[SkeletonBone] {
+        &self.bones
+    }
+
+    pub fn bone(&self, bone_id: BoneId) -> Option<&SkeletonBone> {
+        self.indices.get(&bone_id).map(|idx| &self.bones[*idx])
+    }
+
+    /// Id of the root bone, which is the entity holding the animation player
+    pub fn root(&self) -> Option<BoneId> {
+        self.bones.first().map(|bone| bone.id)
+    }
+
+    pub fn parent(&self, bone_id: BoneId) -> Option<BoneId> {
+        self.bone(bone_id).and_then(|bone| bone.parent)
+    }
+
+    pub fn entity(&self, bone_id: BoneId) -> Option<Entity> {
+        self.bone(bone_id).map(|bone| bone.entity)
+    }
+
+    pub fn path(&self, bone_id: BoneId) -> Option<&EntityPath> {
+        self.bone(bone_id).map(|bone| &bone.path)
+    }
+
+    pub fn contains(&self, bone_id: BoneId) -> bool {
+        self.indices.contains_key(&bone_id)
+    }
+}
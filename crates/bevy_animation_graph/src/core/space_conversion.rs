@@ -1,13 +1,12 @@
 use super::{
-    animation_clip::EntityPath,
     context::PassContext,
     frame::{
-        BoneFrame, BonePoseFrame, CharacterPoseFrame, GlobalPoseFrame, InnerPoseFrame, ValueFrame,
+        BoneFrame, BonePoseFrame, CharacterPoseFrame, FrameInterpolation, GlobalPoseFrame,
+        InnerPoseFrame, ValueFrame,
     },
     pose::BoneId,
 };
-use bevy::{ecs::entity::Entity, transform::components::Transform, utils::HashMap};
-use std::collections::VecDeque;
+use bevy::{transform::components::Transform, utils::HashMap};
 
 pub trait SpaceConversion {
     fn bone_to_character(&self, data: &BonePoseFrame) -> CharacterPoseFrame;
@@ -95,10 +94,6 @@ pub trait SpaceConversion {
 
 impl SpaceConversion for PassContext<'_> {
     fn bone_to_character(&self, data: &BonePoseFrame) -> CharacterPoseFrame {
-        let root_name = self.resources.names_query.get(self.root_entity).unwrap();
-        let root_path = EntityPath {
-            parts: vec![root_name.clone()],
-        };
         let root_transform_frame = ValueFrame {
             prev: Transform::IDENTITY,
             prev_timestamp: f32::MIN,
@@ -106,32 +101,28 @@ impl SpaceConversion for PassContext<'_> {
             next_timestamp: f32::MAX,
             prev_is_wrapped: true,
             next_is_wrapped: true,
+            interpolation: FrameInterpolation::Linear,
         };
 
-        let root_children = self.resources.children_query.get(self.root_entity).unwrap();
-
-        let mut character_transforms: HashMap<EntityPath, ValueFrame<Transform>> = HashMap::new();
-        let mut queue: VecDeque<(Entity, EntityPath, ValueFrame<Transform>)> = VecDeque::new();
+        let mut character_transforms: HashMap<BoneId, ValueFrame<Transform>> = HashMap::new();
 
-        for child in root_children {
-            queue.push_back((*child, root_path.clone(), root_transform_frame.clone()));
-        }
+        // Bones are sorted parents first, so the parent's transform is always computed by the
+        // time we reach a bone
+        for bone in self.skeleton.bones() {
+            let Some(parent_id) = bone.parent else {
+                character_transforms.insert(bone.id, root_transform_frame.clone());
+                continue;
+            };
+            let parent_transform_frame = &character_transforms[&parent_id];
 
-        while !queue.is_empty() {
-            let (entity, parent_path, parent_transform_frame) = queue.pop_front().unwrap();
             // --- Compute the updated transform frame
             // -------------------------------------------------------
-            // First, build the entity path for the current entity
-            let entity_name = self.resources.names_query.get(entity).unwrap();
-            let entity_path = parent_path.child(entity_name.clone());
-
             // Get the entity's current local transform
-            let (entity_transform, _) = self.resources.transform_query.get(entity).unwrap();
+            let (entity_transform, _) = self.resources.transform_query.get(bone.entity).unwrap();
             let inner_data = data.inner_ref();
             // Get the corresponding bone frame
-            let bone_frame: BoneFrame = if inner_data.paths.contains_key(&entity_path) {
-                let bone_id = inner_data.paths.get(&entity_path).unwrap();
-                inner_data.bones[*bone_id].clone()
+            let bone_frame: BoneFrame = if let Some(idx) = inner_data.ids.get(&bone.id) {
+                inner_data.bones[*idx].clone()
             } else {
                 BoneFrame::default()
             };
@@ -144,23 +135,14 @@ impl SpaceConversion for PassContext<'_> {
                 next_timestamp: f32::MAX,
                 prev_is_wrapped: true,
                 next_is_wrapped: true,
+                interpolation: FrameInterpolation::Linear,
             };
             let local_transform_frame =
                 bone_frame.to_transform_frame_linear_with_base_frame(local_transform_frame);
 
             let character_transform_frame = parent_transform_frame
                 .merge_linear(&local_transform_frame, |parent, child| *child * *parent);
-            character_transforms.insert(entity_path.clone(), character_transform_frame.clone());
-
-            if let Ok(children) = self.resources.children_query.get(entity) {
-                for child in children {
-                    queue.push_back((
-                        *child,
-                        entity_path.clone(),
-                        character_transform_frame.clone(),
-                    ));
-                }
-            }
+            character_transforms.insert(bone.id, character_transform_frame);
             // -------------------------------------------------------
         }
 
@@ -173,12 +155,13 @@ impl SpaceConversion for PassContext<'_> {
         let mut final_pose_frame = CharacterPoseFrame::default();
         let inner_character_frame = final_pose_frame.inner_mut();
 
-        for (path, bone_id) in data.inner_ref().paths.iter() {
-            let local_bone_frame = &data.inner_ref().bones[*bone_id];
-            let character_transform_frame = character_transforms.get(path).unwrap();
-            let character_translation_frame = character_transform_frame.map(|t| t.translation);
-            let character_rotation_frame = character_transform_frame.map(|t| t.rotation);
-            let character_scale_frame = character_transform_frame.map(|t| t.scale);
+        for (bone_id, idx) in data.inner_ref().ids.iter() {
+            let local_bone_frame = &data.inner_ref().bones[*idx];
+            let character_transform_frame = character_transforms.get(bone_id).unwrap();
+            let character_translation_frame =
+                character_transform_frame.map_linear(|t| t.translation);
+            let character_rotation_frame = character_transform_frame.map_linear(|t| t.rotation);
+            let character_scale_frame = character_transform_frame.map_linear(|t| t.scale);
 
             let character_bone_frame = BoneFrame {
                 rotation: Some(character_rotation_frame),
@@ -187,7 +170,7 @@ impl SpaceConversion for PassContext<'_> {
                 weights: local_bone_frame.weights.clone(),
             };
 
-            inner_character_frame.add_bone(character_bone_frame, path.clone());
+            inner_character_frame.add_bone(character_bone_frame, *bone_id);
         }
         // -------------------------------------------------------
 
@@ -200,10 +183,6 @@ impl SpaceConversion for PassContext<'_> {
     }
 
     fn character_to_bone(&self, data: &CharacterPoseFrame) -> BonePoseFrame {
-        let root_name = self.resources.names_query.get(self.root_entity).unwrap();
-        let root_path = EntityPath {
-            parts: vec![root_name.clone()],
-        };
         let root_transform_frame = ValueFrame {
             prev: Transform::IDENTITY,
             prev_timestamp: f32::MIN,
@@ -211,48 +190,42 @@ impl SpaceConversion for PassContext<'_> {
             next_timestamp: f32::MAX,
             prev_is_wrapped: true,
             next_is_wrapped: true,
+            interpolation: FrameInterpolation::Linear,
         };
 
-        let root_children = self.resources.children_query.get(self.root_entity).unwrap();
-
-        let mut bone_transforms: HashMap<EntityPath, ValueFrame<Transform>> = HashMap::new();
-        let mut queue: VecDeque<(
-            Entity,
-            EntityPath,
-            ValueFrame<Transform>,
-            ValueFrame<Transform>,
-        )> = VecDeque::new();
-
-        for child in root_children {
-            queue.push_back((
-                *child,
-                root_path.clone(),
-                root_transform_frame.clone(),
-                root_transform_frame.clone(),
-            ));
-        }
+        let mut bone_transforms: HashMap<BoneId, ValueFrame<Transform>> = HashMap::new();
+        // Character space transform of each bone, and its inverse
+        let mut character_transforms: HashMap<
+            BoneId,
+            (ValueFrame<Transform>, ValueFrame<Transform>),
+        > = HashMap::new();
+
+        // Bones are sorted parents first, so the parent's transform is always computed by the
+        // time we reach a bone
+        for bone in self.skeleton.bones() {
+            let Some(parent_id) = bone.parent else {
+                character_transforms.insert(
+                    bone.id,
+                    (root_transform_frame.clone(), root_transform_frame.clone()),
+                );
+                continue;
+            };
+            let (parent_transform_frame, parent_inverse_transform_frame) =
+                &character_transforms[&parent_id];
 
-        while !queue.is_empty() {
-            let (entity, parent_path, parent_transform_frame, parent_inverse_transform_frame) =
-                queue.pop_front().unwrap();
             // --- Compute the updated transform frame
             // -------------------------------------------------------
-            // First, build the entity path for the current entity
-            let entity_name = self.resources.names_query.get(entity).unwrap();
-            let entity_path = parent_path.child(entity_name.clone());
-
             // Get the entity's current local transform (in parent bone space)
-            let (entity_transform, _) = self.resources.transform_query.get(entity).unwrap();
+            let (entity_transform, _) = self.resources.transform_query.get(bone.entity).unwrap();
             let inner_data = data.inner_ref();
             // Get the corresponding bone frame in character space
-            let bone_frame: BoneFrame = if inner_data.paths.contains_key(&entity_path) {
-                let bone_id = inner_data.paths.get(&entity_path).unwrap();
-                inner_data.bones[*bone_id].clone()
+            let bone_frame: BoneFrame = if let Some(idx) = inner_data.ids.get(&bone.id) {
+                inner_data.bones[*idx].clone()
             } else {
                 BoneFrame {
-                    translation: Some(parent_transform_frame.map(|t| t.translation)),
-                    rotation: Some(parent_transform_frame.map(|t| t.rotation)),
-                    scale: Some(parent_transform_frame.map(|t| t.scale)),
+                    translation: Some(parent_transform_frame.map_linear(|t| t.translation)),
+                    rotation: Some(parent_transform_frame.map_linear(|t| t.rotation)),
+                    scale: Some(parent_transform_frame.map_linear(|t| t.scale)),
                     ..Default::default()
                 }
             };
@@ -265,27 +238,23 @@ impl SpaceConversion for PassContext<'_> {
                 next_timestamp: f32::MAX,
                 prev_is_wrapped: true,
                 next_is_wrapped: true,
+                interpolation: FrameInterpolation::Linear,
             }
-            .merge_linear(&parent_transform_frame, |child, parent| *child * *parent);
+            .merge_linear(parent_transform_frame, |child, parent| *child * *parent);
 
             let character_transform_frame =
                 bone_frame.to_transform_frame_linear_with_base_frame(character_transform_frame);
 
             let bone_transform_frame = parent_inverse_transform_frame
                 .merge_linear(&character_transform_frame, |parent, child| *child * *parent);
-            bone_transforms.insert(entity_path.clone(), bone_transform_frame.clone());
-
-            if let Ok(children) = self.resources.children_query.get(entity) {
-                for child in children {
-                    queue.push_back((
-                        *child,
-                        entity_path.clone(),
-                        character_transform_frame.clone(),
-                        character_transform_frame
-                            .map(|t| Transform::from_matrix(t.compute_matrix().inverse())),
-                    ));
-                }
-            }
+            bone_transforms.insert(bone.id, bone_transform_frame);
+
+            let character_inverse_transform_frame = character_transform_frame
+                .map(|t| Transform::from_matrix(t.compute_matrix().inverse()));
+            character_transforms.insert(
+                bone.id,
+                (character_transform_frame, character_inverse_transform_frame),
+            );
             // -------------------------------------------------------
         }
 
@@ -298,12 +267,13 @@ impl SpaceConversion for PassContext<'_> {
         let mut final_pose_frame = BonePoseFrame::default();
         let inner_character_frame = final_pose_frame.inner_mut();
 
-        for (path, bone_id) in data.inner_ref().paths.iter() {
-            let local_bone_frame = &data.inner_ref().bones[*bone_id];
-            let character_transform_frame = bone_transforms.get(path).unwrap();
-            let character_translation_frame = character_transform_frame.map(|t| t.translation);
-            let character_rotation_frame = character_transform_frame.map(|t| t.rotation);
-            let character_scale_frame = character_transform_frame.map(|t| t.scale);
+        for (bone_id, idx) in data.inner_ref().ids.iter() {
+            let local_bone_frame = &data.inner_ref().bones[*idx];
+            let character_transform_frame = bone_transforms.get(bone_id).unwrap();
+            let character_translation_frame =
+                character_transform_frame.map_linear(|t| t.translation);
+            let character_rotation_frame = character_transform_frame.map_linear(|t| t.rotation);
+            let character_scale_frame = character_transform_frame.map_linear(|t| t.scale);
 
             let character_bone_frame = BoneFrame {
                 rotation: Some(character_rotation_frame),
@@ -312,7 +282,7 @@ impl SpaceConversion for PassContext<'_> {
                 weights: local_bone_frame.weights.clone(),
             };
 
-            inner_character_frame.add_bone(character_bone_frame, path.clone());
+            inner_character_frame.add_bone(character_bone_frame, *bone_id);
         }
         // -------------------------------------------------------
 
@@ -336,8 +306,8 @@ impl SpaceConversion for PassContext<'_> {
         let mut final_pose_frame = GlobalPoseFrame::default();
         let inner_global_frame = final_pose_frame.inner_mut();
 
-        for (path, bone_id) in data.inner_ref().paths.iter() {
-            let global_bone_frame = &data.inner_ref().bones[*bone_id];
+        for (bone_id, idx) in data.inner_ref().ids.iter() {
+            let global_bone_frame = &data.inner_ref().bones[*idx];
 
             let global_bone_frame = BoneFrame {
                 rotation: global_bone_frame
@@ -355,7 +325,7 @@ impl SpaceConversion for PassContext<'_> {
                 weights: global_bone_frame.weights.clone(),
             };
 
-            inner_global_frame.add_bone(global_bone_frame, path.clone());
+            inner_global_frame.add_bone(global_bone_frame, *bone_id);
         }
         // -------------------------------------------------------
 
@@ -385,8 +355,8 @@ impl SpaceConversion for PassContext<'_> {
         let mut final_pose_frame = CharacterPoseFrame::default();
         let inner_character_frame = final_pose_frame.inner_mut();
 
-        for (path, bone_id) in data.inner_ref().paths.iter() {
-            let global_bone_frame = &data.inner_ref().bones[*bone_id];
+        for (bone_id, idx) in data.inner_ref().ids.iter() {
+            let global_bone_frame = &data.inner_ref().bones[*idx];
 
             let character_bone_frame = BoneFrame {
                 rotation: global_bone_frame
@@ -404,7 +374,7 @@ impl SpaceConversion for PassContext<'_> {
                 weights: global_bone_frame.weights.clone(),
             };
 
-            inner_character_frame.add_bone(character_bone_frame, path.clone());
+            inner_character_frame.add_bone(character_bone_frame, *bone_id);
         }
         // -------------------------------------------------------
 
@@ -419,23 +389,22 @@ impl SpaceConversion for PassContext<'_> {
         target: BoneId,
         timestamp: f32,
     ) -> Transform {
-        let mut curr_path = target;
+        let mut curr_bone = target;
         let mut curr_transform = Transform::IDENTITY;
 
-        while curr_path != source {
-            let bone_frame: BoneFrame = if data.paths.contains_key(&curr_path) {
-                let bone_id = data.paths.get(&curr_path).unwrap();
-                data.bones[*bone_id].clone()
+        while curr_bone != source {
+            let bone_frame: BoneFrame = if let Some(idx) = data.ids.get(&curr_bone) {
+                data.bones[*idx].clone()
             } else {
                 BoneFrame::default()
             };
-            let curr_entity = self.entity_map.get(&curr_path).unwrap();
-            let curr_local_transform = self.resources.transform_query.get(*curr_entity).unwrap().0;
+            let curr_entity = self.skeleton.entity(curr_bone).unwrap();
+            let curr_local_transform = self.resources.transform_query.get(curr_entity).unwrap().0;
             let merged_local_transform =
                 bone_frame.to_transform_linear_with_base(*curr_local_transform, timestamp);
 
             curr_transform = merged_local_transform * curr_transform;
-            curr_path = curr_path.parent().unwrap();
+            curr_bone = self.skeleton.parent(curr_bone).unwrap();
         }
 
         Transform::from_matrix(curr_transform.compute_matrix().inverse()) * transform
@@ -449,23 +418,22 @@ impl SpaceConversion for PassContext<'_> {
         target: BoneId,
         timestamp: f32,
     ) -> Transform {
-        let mut curr_path = source;
+        let mut curr_bone = source;
         let mut curr_transform = Transform::IDENTITY;
 
-        while curr_path != target {
-            let bone_frame: BoneFrame = if data.paths.contains_key(&curr_path) {
-                let bone_id = data.paths.get(&curr_path).unwrap();
-                data.bones[*bone_id].clone()
+        while curr_bone != target {
+            let bone_frame: BoneFrame = if let Some(idx) = data.ids.get(&curr_bone) {
+                data.bones[*idx].clone()
             } else {
                 BoneFrame::default()
             };
-            let curr_entity = self.entity_map.get(&curr_path).unwrap();
-            let curr_local_transform = self.resources.transform_query.get(*curr_entity).unwrap().0;
+            let curr_entity = self.skeleton.entity(curr_bone).unwrap();
+            let curr_local_transform = self.resources.transform_query.get(curr_entity).unwrap().0;
             let merged_local_transform =
                 bone_frame.to_transform_linear_with_base(*curr_local_transform, timestamp);
 
             curr_transform = merged_local_transform * curr_transform;
-            curr_path = curr_path.parent().unwrap();
+            curr_bone = self.skeleton.parent(curr_bone).unwrap();
         }
 
         curr_transform * transform
@@ -478,12 +446,8 @@ impl SpaceConversion for PassContext<'_> {
         target: BoneId,
         timestamp: f32,
     ) -> Transform {
-        let root_name = self.resources.names_query.get(self.root_entity).unwrap();
-        let root_path = EntityPath {
-            parts: vec![root_name.clone()],
-        };
-
-        self.change_bone_space_down(transform, data, root_path, target, timestamp)
+        let root = self.skeleton.root().unwrap();
+        self.change_bone_space_down(transform, data, root, target, timestamp)
     }
 
     fn global_to_bone_space(
@@ -514,12 +478,8 @@ impl SpaceConversion for PassContext<'_> {
         target: BoneId,
         timestamp: f32,
     ) -> Transform {
-        let root_name = self.resources.names_query.get(self.root_entity).unwrap();
-        let root_path = EntityPath {
-            parts: vec![root_name.clone()],
-        };
-
-        self.change_bone_space_up(Transform::IDENTITY, data, target, root_path, timestamp)
+        let root = self.skeleton.root().unwrap();
+        self.change_bone_space_up(Transform::IDENTITY, data, target, root, timestamp)
     }
 
     fn global_transform_of_bone(
@@ -541,34 +501,16 @@ impl SpaceConversion for PassContext<'_> {
         let mut new_frame = data.clone();
         let new_frame_inner = new_frame.inner_mut();
 
-        let root_name = self.resources.names_query.get(self.root_entity).unwrap();
-        let root_path = EntityPath {
-            parts: vec![root_name.clone()],
-        };
-
-        let root_children = self.resources.children_query.get(self.root_entity).unwrap();
-
-        let mut queue: VecDeque<(Entity, EntityPath)> = VecDeque::new();
-
-        for child in root_children {
-            queue.push_back((*child, root_path.clone()));
-        }
-
-        while !queue.is_empty() {
-            let (entity, parent_path) = queue.pop_front().unwrap();
+        // The root bone is the character itself, so its transform is never part of the pose
+        for bone in self.skeleton.bones().iter().skip(1) {
             // --- Compute the updated transform frame
             // -------------------------------------------------------
-            // First, build the entity path for the current entity
-            let entity_name = self.resources.names_query.get(entity).unwrap();
-            let entity_path = parent_path.child(entity_name.clone());
-
             // Get the entity's current local transform
-            let (entity_transform, _) = self.resources.transform_query.get(entity).unwrap();
+            let (entity_transform, _) = self.resources.transform_query.get(bone.entity).unwrap();
             let inner_data = data.inner_ref();
             // Get the corresponding bone frame
-            let mut bone_frame: BoneFrame = if inner_data.paths.contains_key(&entity_path) {
-                let bone_id = inner_data.paths.get(&entity_path).unwrap();
-                inner_data.bones[*bone_id].clone()
+            let mut bone_frame: BoneFrame = if let Some(idx) = inner_data.ids.get(&bone.id) {
+                inner_data.bones[*idx].clone()
             } else {
                 BoneFrame::default()
             };
@@ -581,19 +523,20 @@ impl SpaceConversion for PassContext<'_> {
                 next_timestamp: f32::MAX,
                 prev_is_wrapped: true,
                 next_is_wrapped: true,
+                interpolation: FrameInterpolation::Linear,
             };
 
             if bone_frame.translation.is_none() {
-                bone_frame.translation = Some(local_transform_frame.map(|t| t.translation));
+                bone_frame.translation = Some(local_transform_frame.map_linear(|t| t.translation));
             }
             if bone_frame.rotation.is_none() {
-                bone_frame.rotation = Some(local_transform_frame.map(|t| t.rotation));
+                bone_frame.rotation = Some(local_transform_frame.map_linear(|t| t.rotation));
             }
             if bone_frame.scale.is_none() {
-                bone_frame.scale = Some(local_transform_frame.map(|t| t.scale));
+                bone_frame.scale = Some(local_transform_frame.map_linear(|t| t.scale));
             }
 
-            new_frame_inner.add_bone(bone_frame, entity_path);
+            new_frame_inner.add_bone(bone_frame, bone.id);
         }
 
         new_frame
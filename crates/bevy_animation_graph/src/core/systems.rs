@@ -1,58 +1,14 @@
 use super::{
-    animation_clip::EntityPath,
     animation_graph::{TimeUpdate, UpdateTime},
-    animation_graph_player::AnimationGraphPlayer,
-    pose::{BoneId, Pose},
+    animation_graph_player::{AnimationEvent, AnimationGraphPlayer},
+    pose::Pose,
+    skeleton::Skeleton,
 };
 use crate::prelude::SystemResources;
 use bevy::{
     core::prelude::*, ecs::prelude::*, gizmos::gizmos::Gizmos, hierarchy::prelude::*,
     log::prelude::*, render::mesh::morph::MorphWeights, time::prelude::*, transform::prelude::*,
-    utils::HashMap,
 };
-use std::{collections::VecDeque, ops::Deref};
-
-fn entity_from_path(
-    root: Entity,
-    path: &EntityPath,
-    children: &Query<&Children>,
-    names: &Query<&Name>,
-) -> Option<Entity> {
-    // PERF: finding the target entity can be optimised
-    let mut current_entity = root;
-
-    let mut parts = path.parts.iter().enumerate();
-
-    // check the first name is the root node which we already have
-    let Some((_, root_name)) = parts.next() else {
-        return None;
-    };
-    if names.get(current_entity) != Ok(root_name) {
-        return None;
-    }
-
-    for (_idx, part) in parts {
-        let mut found = false;
-        let children = children.get(current_entity).ok()?;
-        if !found {
-            for child in children.deref() {
-                if let Ok(name) = names.get(*child) {
-                    if name == part {
-                        // Found a children with the right name, continue to the next part
-                        current_entity = *child;
-                        found = true;
-                        break;
-                    }
-                }
-            }
-        }
-        if !found {
-            warn!("Entity not found for path {:?} on part {:?}", path, part);
-            return None;
-        }
-    }
-    Some(current_entity)
-}
 
 /// Verify that there are no ancestors of a given entity that have an [`AnimationPlayer`].
 fn verify_no_ancestor_player(
@@ -77,40 +33,31 @@ fn verify_no_ancestor_player(
     }
 }
 
-fn build_entity_map(root_entity: Entity, resources: &SystemResources) -> HashMap<BoneId, Entity> {
-    let mut entity_map = HashMap::default();
-
-    let root_name = resources.names_query.get(root_entity).unwrap();
-    let root_path: BoneId = EntityPath {
-        parts: vec![root_name.clone()],
-    };
-
-    entity_map.insert(root_path.clone(), root_entity);
-
-    let root_children = resources.children_query.get(root_entity).unwrap();
-
-    let mut queue: VecDeque<(Entity, EntityPath)> = VecDeque::new();
-
-    for child in root_children {
-        queue.push_back((*child, root_path.clone()));
-    }
-
-    while !queue.is_empty() {
-        let (entity, parent_path) = queue.pop_front().unwrap();
-        let Ok(name) = resources.names_query.get(entity) else {
-            continue;
-        };
-        let path = parent_path.child(name.clone());
-        entity_map.insert(path.clone(), entity);
-
-        if let Ok(children) = resources.children_query.get(entity) {
-            for child in children {
-                queue.push_back((*child, path.clone()));
+/// System that marks the cached [`Skeleton`] of animation players as outdated whenever the
+/// named hierarchy under them changes, so that it is rebuilt before the next pose is applied.
+#[allow(clippy::type_complexity)]
+pub fn invalidate_player_skeletons(
+    changed: Query<Entity, Or<(Changed<Children>, Changed<Name>)>>,
+    mut removed_children: RemovedComponents<Children>,
+    mut removed_names: RemovedComponents<Name>,
+    parents: Query<&Parent>,
+    mut players: Query<&mut AnimationGraphPlayer>,
+) {
+    for entity in changed
+        .iter()
+        .chain(removed_children.read())
+        .chain(removed_names.read())
+    {
+        let mut current = Some(entity);
+        while let Some(entity) = current {
+            if let Ok(mut player) = players.get_mut(entity) {
+                if player.skeleton_up_to_date {
+                    player.skeleton_up_to_date = false;
+                }
             }
+            current = parents.get(entity).ok().map(Parent::get);
         }
     }
-
-    entity_map
 }
 
 /// System that will play all animations, using any entity with a [`AnimationGraphPlayer`]
@@ -122,6 +69,7 @@ pub fn animation_player(
     parents: Query<(Has<AnimationGraphPlayer>, Option<&Parent>)>,
     mut animation_players: Query<(Entity, Option<&Parent>, &mut AnimationGraphPlayer)>,
     sysres: SystemResources,
+    mut animation_events: EventWriter<AnimationEvent>,
 ) {
     animation_players
         .par_iter_mut()
@@ -141,6 +89,16 @@ pub fn animation_player(
         .for_each(|(root, _, player)| {
             debug_draw_animation_players(player, root, &sysres);
         });
+    for (entity, _, mut player) in &mut animation_players {
+        if player.passed_events.is_empty() {
+            continue;
+        }
+        animation_events.send_batch(player.passed_events.drain(..).map(|e| AnimationEvent {
+            entity,
+            name: e.name,
+            node_path: e.node_path,
+        }));
+    }
 }
 
 /// System that will draw deferred gizmo commands called during graph evaluation
@@ -176,7 +134,10 @@ pub fn run_animation_player(
         .update(player.pending_update);
     player.pending_update = None;
 
-    player.entity_map = build_entity_map(root, system_resources);
+    if !player.skeleton_up_to_date {
+        player.skeleton = Skeleton::build(root, system_resources);
+        player.skeleton_up_to_date = true;
+    }
 
     let Some(out_pose) = player.query(system_resources, root) else {
         return;
@@ -186,13 +147,25 @@ pub fn run_animation_player(
     apply_pose(
         &out_pose,
         root,
-        &system_resources.names_query,
+        &player.skeleton,
         &system_resources.transform_query,
         morphs,
         maybe_parent,
         parents,
-        &system_resources.children_query,
     );
+
+    if player.apply_root_motion {
+        // SAFETY: Same as in `apply_pose`, no other animation player can be animating the root
+        // entity of this player.
+        if let Ok((mut transform, _)) =
+            unsafe { system_resources.transform_query.get_unchecked(root) }
+        {
+            let motion = player.root_motion;
+            let offset = transform.rotation * motion.translation;
+            transform.translation += offset;
+            transform.rotation *= motion.rotation;
+        }
+    }
 }
 
 pub fn debug_draw_animation_players(
@@ -231,12 +204,11 @@ pub(crate) fn get_keyframe(target_count: usize, keyframes: &[f32], key_index: us
 fn apply_pose(
     animation_pose: &Pose,
     root: Entity,
-    names: &Query<&Name>,
+    skeleton: &Skeleton,
     transforms: &Query<(&mut Transform, &GlobalTransform)>,
     morphs: &Query<&mut MorphWeights>,
     maybe_parent: Option<&Parent>,
     parents: &Query<(Has<AnimationGraphPlayer>, Option<&Parent>)>,
-    children: &Query<&Children>,
 ) {
     if !verify_no_ancestor_player(maybe_parent, parents) {
         warn!("Animation player on {:?} has a conflicting animation player on an ancestor. Cannot safely animate.", root);
@@ -244,8 +216,8 @@ fn apply_pose(
     }
 
     let mut any_path_found = false;
-    for (path, bone_id) in &animation_pose.paths {
-        let Some(target) = entity_from_path(root, path, children, names) else {
+    for (bone_id, idx) in &animation_pose.ids {
+        let Some(target) = skeleton.entity(*bone_id) else {
             continue;
         };
         any_path_found = true;
@@ -265,7 +237,7 @@ fn apply_pose(
             continue;
         };
 
-        let pose = &animation_pose.bones[*bone_id];
+        let pose = &animation_pose.bones[*idx];
         let mut morphs = unsafe { morphs.get_unchecked(target) };
         if let Some(rotation) = pose.rotation {
             transform.rotation = rotation;
@@ -287,3 +259,59 @@ fn apply_pose(
         warn!("Animation player on {root:?} did not match any entity paths.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skeleton_invalidated_on_hierarchy_change() {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(invalidate_player_skeletons);
+
+        let root = world
+            .spawn((Name::new("root"), AnimationGraphPlayer::new()))
+            .id();
+        let bone = world.spawn(Name::new("bone")).set_parent(root).id();
+        schedule.run(&mut world);
+
+        let up_to_date = |world: &World| {
+            world
+                .get::<AnimationGraphPlayer>(root)
+                .unwrap()
+                .skeleton_up_to_date
+        };
+
+        // Nothing changed since the last run
+        world
+            .get_mut::<AnimationGraphPlayer>(root)
+            .unwrap()
+            .skeleton_up_to_date = true;
+        schedule.run(&mut world);
+        assert!(up_to_date(&world));
+
+        // Renaming a bone
+        world.get_mut::<Name>(bone).unwrap().set("renamed");
+        schedule.run(&mut world);
+        assert!(!up_to_date(&world));
+
+        // Removing the name of a bone
+        world
+            .get_mut::<AnimationGraphPlayer>(root)
+            .unwrap()
+            .skeleton_up_to_date = true;
+        world.entity_mut(bone).remove::<Name>();
+        schedule.run(&mut world);
+        assert!(!up_to_date(&world));
+
+        // Removing the last child of an entity
+        world
+            .get_mut::<AnimationGraphPlayer>(root)
+            .unwrap()
+            .skeleton_up_to_date = true;
+        world.entity_mut(bone).remove_parent();
+        schedule.run(&mut world);
+        assert!(!up_to_date(&world));
+    }
+}
@@ -0,0 +1,126 @@
+//! Helpers for evaluating animation graphs in unit tests, without a running app.
+
+use super::{
+    animation_clip::{EntityPath, GraphClip, Interpolation, Keyframes, VariableCurve},
+    animation_graph::{AnimationGraph, InputOverlay, PinId, TargetPin, TimeUpdate},
+    context::{DeferredGizmos, GraphContext, PassContext, SystemResources},
+    errors::GraphError,
+    frame::PoseEvent,
+    parameters::ParamValue,
+    pose::Pose,
+    skeleton::Skeleton,
+};
+use bevy::{
+    asset::{Assets, Handle},
+    core::Name,
+    ecs::{entity::Entity, system::SystemState, world::World},
+    math::Vec3,
+    transform::components::{GlobalTransform, Transform},
+};
+
+/// Name of the root entity of the test skeleton
+pub const ROOT: &str = "root";
+
+/// World holding the assets used by a graph and an entity to evaluate it on.
+pub struct TestWorld {
+    pub world: World,
+    pub root: Entity,
+    pub context: GraphContext,
+}
+
+impl TestWorld {
+    pub fn new() -> Self {
+        let mut world = World::new();
+        world.init_resource::<Assets<GraphClip>>();
+        world.init_resource::<Assets<AnimationGraph>>();
+        let root = world
+            .spawn((
+                Name::new(ROOT),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .id();
+
+        Self {
+            world,
+            root,
+            context: GraphContext::default(),
+        }
+    }
+
+    pub fn add_clip(&mut self, clip: GraphClip) -> Handle<GraphClip> {
+        self.world.resource_mut::<Assets<GraphClip>>().add(clip)
+    }
+
+    pub fn add_graph(&mut self, graph: AnimationGraph) -> Handle<AnimationGraph> {
+        self.world
+            .resource_mut::<Assets<AnimationGraph>>()
+            .add(graph)
+    }
+
+    /// Evaluates the graph with the given time update, returning the output pose and the events
+    /// passed by playback.
+    pub fn query(
+        &mut self,
+        graph: &Handle<AnimationGraph>,
+        time_update: TimeUpdate,
+    ) -> Result<(Pose, Vec<PoseEvent>), GraphError> {
+        let mut state = SystemState::<SystemResources>::new(&mut self.world);
+        let resources = state.get_mut(&mut self.world);
+        let skeleton = Skeleton::build(self.root, &resources);
+        let graph = resources.animation_graph_assets.get(graph).unwrap();
+
+        let pose = graph.query(
+            time_update,
+            &mut self.context,
+            &resources,
+            self.root,
+            &skeleton,
+            &mut DeferredGizmos::default(),
+        )?;
+        let events = graph.passed_events(time_update, &self.context);
+
+        Ok((pose, events))
+    }
+
+    /// Evaluates an output parameter of the graph, in a fresh context.
+    pub fn parameter(
+        &mut self,
+        graph: &Handle<AnimationGraph>,
+        pin_id: impl Into<PinId>,
+    ) -> Result<ParamValue, GraphError> {
+        let mut state = SystemState::<SystemResources>::new(&mut self.world);
+        let resources = state.get_mut(&mut self.world);
+        let skeleton = Skeleton::build(self.root, &resources);
+        let graph = resources.animation_graph_assets.get(graph).unwrap();
+
+        let mut context = GraphContext::default();
+        let mut deferred_gizmos = DeferredGizmos::default();
+        let overlay = InputOverlay::default();
+        let ctx = PassContext::new(
+            &mut context,
+            &resources,
+            &overlay,
+            self.root,
+            &skeleton,
+            &mut deferred_gizmos,
+        );
+        graph.get_parameter(TargetPin::OutputParameter(pin_id.into()), ctx)
+    }
+}
+
+/// Clip of the given duration moving the root bone along the x axis at one unit per second.
+pub fn linear_clip(duration: f32) -> GraphClip {
+    let mut clip = GraphClip::default();
+    clip.add_curve_to_path(
+        EntityPath {
+            parts: vec![Name::new(ROOT)],
+        },
+        VariableCurve {
+            keyframe_timestamps: vec![0., duration],
+            keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::new(duration, 0., 0.)]),
+            interpolation: Interpolation::Linear,
+        },
+    );
+    clip
+}
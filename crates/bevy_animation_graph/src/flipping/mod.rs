@@ -4,42 +4,34 @@ use self::config::FlipConfig;
 use crate::core::{
     animation_clip::EntityPath,
     frame::{BoneFrame, BonePoseFrame, InnerPoseFrame, ValueFrame},
+    skeleton::Skeleton,
 };
 use bevy::math::prelude::*;
 
 pub trait FlipXBySuffix {
-    fn flipped(&self, config: &FlipConfig) -> Self;
+    fn flipped(&self, config: &FlipConfig, skeleton: &Skeleton) -> Self;
 }
 
 impl FlipXBySuffix for ValueFrame<Vec3> {
-    fn flipped(&self, _: &FlipConfig) -> Self {
-        let mut out = self.clone();
-
-        out.prev.x *= -1.;
-        out.next.x *= -1.;
-
-        out
+    fn flipped(&self, _: &FlipConfig, _: &Skeleton) -> Self {
+        self.map_linear(|v| Vec3::new(-v.x, v.y, v.z))
     }
 }
 
 impl FlipXBySuffix for ValueFrame<Quat> {
-    fn flipped(&self, _: &FlipConfig) -> Self {
-        let mut out = self.clone();
-
-        out.prev.x *= -1.;
-        out.prev.w *= -1.;
-        out.next.x *= -1.;
-        out.next.w *= -1.;
-
-        out
+    fn flipped(&self, _: &FlipConfig, _: &Skeleton) -> Self {
+        self.map_linear(|q| Quat::from_xyzw(-q.x, q.y, q.z, -q.w))
     }
 }
 
 impl FlipXBySuffix for BoneFrame {
-    fn flipped(&self, config: &FlipConfig) -> Self {
+    fn flipped(&self, config: &FlipConfig, skeleton: &Skeleton) -> Self {
         BoneFrame {
-            rotation: self.rotation.clone().map(|v| v.flipped(config)),
-            translation: self.translation.clone().map(|v| v.flipped(config)),
+            rotation: self.rotation.clone().map(|v| v.flipped(config, skeleton)),
+            translation: self
+                .translation
+                .clone()
+                .map(|v| v.flipped(config, skeleton)),
             scale: self.scale.clone(),
             weights: self.weights.clone(),
         }
@@ -47,10 +39,15 @@ impl FlipXBySuffix for BoneFrame {
 }
 
 impl FlipXBySuffix for InnerPoseFrame {
-    fn flipped(&self, config: &FlipConfig) -> Self {
+    fn flipped(&self, config: &FlipConfig, skeleton: &Skeleton) -> Self {
         let mut out = InnerPoseFrame::default();
-        for (path, bone_id) in self.paths.iter() {
-            let channel = self.bones[*bone_id].flipped(config);
+        for (bone_id, idx) in self.ids.iter() {
+            let channel = self.bones[*idx].flipped(config, skeleton);
+            // Bone names are only known for bones in the skeleton, other bones keep their id
+            let Some(path) = skeleton.path(*bone_id) else {
+                out.add_bone(channel, *bone_id);
+                continue;
+            };
             let new_path = EntityPath {
                 parts: path
                     .parts
@@ -65,14 +62,14 @@ impl FlipXBySuffix for InnerPoseFrame {
                     .collect(),
             };
 
-            out.add_bone(channel, new_path);
+            out.add_bone(channel, new_path.id());
         }
         out
     }
 }
 
 impl FlipXBySuffix for BonePoseFrame {
-    fn flipped(&self, config: &FlipConfig) -> Self {
-        BonePoseFrame(self.0.flipped(config))
+    fn flipped(&self, config: &FlipConfig, skeleton: &Skeleton) -> Self {
+        BonePoseFrame(self.0.flipped(config, skeleton))
     }
 }
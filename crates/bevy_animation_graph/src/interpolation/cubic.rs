@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+/// Cubic Hermite spline interpolation, as used by glTF `CUBICSPLINE` animation samplers.
+///
+/// Tangents are expected to already be multiplied by the duration of the interval between
+/// `self` and `other`.
+pub trait InterpolateCubicSpline {
+    fn interpolate_cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        f: f32,
+    ) -> Self;
+}
+
+/// Hermite basis functions for the start value, start tangent, end value and end tangent.
+fn hermite_basis(f: f32) -> [f32; 4] {
+    let f2 = f * f;
+    let f3 = f2 * f;
+    [
+        2. * f3 - 3. * f2 + 1.,
+        f3 - 2. * f2 + f,
+        -2. * f3 + 3. * f2,
+        f3 - f2,
+    ]
+}
+
+impl InterpolateCubicSpline for Vec<f32> {
+    fn interpolate_cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        f: f32,
+    ) -> Self {
+        let [a, b, c, d] = hermite_basis(f);
+        self.iter()
+            .zip(out_tangent)
+            .zip(other.iter().zip(in_tangent))
+            .map(|((p0, m0), (p1, m1))| a * p0 + b * m0 + c * p1 + d * m1)
+            .collect()
+    }
+}
+
+impl InterpolateCubicSpline for Vec3 {
+    fn interpolate_cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        f: f32,
+    ) -> Self {
+        let [a, b, c, d] = hermite_basis(f);
+        a * *self + b * *out_tangent + c * *other + d * *in_tangent
+    }
+}
+
+/// Quaternions are interpolated component-wise and then normalized, as the glTF spec requires.
+impl InterpolateCubicSpline for Quat {
+    fn interpolate_cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        f: f32,
+    ) -> Self {
+        let [a, b, c, d] = hermite_basis(f);
+        let v = a * Vec4::from(*self)
+            + b * Vec4::from(*out_tangent)
+            + c * Vec4::from(*other)
+            + d * Vec4::from(*in_tangent);
+        Quat::from_vec4(v).normalize()
+    }
+}
+
+impl InterpolateCubicSpline for Transform {
+    fn interpolate_cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        f: f32,
+    ) -> Self {
+        Transform {
+            translation: self.translation.interpolate_cubic_spline(
+                &out_tangent.translation,
+                &other.translation,
+                &in_tangent.translation,
+                f,
+            ),
+            rotation: self.rotation.interpolate_cubic_spline(
+                &out_tangent.rotation,
+                &other.rotation,
+                &in_tangent.rotation,
+                f,
+            ),
+            scale: self.scale.interpolate_cubic_spline(
+                &out_tangent.scale,
+                &other.scale,
+                &in_tangent.scale,
+                f,
+            ),
+        }
+    }
+}
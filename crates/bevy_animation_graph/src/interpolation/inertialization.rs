@@ -0,0 +1,235 @@
+//! Inertialization, an alternative to crossfading between two animations where only the target
+//! animation is evaluated. At the moment of the transition, the offset between the last pose of
+//! the source animation and the target pose is recorded together with its velocity, and the
+//! offset is then decayed to zero using a quintic polynomial.
+//!
+//! Based on David Bollo's "Inertialization: High-Performance Animation Transitions in Gears of
+//! War" (GDC 2018).
+
+use crate::core::pose::{BoneId, Pose};
+use bevy::{math::prelude::*, utils::HashMap};
+
+/// Decaying offset along a single direction.
+#[derive(Clone, Copy, Debug)]
+struct DecayCurve {
+    x0: f32,
+    v0: f32,
+    a0: f32,
+    duration: f32,
+    a: f32,
+    b: f32,
+    c: f32,
+}
+
+impl DecayCurve {
+    fn new(x0: f32, v0: f32, duration: f32) -> Self {
+        // The offset must not keep growing away from zero, and it must not overshoot
+        let v0 = v0.min(0.);
+        let duration = if v0 < 0. {
+            duration.min(-5. * x0 / v0)
+        } else {
+            duration
+        };
+        let t1 = duration.max(f32::EPSILON);
+        let a0 = ((-8. * v0 * t1 - 20. * x0) / t1.powi(2)).max(0.);
+
+        Self {
+            x0,
+            v0,
+            a0,
+            duration,
+            a: -(a0 * t1.powi(2) + 6. * v0 * t1 + 12. * x0) / (2. * t1.powi(5)),
+            b: (3. * a0 * t1.powi(2) + 16. * v0 * t1 + 30. * x0) / (2. * t1.powi(4)),
+            c: -(3. * a0 * t1.powi(2) + 12. * v0 * t1 + 20. * x0) / (2. * t1.powi(3)),
+        }
+    }
+
+    fn evaluate(&self, t: f32) -> f32 {
+        if t >= self.duration {
+            return 0.;
+        }
+        self.a * t.powi(5)
+            + self.b * t.powi(4)
+            + self.c * t.powi(3)
+            + self.a0 / 2. * t.powi(2)
+            + self.v0 * t
+            + self.x0
+    }
+}
+
+/// Offset of a vector (translation or scale) channel.
+#[derive(Clone, Copy, Debug)]
+struct VectorOffset {
+    direction: Vec3,
+    curve: DecayCurve,
+}
+
+impl VectorOffset {
+    fn new(prev: Vec3, prev_prev: Option<Vec3>, target: Vec3, dt: f32, duration: f32) -> Self {
+        let offset = prev - target;
+        let x0 = offset.length();
+        let direction = offset.normalize_or_zero();
+        let v0 = match prev_prev {
+            Some(prev_prev) if dt > 0. => ((prev - prev_prev) / dt).dot(direction),
+            _ => 0.,
+        };
+
+        Self {
+            direction,
+            curve: DecayCurve::new(x0, v0, duration),
+        }
+    }
+
+    fn apply(&self, target: Vec3, t: f32) -> Vec3 {
+        target + self.direction * self.curve.evaluate(t)
+    }
+}
+
+/// Offset of a rotation channel, around a fixed axis.
+#[derive(Clone, Copy, Debug)]
+struct RotationOffset {
+    axis: Vec3,
+    curve: DecayCurve,
+}
+
+impl RotationOffset {
+    fn new(prev: Quat, prev_prev: Option<Quat>, target: Quat, dt: f32, duration: f32) -> Self {
+        let (axis, x0) = shortest_axis_angle(prev * target.inverse());
+        let v0 = match prev_prev {
+            Some(prev_prev) if dt > 0. => {
+                let (velocity_axis, velocity_angle) =
+                    shortest_axis_angle(prev * prev_prev.inverse());
+                (velocity_axis * velocity_angle / dt).dot(axis)
+            }
+            _ => 0.,
+        };
+
+        Self {
+            axis,
+            curve: DecayCurve::new(x0, v0, duration),
+        }
+    }
+
+    fn apply(&self, target: Quat, t: f32) -> Quat {
+        Quat::from_axis_angle(self.axis, self.curve.evaluate(t)) * target
+    }
+}
+
+fn shortest_axis_angle(rotation: Quat) -> (Vec3, f32) {
+    let rotation = if rotation.w < 0. { -rotation } else { rotation };
+    let (axis, angle) = rotation.normalize().to_axis_angle();
+    if angle.is_finite() && axis.is_finite() {
+        (axis, angle)
+    } else {
+        (Vec3::X, 0.)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct BoneOffset {
+    rotation: Option<RotationOffset>,
+    translation: Option<VectorOffset>,
+    scale: Option<VectorOffset>,
+}
+
+/// Offsets between a source and a target pose, decaying over time.
+#[derive(Clone, Debug, Default)]
+pub struct Inertialization {
+    bones: HashMap<BoneId, BoneOffset>,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl Inertialization {
+    /// Records the offsets between the last pose of the source animation and the first pose of
+    /// the target animation. The pose before the last one (`prev_prev`), sampled `dt` seconds
+    /// earlier, is used to compute the velocity of the offset.
+    pub fn new(
+        prev: &Pose,
+        prev_prev: Option<&Pose>,
+        dt: f32,
+        target: &Pose,
+        duration: f32,
+    ) -> Self {
+        let mut bones = HashMap::default();
+
+        for (bone_id, target_idx) in target.ids.iter() {
+            let Some(prev_idx) = prev.ids.get(bone_id) else {
+                continue;
+            };
+            let target_bone = &target.bones[*target_idx];
+            let prev_bone = &prev.bones[*prev_idx];
+            let prev_prev_bone = prev_prev
+                .and_then(|p| p.ids.get(bone_id).map(|idx| &p.bones[*idx]))
+                .cloned()
+                .unwrap_or_default();
+
+            let offset =
+                BoneOffset {
+                    rotation: prev_bone.rotation.zip(target_bone.rotation).map(|(p, t)| {
+                        RotationOffset::new(p, prev_prev_bone.rotation, t, dt, duration)
+                    }),
+                    translation: prev_bone.translation.zip(target_bone.translation).map(
+                        |(p, t)| VectorOffset::new(p, prev_prev_bone.translation, t, dt, duration),
+                    ),
+                    scale: prev_bone
+                        .scale
+                        .zip(target_bone.scale)
+                        .map(|(p, t)| VectorOffset::new(p, prev_prev_bone.scale, t, dt, duration)),
+                };
+
+            bones.insert(*bone_id, offset);
+        }
+
+        Self {
+            bones,
+            elapsed: 0.,
+            duration,
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Adds the current (decayed) offsets to the given pose.
+    pub fn apply(&self, pose: &mut Pose) {
+        for (bone_id, idx) in pose.ids.iter() {
+            let Some(offset) = self.bones.get(bone_id) else {
+                continue;
+            };
+            let bone = &mut pose.bones[*idx];
+
+            if let (Some(rotation), Some(offset)) = (bone.rotation.as_mut(), offset.rotation) {
+                *rotation = offset.apply(*rotation, self.elapsed);
+            }
+            if let (Some(translation), Some(offset)) =
+                (bone.translation.as_mut(), offset.translation)
+            {
+                *translation = offset.apply(*translation, self.elapsed);
+            }
+            if let (Some(scale), Some(offset)) = (bone.scale.as_mut(), offset.scale) {
+                *scale = offset.apply(*scale, self.elapsed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_curve_reaches_zero() {
+        let curve = DecayCurve::new(1., -0.5, 0.5);
+
+        assert!((curve.evaluate(0.) - 1.).abs() < 1e-5);
+        assert!(curve.evaluate(0.25) < 1.);
+        assert!(curve.evaluate(0.5).abs() < 1e-4);
+        assert_eq!(curve.evaluate(1.), 0.);
+    }
+}
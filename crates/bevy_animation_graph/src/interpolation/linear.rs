@@ -1,3 +1,4 @@
+use super::cubic::InterpolateCubicSpline;
 use crate::core::frame::{
     BoneFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
 };
@@ -12,7 +13,7 @@ impl InterpolateLinear for Vec<f32> {
     fn interpolate_linear(&self, other: &Vec<f32>, f: f32) -> Vec<f32> {
         self.iter()
             .zip(other)
-            .map(|(old, new)| (new - old) * f)
+            .map(|(old, new)| old + (new - old) * f)
             .collect()
     }
 }
@@ -25,7 +26,8 @@ impl InterpolateLinear for Vec3 {
 
 impl InterpolateLinear for Quat {
     fn interpolate_linear(&self, other: &Self, f: f32) -> Self {
-        self.slerp(*other, f)
+        // Slerp takes the shortest path between both rotations, but it expects normalized inputs
+        self.normalize().slerp(other.normalize(), f)
     }
 }
 
@@ -39,8 +41,64 @@ impl InterpolateLinear for Transform {
     }
 }
 
-impl<T: InterpolateLinear + FromReflect + TypePath + std::fmt::Debug + Clone> InterpolateLinear
-    for ValueFrame<T>
+/// Difference between two values and its weighted application, used for additive poses.
+pub trait InterpolateAdditive {
+    /// Returns the difference that takes `reference` to `self`.
+    fn difference(&self, reference: &Self) -> Self;
+    /// Applies the given difference to `self`, scaled by `f`.
+    fn add_weighted(&self, difference: &Self, f: f32) -> Self;
+}
+
+impl InterpolateAdditive for Vec<f32> {
+    fn difference(&self, reference: &Self) -> Self {
+        self.iter().zip(reference).map(|(v, r)| v - r).collect()
+    }
+
+    fn add_weighted(&self, difference: &Self, f: f32) -> Self {
+        self.iter()
+            .zip(difference)
+            .map(|(v, d)| v + d * f)
+            .collect()
+    }
+}
+
+impl InterpolateAdditive for Vec3 {
+    fn difference(&self, reference: &Self) -> Self {
+        *self - *reference
+    }
+
+    fn add_weighted(&self, difference: &Self, f: f32) -> Self {
+        *self + *difference * f
+    }
+}
+
+/// Rotations are composed in the local space of the bone, i.e. the difference `d` satisfies
+/// `reference * d == self`.
+impl InterpolateAdditive for Quat {
+    fn difference(&self, reference: &Self) -> Self {
+        let difference = (reference.inverse() * *self).normalize();
+        // Keep the difference in the same hemisphere as the identity, so that scaling it by a
+        // weight rotates along the shortest path
+        if difference.w < 0. {
+            -difference
+        } else {
+            difference
+        }
+    }
+
+    fn add_weighted(&self, difference: &Self, f: f32) -> Self {
+        (*self * Quat::IDENTITY.interpolate_linear(difference, f)).normalize()
+    }
+}
+
+impl<
+        T: InterpolateLinear
+            + InterpolateCubicSpline
+            + FromReflect
+            + TypePath
+            + std::fmt::Debug
+            + Clone,
+    > InterpolateLinear for ValueFrame<T>
 {
     fn interpolate_linear(&self, other: &Self, f: f32) -> Self {
         self.merge_linear(other, |l, r| l.interpolate_linear(r, f))
@@ -97,22 +155,22 @@ impl InterpolateLinear for InnerPoseFrame {
     fn interpolate_linear(&self, other: &Self, f: f32) -> Self {
         let mut result = InnerPoseFrame::default();
 
-        for (path, bone_id) in self.paths.iter() {
-            if let Some(other_bone_id) = other.paths.get(path) {
+        for (bone_id, idx) in self.ids.iter() {
+            if let Some(other_idx) = other.ids.get(bone_id) {
                 result.add_bone(
-                    self.bones[*bone_id].interpolate_linear(&other.bones[*other_bone_id], f),
-                    path.clone(),
+                    self.bones[*idx].interpolate_linear(&other.bones[*other_idx], f),
+                    *bone_id,
                 );
             } else {
-                result.add_bone(self.bones[*bone_id].clone(), path.clone());
+                result.add_bone(self.bones[*idx].clone(), *bone_id);
             }
         }
 
-        for (path, bone_id) in other.paths.iter() {
-            if self.paths.contains_key(path) {
+        for (bone_id, idx) in other.ids.iter() {
+            if self.ids.contains_key(bone_id) {
                 continue;
             }
-            result.add_bone(other.bones[*bone_id].clone(), path.clone());
+            result.add_bone(other.bones[*idx].clone(), *bone_id);
         }
 
         result
@@ -137,6 +195,9 @@ impl InterpolateLinear for PoseFrameData {
                     f1.inner_ref().interpolate_linear(f2.inner_ref(), f).into(),
                 )
             }
+            (PoseFrameData::Additive(f1), PoseFrameData::Additive(f2)) => {
+                PoseFrameData::Additive(f1.inner_ref().interpolate_linear(f2.inner_ref(), f).into())
+            }
             _ => {
                 panic!(
                     "Tried to chain {:?} with {:?}",
@@ -149,10 +210,14 @@ impl InterpolateLinear for PoseFrameData {
 }
 
 impl InterpolateLinear for PoseFrame {
+    /// Events are only kept from the frames with a non-zero weight
     fn interpolate_linear(&self, other: &Self, f: f32) -> Self {
+        let self_events = self.events.iter().filter(|_| f < 1.);
+        let other_events = other.events.iter().filter(|_| f > 0.);
         Self {
             data: self.data.interpolate_linear(&other.data, f),
             timestamp: self.timestamp,
+            events: self_events.chain(other_events).cloned().collect(),
         }
     }
 }
@@ -160,6 +225,29 @@ impl InterpolateLinear for PoseFrame {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::frame::{FrameInterpolation, PoseEvent};
+
+    #[test]
+    fn test_pose_frame_events_need_weight() {
+        let frame = |name: &str| PoseFrame {
+            events: vec![PoseEvent {
+                name: name.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let names = |f| {
+            frame("a")
+                .interpolate_linear(&frame("b"), f)
+                .events
+                .into_iter()
+                .map(|e| e.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(0.), ["a"]);
+        assert_eq!(names(0.5), ["a", "b"]);
+        assert_eq!(names(1.), ["b"]);
+    }
 
     #[test]
     fn test_interpolate_value_frame_nest_1() {
@@ -170,6 +258,7 @@ mod tests {
             next_timestamp: 1.,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
         let frame_2 = ValueFrame {
             prev: Vec3::new(0., 0., 0.),
@@ -178,6 +267,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let interpolated_0 = frame_1.interpolate_linear(&frame_2, 0.);
@@ -191,6 +281,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let expected_half = ValueFrame {
@@ -200,6 +291,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let expected_1 = ValueFrame {
@@ -209,6 +301,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         assert_eq!(expected_0, interpolated_0);
@@ -225,6 +318,7 @@ mod tests {
             next_timestamp: 1.,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
         let frame_1 = ValueFrame {
             prev: Vec3::new(0., 0., 0.),
@@ -233,6 +327,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let interpolated_1 = frame_1.interpolate_linear(&frame_2, 0.);
@@ -246,6 +341,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let expected_half = ValueFrame {
@@ -255,6 +351,7 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         let expected_1 = ValueFrame {
@@ -264,10 +361,36 @@ mod tests {
             next_timestamp: 0.8,
             next_is_wrapped: false,
             prev_is_wrapped: false,
+            interpolation: FrameInterpolation::Linear,
         };
 
         assert_eq!(expected_0, interpolated_0);
         assert_eq!(expected_1, interpolated_1);
         assert_eq!(expected_half, interpolated_half);
     }
+
+    #[test]
+    fn test_interpolate_morph_weights() {
+        let from = vec![0., 2.];
+        let to = vec![2., 4.];
+        assert_eq!(from.interpolate_linear(&to, 0.), from);
+        assert_eq!(from.interpolate_linear(&to, 0.5), vec![1., 3.]);
+        assert_eq!(from.interpolate_linear(&to, 1.), to);
+    }
+
+    #[test]
+    fn test_additive_quat_round_trip() {
+        let reference = Quat::from_rotation_y(0.5);
+        let value = Quat::from_rotation_x(1.2) * Quat::from_rotation_y(-0.3);
+        // Flipped sign, representing the same rotation
+        let difference = (-value).difference(&reference);
+
+        assert!(difference.w >= 0.);
+        assert!(reference
+            .add_weighted(&difference, 1.)
+            .abs_diff_eq(value, 1e-5));
+        assert!(reference
+            .add_weighted(&difference, 0.)
+            .abs_diff_eq(reference, 1e-5));
+    }
 }
@@ -1,5 +1,8 @@
+pub mod cubic;
+pub mod inertialization;
 pub mod linear;
 
 pub mod prelude {
+    pub use super::cubic::*;
     pub use super::linear::*;
 }
@@ -8,7 +8,9 @@
 //! - [`GraphClip`], which are defined in `*.anim.ron` files. These assets contain animation data,
 //!   similarly to Bevy's [`AnimationClip`]. The `*.anim.ron` files don't contain the actual
 //!   animation data, but rather point to the source for the
-//!   animation. Currently, animation from a Gltf file identified by their name label are supported.
+//!   animation. Currently, animations from a Gltf file identified by their name label and BVH
+//!   motion capture files are supported. Cubic spline interpolation of glTF animations is kept
+//!   when playing a clip on its own, but blending clips interpolates linearly between keyframes.
 //!   For example:
 //!   ```ron
 //!   (
@@ -18,8 +20,52 @@
 //!           // Name of the animation within that asset
 //!           animation_name: "Walk",
 //!       ),
+//!       // Optional named events at given times of the clip (in seconds)
+//!       events: [
+//!           (time: 0.2, name: "footstep_l"),
+//!           (time: 0.7, name: "footstep_r"),
+//!       ],
+//!       // Optional named markers used to align clips in synchronized blends
+//!       sync_markers: [
+//!           (time: 0.2, name: "left_down"),
+//!           (time: 0.7, name: "right_down"),
+//!       ],
 //!   )
 //!   ```
+//!   A BVH source maps each joint to the entity path of the same name under a given root, and can
+//!   scale joint positions to scene units:
+//!   ```ron
+//!   source: Bvh(
+//!       path: "mocap/walk.bvh",
+//!       root: ["Armature"],
+//!       scale: 0.01,
+//!   ),
+//!   ```
+//!   Clips can also be derived from a section of another clip, which is resampled at load time.
+//!   The section can be reversed and its end blended into the motion leading to its start, so
+//!   that it loops seamlessly:
+//!   ```ron
+//!   source: SubClip(
+//!       source: Clip(path: "animations/attack.anim.ron"),
+//!       start: 0.33,
+//!       end: Some(1.33),
+//!       reverse: false,
+//!       loop_blend: 0.2,
+//!       sample_rate: 30.0,
+//!   ),
+//!   ```
+//!   Clips authored for a different skeleton can be converted at load time with a
+//!   [`RetargetMap`](retargeting::RetargetMap), which maps bones by path and corrects for
+//!   differing rest orientations and limb lengths. The rest poses are read from a scene or, for
+//!   mocap data, from the joint offsets of a BVH file:
+//!   ```ron
+//!   source: Retargeted(
+//!       source: Bvh(path: "mocap/walk.bvh", root: ["Armature"]),
+//!       retarget_map: "retarget/mocap_to_fox.retarget.ron",
+//!   ),
+//!   ```
+//!   Whenever playback passes one of these events, the [`AnimationGraphPlayer`] playing the graph
+//!   sends an [`AnimationEvent`] with the event name and the path of the node that produced it.
 //! - [`AnimationGraph`], defined in `*.animgraph.ron` files. These assets are the core
 //!   of the library and specify the nodes, edges, inputs, outputs and default parameters of an
 //!   animation graph. The animation player ([`AnimationGraphPlayer`]) uses a handle to an
@@ -71,7 +117,14 @@
 //! The currently implemented graph nodes are:
 //! - [`ClipNode`]: Plays back an animation clip.
 //! - [`ChainNode`]: Chains (plays one after the other) two animation inputs.
-//! - [`BlendNode`]: Blends two animation inputs linearly based on an input factor.
+//! - [`BlendNode`]: Blends two animation inputs linearly based on an input factor. Optionally keeps
+//!   the inputs in sync, matching their normalized phase or their sync markers.
+//! - [`BlendSpace1DNode`]: Blends between any number of animation inputs placed along an axis,
+//!   based on an input position, keeping their playback in sync.
+//! - [`BlendSpace2DNode`]: Blends between any number of animation inputs placed on a plane, based
+//!   on an input position (e.g. a velocity), keeping their playback in sync.
+//! - [`LayeredBlendNode`]: Blends a layer pose on top of a base pose with per bone weights given
+//!   by a bone mask, e.g. to play an upper body animation over locomotion.
 //! - [`FlipLRNode`]: Mirrors an animation on the X axis, based on the bone names having `L` and `R`
 //!   suffixes to specify which side they are on.
 //! - [`LoopNode`]: Loops an animation input indefinitely.
@@ -79,6 +132,20 @@
 //! - [`GraphNode`]: Nested animation graph. The node inputs and outputs match the nested graph's
 //! - [`RotationNode`]: Applies a (quaternion) rotation to a set of bones from the input pose defined using a bone mask.
 //!   inputs and outputs.
+//! - [`MakeAdditiveNode`]: Turns an animation input into an additive pose by subtracting a
+//!   reference pose from it.
+//! - [`ApplyAdditiveNode`]: Layers an additive pose on top of a base pose with a given weight.
+//! - [`RootMotionNode`]: Extracts the horizontal translation and/or yaw of the root bone, which
+//!   can then be read from the [`AnimationGraphPlayer`] or applied to the player entity.
+//! - [`IkChainNode`]: Rotates a chain of bones of arbitrary length so that its last bone reaches
+//!   a target position, using either FABRIK or CCD.
+//! - [`LookAtNode`]: Rotates a bone, optionally together with some of its parents, so that one of
+//!   its axes points toward a target position.
+//! - [`FootPlacementNode`]: Adapts the legs to uneven ground given the ground height and normal
+//!   below each foot, adjusting the pelvis height, solving each leg with two-bone IK and aligning
+//!   the feet with the ground.
+//! - [`StateMachineNode`]: Switches between states (pose inputs or nested animation graphs) when
+//!   the conditions on its parameter inputs are met, crossfading between them during transitions.
 //! - Parameter arithmetic:
 //!   - Floating point numbers (`f32`)
 //!     - [`AddF32`]
@@ -86,9 +153,46 @@
 //!     - [`MulF32`]
 //!     - [`DivF32`]
 //!     - [`ClampF32`]
+//!     - [`ExpressionNode`]: Evaluates a formula such as `clamp(speed / max_speed, 0, 1)`, with an
+//!       input for each variable in it.
 //!   - Vector (`vec3`)
 //!     - [`RotationArcNode`]: Given two vectors, output quaternion rotation needed to rotate the first
 //!       into the second.
+//!     - [`AddVec3`]
+//!     - [`SubVec3`]
+//!     - [`ScaleVec3`]: Multiplies a vector by a scalar.
+//!     - [`DotVec3`]
+//!     - [`CrossVec3`]
+//!     - [`NormalizeVec3`]
+//!     - [`LengthVec3`]
+//!     - [`LerpVec3`]
+//!     - [`BuildVec3`], [`DecomposeVec3`]: Compose a vector from its components and vice versa.
+//!   - Quaternion (`quat`)
+//!     - [`MulQuat`]: Composes two rotations.
+//!     - [`InvertQuat`]
+//!     - [`SlerpQuat`]
+//!     - [`QuatFromAxisAngle`]
+//!     - [`QuatFromEuler`]: Builds a rotation from euler angles, applied in a configurable order.
+//!   - Logic (`bool`, `i32` and enumerations)
+//!     - [`AndBool`]
+//!     - [`OrBool`]
+//!     - [`NotBool`]
+//!     - [`CompareF32`], [`CompareI32`]: Compares two numbers, outputting a `bool`.
+//!     - [`EqualEnum`]: Whether two enumeration parameters hold the same variant.
+//!     - [`SelectNode`]: Outputs one of two parameters of any type based on a `bool` condition.
+//!     - [`SwitchNode`]: Outputs one of several parameters of any type based on an enumeration
+//!       variant.
+//!   - Smoothing
+//!     - [`SmoothF32`], [`SmoothVec3`], [`SmoothQuat`]: Follow the input parameter over time
+//!       instead of jumping to new values, using a critically damped spring, exponential decay or
+//!       a maximum rate of change (see [`Smoothing`]).
+//!
+//! Custom nodes can be added by implementing [`NodeLike`]. In order to save them to and load them
+//! from animation graph files, register their type with `app.register_custom_node::<T>()` (see
+//! [`AnimationGraphAppExt`]) and create them with [`CustomNode::registered`]. Since nodes are
+//! shared by all players of a graph, any data that must persist across frames (e.g. a smoothed
+//! value) should be stored with [`PassContext::node_state_mut`], which is kept separately for each
+//! player and nested graph instance.
 //!
 //! ## Editor installation
 //!
@@ -201,6 +305,7 @@
 //!
 //! [`ParamValue`]: crate::core::parameters::ParamValue
 //! [`PassContext`]: crate::core::context::PassContext
+//! [`PassContext::node_state_mut`]: crate::core::context::PassContext::node_state_mut
 //! [`AnimationNode`]: prelude::AnimationNode
 //!
 //! [`SpeedNode`]: crate::nodes::SpeedNode
@@ -208,15 +313,56 @@
 //! [`ChainNode`]: crate::nodes::ChainNode
 //! [`BlendNode`]: crate::nodes::BlendNode
 //! [`RotationNode`]: crate::nodes::RotationNode
+//! [`LayeredBlendNode`]: crate::nodes::LayeredBlendNode
+//! [`BlendSpace1DNode`]: crate::nodes::BlendSpace1DNode
+//! [`BlendSpace2DNode`]: crate::nodes::BlendSpace2DNode
 //! [`FlipLRNode`]: crate::nodes::FlipLRNode
 //! [`LoopNode`]: crate::nodes::LoopNode
 //! [`GraphNode`]: crate::nodes::GraphNode
+//! [`StateMachineNode`]: crate::nodes::StateMachineNode
+//! [`RootMotionNode`]: crate::nodes::RootMotionNode
+//! [`IkChainNode`]: crate::nodes::IkChainNode
+//! [`NodeLike`]: crate::core::animation_node::NodeLike
+//! [`CustomNode::registered`]: crate::core::animation_node::CustomNode::registered
+//! [`AnimationGraphAppExt`]: crate::core::plugin::AnimationGraphAppExt
+//! [`LookAtNode`]: crate::nodes::LookAtNode
+//! [`FootPlacementNode`]: crate::nodes::FootPlacementNode
+//! [`MakeAdditiveNode`]: crate::nodes::MakeAdditiveNode
+//! [`ApplyAdditiveNode`]: crate::nodes::ApplyAdditiveNode
 //! [`AddF32`]: crate::nodes::AddF32
 //! [`SubF32`]: crate::nodes::SubF32
 //! [`MulF32`]: crate::nodes::MulF32
 //! [`DivF32`]: crate::nodes::DivF32
 //! [`ClampF32`]: crate::nodes::ClampF32
+//! [`ExpressionNode`]: crate::nodes::ExpressionNode
 //! [`RotationArcNode`]: crate::nodes::RotationArcNode
+//! [`AddVec3`]: crate::nodes::AddVec3
+//! [`SubVec3`]: crate::nodes::SubVec3
+//! [`ScaleVec3`]: crate::nodes::ScaleVec3
+//! [`DotVec3`]: crate::nodes::DotVec3
+//! [`CrossVec3`]: crate::nodes::CrossVec3
+//! [`NormalizeVec3`]: crate::nodes::NormalizeVec3
+//! [`LengthVec3`]: crate::nodes::LengthVec3
+//! [`LerpVec3`]: crate::nodes::LerpVec3
+//! [`BuildVec3`]: crate::nodes::BuildVec3
+//! [`DecomposeVec3`]: crate::nodes::DecomposeVec3
+//! [`MulQuat`]: crate::nodes::MulQuat
+//! [`InvertQuat`]: crate::nodes::InvertQuat
+//! [`SlerpQuat`]: crate::nodes::SlerpQuat
+//! [`QuatFromAxisAngle`]: crate::nodes::QuatFromAxisAngle
+//! [`QuatFromEuler`]: crate::nodes::QuatFromEuler
+//! [`AndBool`]: crate::nodes::AndBool
+//! [`OrBool`]: crate::nodes::OrBool
+//! [`NotBool`]: crate::nodes::NotBool
+//! [`CompareF32`]: crate::nodes::CompareF32
+//! [`CompareI32`]: crate::nodes::CompareI32
+//! [`EqualEnum`]: crate::nodes::EqualEnum
+//! [`SelectNode`]: crate::nodes::SelectNode
+//! [`SwitchNode`]: crate::nodes::SwitchNode
+//! [`SmoothF32`]: crate::nodes::SmoothF32
+//! [`SmoothVec3`]: crate::nodes::SmoothVec3
+//! [`SmoothQuat`]: crate::nodes::SmoothQuat
+//! [`Smoothing`]: crate::nodes::Smoothing
 //!
 //! [`NodeLike`]: crate::core::animation_node::NodeLike
 //! [`GraphClip`]: crate::core::animation_clip::GraphClip
@@ -224,6 +370,7 @@
 //! [`AnimationPlayer`]: bevy::animation::prelude::AnimationPlayer
 //! [`AnimationGraph`]: crate::core::animation_graph::AnimationGraph
 //! [`AnimationGraphPlayer`]: crate::core::animation_graph_player::AnimationGraphPlayer
+//! [`AnimationEvent`]: crate::core::animation_graph_player::AnimationEvent
 //! [`AnimatedScene`]: crate::core::animated_scene::AnimatedScene
 
 pub mod chaining;
@@ -231,6 +378,7 @@ pub mod core;
 pub mod flipping;
 pub mod interpolation;
 pub mod nodes;
+pub mod retargeting;
 pub mod sampling;
 mod utils;
 
@@ -238,6 +386,7 @@ pub mod prelude {
     pub use super::chaining::*;
     pub use super::core::prelude::*;
     pub use super::flipping::*;
+    pub use super::interpolation::cubic::*;
     pub use super::interpolation::linear::*;
     pub use super::nodes::*;
     pub use super::sampling::prelude::*;
@@ -0,0 +1,145 @@
+use crate::{
+    core::{
+        animation_clip::SyncMarker,
+        animation_graph::{PinMap, TimeUpdate},
+        animation_node::{AnimationNode, AnimationNodeType, NodeLike},
+        duration_data::DurationData,
+        errors::GraphError,
+        frame::{
+            AdditivePoseFrame, BoneFrame, BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec,
+            ValueFrame,
+        },
+    },
+    interpolation::{
+        cubic::InterpolateCubicSpline,
+        linear::{InterpolateAdditive, InterpolateLinear},
+    },
+    prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext},
+    utils::unwrap::Unwrap,
+};
+use bevy::{
+    math::Vec3,
+    reflect::{std_traits::ReflectDefault, FromReflect, Reflect, TypePath},
+};
+
+/// Layers an additive pose on top of a base pose. The weight scales the additive offsets, so
+/// that a weight of 0 returns the base pose unchanged. Additive scale is a ratio, so it
+/// multiplies the base scale.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct ApplyAdditiveNode {}
+
+impl ApplyAdditiveNode {
+    pub const BASE: &'static str = "Base Pose";
+    pub const ADDITIVE: &'static str = "Additive Pose";
+    pub const WEIGHT: &'static str = "Weight";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::ApplyAdditive(self))
+    }
+}
+
+fn apply_channel<T>(
+    base: &Option<ValueFrame<T>>,
+    additive: &Option<ValueFrame<T>>,
+    apply: impl Fn(&T, &T) -> T,
+) -> Option<ValueFrame<T>>
+where
+    T: InterpolateLinear + InterpolateCubicSpline + FromReflect + TypePath + Clone,
+{
+    match (base, additive) {
+        (Some(base), Some(additive)) => Some(base.merge_linear(additive, apply)),
+        (base, _) => base.clone(),
+    }
+}
+
+impl NodeLike for ApplyAdditiveNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::BASE)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let weight = ctx.parameter_back(Self::WEIGHT)?.unwrap_f32();
+        let base_pose = ctx.pose_back(Self::BASE, input)?;
+        let additive_pose = ctx.pose_back(Self::ADDITIVE, input)?;
+        let mut bone_pose: BonePoseFrame = base_pose.data.unwrap();
+        let additive: AdditivePoseFrame = additive_pose.data.unwrap();
+        let additive = additive.inner_ref();
+
+        // Align the additive pose with the base pose timeline
+        let mut additive = additive.clone();
+        let offset = base_pose.timestamp - additive_pose.timestamp;
+        additive.map_ts(|t| t + offset);
+
+        for (bone_id, idx) in bone_pose.inner_ref().ids.clone() {
+            let Some(additive_idx) = additive.ids.get(&bone_id) else {
+                continue;
+            };
+            let base_bone = &bone_pose.inner_ref().bones[idx];
+            let additive_bone = &additive.bones[*additive_idx];
+            let bone = BoneFrame {
+                rotation: apply_channel(&base_bone.rotation, &additive_bone.rotation, |b, a| {
+                    b.add_weighted(a, weight)
+                }),
+                translation: apply_channel(
+                    &base_bone.translation,
+                    &additive_bone.translation,
+                    |b, a| b.add_weighted(a, weight),
+                ),
+                scale: apply_channel(&base_bone.scale, &additive_bone.scale, |b, a| {
+                    *b * Vec3::ONE.lerp(*a, weight)
+                }),
+                weights: apply_channel(&base_bone.weights, &additive_bone.weights, |b, a| {
+                    b.add_weighted(a, weight)
+                }),
+            };
+            bone_pose.inner_mut().bones[idx] = bone;
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(bone_pose),
+            timestamp: base_pose.timestamp,
+            events: base_pose
+                .events
+                .into_iter()
+                .chain(additive_pose.events.into_iter().map(|mut e| {
+                    e.timestamp += offset;
+                    e
+                }))
+                .collect(),
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::BASE)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::WEIGHT.into(), ParamSpec::F32.into())].into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [
+            (Self::BASE.into(), PoseSpec::BoneSpace),
+            (Self::ADDITIVE.into(), PoseSpec::Additive),
+        ]
+        .into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "⊕ Apply Additive".into()
+    }
+}
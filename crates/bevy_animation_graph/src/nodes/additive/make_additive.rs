@@ -0,0 +1,128 @@
+use crate::{
+    core::{
+        animation_clip::SyncMarker,
+        animation_graph::{PinMap, TimeUpdate},
+        animation_node::{AnimationNode, AnimationNodeType, NodeLike},
+        duration_data::DurationData,
+        errors::GraphError,
+        frame::{BoneFrame, BonePoseFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+    },
+    interpolation::linear::InterpolateAdditive,
+    prelude::{PassContext, SampleLinearAt, SpecContext},
+    utils::unwrap::Unwrap,
+};
+use bevy::{
+    math::Vec3,
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+/// Turns a pose into an additive pose by subtracting a reference pose from it. Scale is stored as
+/// a ratio to the reference scale instead. The reference input is always sampled at
+/// `reference_time`, so it should come from its own node (e.g. a separate clip node playing the
+/// same clip).
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct MakeAdditiveNode {
+    pub reference_time: f32,
+}
+
+impl MakeAdditiveNode {
+    pub const INPUT: &'static str = "Pose In";
+    pub const REFERENCE: &'static str = "Reference Pose";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(reference_time: f32) -> Self {
+        Self { reference_time }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::MakeAdditive(self))
+    }
+}
+
+/// Ratio that takes `reference` to `scale`. Axes with a zero reference scale keep a ratio of 1.
+fn scale_ratio(scale: Vec3, reference: Vec3) -> Vec3 {
+    Vec3::select(reference.cmpeq(Vec3::ZERO), Vec3::ONE, scale / reference)
+}
+
+impl NodeLike for MakeAdditiveNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::INPUT)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let in_pose = ctx.pose_back(Self::INPUT, input)?;
+        let reference_pose =
+            ctx.pose_back(Self::REFERENCE, TimeUpdate::Absolute(self.reference_time))?;
+        let reference_time = reference_pose.timestamp;
+        let reference: BonePoseFrame = reference_pose.data.unwrap();
+        let reference = reference.inner_ref().sample_linear_at(reference_time);
+        let bone_pose: BonePoseFrame = in_pose.data.unwrap();
+        let bone_pose = bone_pose.inner_ref();
+
+        // Channels missing from the reference pose have no offset, so they are left out
+        let mut additive = InnerPoseFrame::default();
+        for (bone_id, idx) in bone_pose.ids.iter() {
+            let Some(reference_idx) = reference.ids.get(bone_id) else {
+                continue;
+            };
+            let bone = &bone_pose.bones[*idx];
+            let reference_bone = &reference.bones[*reference_idx];
+
+            let additive_bone = BoneFrame {
+                rotation: bone
+                    .rotation
+                    .as_ref()
+                    .zip(reference_bone.rotation.as_ref())
+                    .map(|(v, r)| v.map(|v| v.difference(r))),
+                translation: bone
+                    .translation
+                    .as_ref()
+                    .zip(reference_bone.translation.as_ref())
+                    .map(|(v, r)| v.map(|v| v.difference(r))),
+                scale: bone
+                    .scale
+                    .as_ref()
+                    .zip(reference_bone.scale.as_ref())
+                    .map(|(v, r)| v.map(|v| scale_ratio(*v, *r))),
+                weights: bone
+                    .weights
+                    .as_ref()
+                    .zip(reference_bone.weights.as_ref())
+                    .map(|(v, r)| v.map(|v| v.difference(r))),
+            };
+
+            additive.add_bone(additive_bone, *bone_id);
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::Additive(additive.into()),
+            timestamp: in_pose.timestamp,
+            events: in_pose.events,
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [
+            (Self::INPUT.into(), PoseSpec::BoneSpace),
+            (Self::REFERENCE.into(), PoseSpec::BoneSpace),
+        ]
+        .into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::Additive)
+    }
+
+    fn display_name(&self) -> String {
+        "∆ Make Additive".into()
+    }
+}
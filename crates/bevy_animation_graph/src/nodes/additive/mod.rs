@@ -0,0 +1,5 @@
+mod apply_additive;
+mod make_additive;
+
+pub use apply_additive::*;
+pub use make_additive::*;
@@ -1,5 +1,7 @@
 mod f32;
+mod quat;
 mod vec3;
 
 pub use f32::*;
+pub use quat::*;
 pub use vec3::*;
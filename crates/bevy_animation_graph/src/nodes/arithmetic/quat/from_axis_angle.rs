@@ -0,0 +1,57 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct QuatFromAxisAngle {}
+
+impl QuatFromAxisAngle {
+    pub const AXIS: &'static str = "Axis";
+    pub const ANGLE: &'static str = "Angle";
+    pub const OUTPUT: &'static str = "Quat Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::QuatFromAxisAngle(self))
+    }
+}
+
+impl NodeLike for QuatFromAxisAngle {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let axis: Vec3 = ctx.parameter_back(Self::AXIS)?.unwrap();
+        let angle = ctx.parameter_back(Self::ANGLE)?.unwrap_f32();
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Quat(Quat::from_axis_angle(axis.normalize_or_zero(), angle)),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::AXIS.into(), ParamSpec::Vec3.into()),
+            (Self::ANGLE.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Quat)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Quat From Axis Angle".into()
+    }
+}
@@ -0,0 +1,100 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use bevy::math::EulerRot;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Builds a rotation from euler angles (in radians), one for each axis. The `mode` determines
+/// the order in which the rotations around each axis are applied.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct QuatFromEuler {
+    pub mode: EulerRot,
+}
+
+impl QuatFromEuler {
+    pub const X: &'static str = "X";
+    pub const Y: &'static str = "Y";
+    pub const Z: &'static str = "Z";
+    pub const OUTPUT: &'static str = "Quat Out";
+
+    pub fn new(mode: EulerRot) -> Self {
+        Self { mode }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::QuatFromEuler(self))
+    }
+}
+
+/// Reorders the per-axis angles into the argument order expected by [`Quat::from_euler`]
+fn angles_in_order(mode: EulerRot, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    match mode {
+        EulerRot::ZYX => (z, y, x),
+        EulerRot::ZXY => (z, x, y),
+        EulerRot::YXZ => (y, x, z),
+        EulerRot::YZX => (y, z, x),
+        EulerRot::XYZ => (x, y, z),
+        EulerRot::XZY => (x, z, y),
+    }
+}
+
+impl NodeLike for QuatFromEuler {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let x = ctx.parameter_back(Self::X)?.unwrap_f32();
+        let y = ctx.parameter_back(Self::Y)?.unwrap_f32();
+        let z = ctx.parameter_back(Self::Z)?.unwrap_f32();
+
+        let (a, b, c) = angles_in_order(self.mode, x, y, z);
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Quat(Quat::from_euler(self.mode, a, b, c)),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::X.into(), ParamSpec::F32.into()),
+            (Self::Y.into(), ParamSpec::F32.into()),
+            (Self::Z.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Quat)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Quat From Euler".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angles_follow_axes() {
+        let (x, y, z) = (0.2, 0.3, 0.1);
+        let rot_x = Quat::from_rotation_x(x);
+        let rot_y = Quat::from_rotation_y(y);
+        let rot_z = Quat::from_rotation_z(z);
+
+        for (mode, expected) in [
+            (EulerRot::YXZ, rot_y * rot_x * rot_z),
+            (EulerRot::XYZ, rot_x * rot_y * rot_z),
+            (EulerRot::ZYX, rot_z * rot_y * rot_x),
+        ] {
+            let (a, b, c) = angles_in_order(mode, x, y, z);
+            assert!(Quat::from_euler(mode, a, b, c).abs_diff_eq(expected, 1e-5));
+        }
+    }
+}
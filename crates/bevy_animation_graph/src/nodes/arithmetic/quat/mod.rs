@@ -0,0 +1,11 @@
+mod from_axis_angle;
+mod from_euler;
+mod invert_quat;
+mod mul_quat;
+mod slerp_quat;
+
+pub use from_axis_angle::*;
+pub use from_euler::*;
+pub use invert_quat::*;
+pub use mul_quat::*;
+pub use slerp_quat::*;
@@ -0,0 +1,60 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SlerpQuat {}
+
+impl SlerpQuat {
+    pub const INPUT_1: &'static str = "Quat In 1";
+    pub const INPUT_2: &'static str = "Quat In 2";
+    pub const FACTOR: &'static str = "Factor";
+    pub const OUTPUT: &'static str = "Quat Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::SlerpQuat(self))
+    }
+}
+
+impl NodeLike for SlerpQuat {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input_1: Quat = ctx.parameter_back(Self::INPUT_1)?.unwrap();
+        let input_2: Quat = ctx.parameter_back(Self::INPUT_2)?.unwrap();
+        let factor = ctx.parameter_back(Self::FACTOR)?.unwrap_f32();
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Quat(input_1.slerp(input_2, factor)),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::INPUT_1.into(), ParamSpec::Quat.into()),
+            (Self::INPUT_2.into(), ParamSpec::Quat.into()),
+            (Self::FACTOR.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Quat)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Slerp".into()
+    }
+}
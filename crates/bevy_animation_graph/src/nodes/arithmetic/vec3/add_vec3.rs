@@ -0,0 +1,53 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct AddVec3 {}
+
+impl AddVec3 {
+    pub const INPUT_1: &'static str = "Vec3 In 1";
+    pub const INPUT_2: &'static str = "Vec3 In 2";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::AddVec3(self))
+    }
+}
+
+impl NodeLike for AddVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input_1: Vec3 = ctx.parameter_back(Self::INPUT_1)?.unwrap();
+        let input_2: Vec3 = ctx.parameter_back(Self::INPUT_2)?.unwrap();
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Vec3(input_1 + input_2))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::INPUT_1.into(), ParamSpec::Vec3.into()),
+            (Self::INPUT_2.into(), ParamSpec::Vec3.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "+ Add Vec3".into()
+    }
+}
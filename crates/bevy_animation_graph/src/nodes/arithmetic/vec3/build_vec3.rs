@@ -0,0 +1,55 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct BuildVec3 {}
+
+impl BuildVec3 {
+    pub const X: &'static str = "X";
+    pub const Y: &'static str = "Y";
+    pub const Z: &'static str = "Z";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::BuildVec3(self))
+    }
+}
+
+impl NodeLike for BuildVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let x = ctx.parameter_back(Self::X)?.unwrap_f32();
+        let y = ctx.parameter_back(Self::Y)?.unwrap_f32();
+        let z = ctx.parameter_back(Self::Z)?.unwrap_f32();
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Vec3(Vec3::new(x, y, z)))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::X.into(), ParamSpec::F32.into()),
+            (Self::Y.into(), ParamSpec::F32.into()),
+            (Self::Z.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Build Vec3".into()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct DecomposeVec3 {}
+
+impl DecomposeVec3 {
+    pub const INPUT: &'static str = "Vec3 In";
+    pub const X: &'static str = "X";
+    pub const Y: &'static str = "Y";
+    pub const Z: &'static str = "Z";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::DecomposeVec3(self))
+    }
+}
+
+impl NodeLike for DecomposeVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input: Vec3 = ctx.parameter_back(Self::INPUT)?.unwrap();
+
+        Ok([
+            (Self::X.into(), ParamValue::F32(input.x)),
+            (Self::Y.into(), ParamValue::F32(input.y)),
+            (Self::Z.into(), ParamValue::F32(input.z)),
+        ]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::Vec3.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [
+            (Self::X.into(), ParamSpec::F32),
+            (Self::Y.into(), ParamSpec::F32),
+            (Self::Z.into(), ParamSpec::F32),
+        ]
+        .into()
+    }
+
+    fn display_name(&self) -> String {
+        "Decompose Vec3".into()
+    }
+}
@@ -0,0 +1,60 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct LerpVec3 {}
+
+impl LerpVec3 {
+    pub const INPUT_1: &'static str = "Vec3 In 1";
+    pub const INPUT_2: &'static str = "Vec3 In 2";
+    pub const FACTOR: &'static str = "Factor";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::LerpVec3(self))
+    }
+}
+
+impl NodeLike for LerpVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input_1: Vec3 = ctx.parameter_back(Self::INPUT_1)?.unwrap();
+        let input_2: Vec3 = ctx.parameter_back(Self::INPUT_2)?.unwrap();
+        let factor = ctx.parameter_back(Self::FACTOR)?.unwrap_f32();
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Vec3(input_1.lerp(input_2, factor)),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::INPUT_1.into(), ParamSpec::Vec3.into()),
+            (Self::INPUT_2.into(), ParamSpec::Vec3.into()),
+            (Self::FACTOR.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Lerp Vec3".into()
+    }
+}
@@ -1,3 +1,23 @@
+mod add_vec3;
+mod build_vec3;
+mod cross_vec3;
+mod decompose_vec3;
+mod dot_vec3;
+mod length_vec3;
+mod lerp_vec3;
+mod normalize_vec3;
 mod rotation_arc;
+mod scale_vec3;
+mod sub_vec3;
 
+pub use add_vec3::*;
+pub use build_vec3::*;
+pub use cross_vec3::*;
+pub use decompose_vec3::*;
+pub use dot_vec3::*;
+pub use length_vec3::*;
+pub use lerp_vec3::*;
+pub use normalize_vec3::*;
 pub use rotation_arc::*;
+pub use scale_vec3::*;
+pub use sub_vec3::*;
@@ -0,0 +1,51 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct NormalizeVec3 {}
+
+impl NormalizeVec3 {
+    pub const INPUT: &'static str = "Vec3 In";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::NormalizeVec3(self))
+    }
+}
+
+impl NodeLike for NormalizeVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input: Vec3 = ctx.parameter_back(Self::INPUT)?.unwrap();
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Vec3(input.normalize_or_zero()),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::Vec3.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Normalize".into()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct ScaleVec3 {}
+
+impl ScaleVec3 {
+    pub const INPUT: &'static str = "Vec3 In";
+    pub const SCALE: &'static str = "F32 In";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::ScaleVec3(self))
+    }
+}
+
+impl NodeLike for ScaleVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input: Vec3 = ctx.parameter_back(Self::INPUT)?.unwrap();
+        let scale = ctx.parameter_back(Self::SCALE)?.unwrap_f32();
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Vec3(input * scale))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::INPUT.into(), ParamSpec::Vec3.into()),
+            (Self::SCALE.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Scale Vec3".into()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::animation_clip::SyncMarker;
 use crate::core::animation_graph::{PinMap, TimeUpdate};
 use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
@@ -6,10 +7,43 @@ use crate::core::frame::{PoseFrame, PoseSpec};
 use crate::interpolation::linear::InterpolateLinear;
 use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How the time of the inputs of a [`BlendNode`] is kept in sync.
+///
+/// Synchronization only applies when both inputs have a finite duration, otherwise both inputs
+/// receive the same time update as the blend node and a warning is logged. In particular, inputs
+/// coming from a [`LoopNode`] have no duration, so to blend looping animations in sync, place the
+/// loop node after the blend node instead.
+///
+/// [`LoopNode`]: crate::nodes::LoopNode
+#[derive(Reflect, Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum BlendSyncMode {
+    /// Both inputs receive the same time update as the blend node.
+    #[default]
+    Disabled,
+    /// Inputs advance in normalized phase, so that both start and end their cycles at the same
+    /// time. The length of the blended cycle is interpolated using the blend factor.
+    Phase,
+    /// Like [`BlendSyncMode::Phase`], but the input with the lowest weight is aligned to the
+    /// other one using named sync markers. Falls back to phase matching when the inputs have no
+    /// matching sync markers.
+    SyncMarkers,
+}
+
+/// Runtime state of a [`BlendNode`], stored per graph instance as node state.
+#[derive(Default)]
+struct BlendState {
+    /// Whether the node already warned that its inputs cannot be synced
+    warned_unsynced: bool,
+}
 
 #[derive(Reflect, Clone, Debug, Default)]
 #[reflect(Default)]
-pub struct BlendNode;
+pub struct BlendNode {
+    pub sync_mode: BlendSyncMode,
+}
 
 impl BlendNode {
     pub const INPUT_1: &'static str = "Pose In 1";
@@ -18,12 +52,263 @@ impl BlendNode {
     pub const OUTPUT: &'static str = "Pose Out";
 
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_sync_mode(mut self, sync_mode: BlendSyncMode) -> Self {
+        self.sync_mode = sync_mode;
+        self
     }
 
     pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
         AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::Blend(self))
     }
+
+    /// Weight of an input in the output for the given blend factor
+    fn input_weight(pin: &str, alpha: f32) -> f32 {
+        if pin == Self::INPUT_1 {
+            1. - alpha.clamp(0., 1.)
+        } else {
+            alpha.clamp(0., 1.)
+        }
+    }
+
+    /// Queries both input poses so that they stay in sync, and remaps them into the output
+    /// timeline.
+    fn synced_poses(
+        &self,
+        input: TimeUpdate,
+        alpha: f32,
+        duration_1: f32,
+        duration_2: f32,
+        ctx: &mut PassContext,
+    ) -> Result<(PoseFrame, PoseFrame), GraphError> {
+        let duration = duration_1 + (duration_2 - duration_1) * alpha.clamp(0., 1.);
+        let time = input.apply(ctx.prev_time_fwd());
+
+        // The input with the highest weight leads, the other one follows
+        let (leader, follower, leader_duration, follower_duration) = if alpha <= 0.5 {
+            (Self::INPUT_1, Self::INPUT_2, duration_1, duration_2)
+        } else {
+            (Self::INPUT_2, Self::INPUT_1, duration_2, duration_1)
+        };
+
+        let leader_prev_time = ctx.prev_time_back(leader);
+        let leader_time = match input {
+            TimeUpdate::Delta(dt) => leader_prev_time + dt * leader_duration / duration,
+            TimeUpdate::Absolute(t) => t * leader_duration / duration,
+        };
+        let leader_delta = leader_time - leader_prev_time;
+
+        let mut follower_time = leader_time / leader_duration * follower_duration;
+        if self.sync_mode == BlendSyncMode::SyncMarkers {
+            let leader_markers = ctx.sync_markers_back(leader)?;
+            let follower_markers = ctx.sync_markers_back(follower)?;
+            if let Some(t) = sync_marker_time(
+                leader_time,
+                &leader_markers,
+                leader_duration,
+                &follower_markers,
+                follower_duration,
+            ) {
+                follower_time = t;
+            }
+        }
+
+        let leader_update = synced_update(input, leader_prev_time, leader_time, leader_delta);
+        let follower_update = synced_update(
+            input,
+            ctx.prev_time_back(follower),
+            follower_time,
+            leader_delta * follower_duration / leader_duration,
+        );
+        let mut leader_pose = ctx
+            .with_root_motion_weight(Self::input_weight(leader, alpha))
+            .pose_back(leader, leader_update)?;
+        let mut follower_pose = ctx
+            .with_root_motion_weight(Self::input_weight(follower, alpha))
+            .pose_back(follower, follower_update)?;
+
+        for (pose, input_duration) in [
+            (&mut leader_pose, leader_duration),
+            (&mut follower_pose, follower_duration),
+        ] {
+            let pose_time = pose.timestamp;
+            let scale = duration / input_duration;
+            pose.map_ts(|t| time + (t - pose_time) * scale);
+        }
+
+        if leader == Self::INPUT_1 {
+            Ok((leader_pose, follower_pose))
+        } else {
+            Ok((follower_pose, leader_pose))
+        }
+    }
+}
+
+/// Time update that moves a synced input from `prev_time` to `time`. Playback keeps advancing by
+/// delta updates, so that downstream nodes (e.g. state machines or root motion) see continuous
+/// time. Absolute updates are only sent for actual jumps: when the blend node itself received an
+/// absolute update, or when staying in sync moves the input further than its regular advance in
+/// one frame, `frame_delta`.
+pub(crate) fn synced_update(
+    input: TimeUpdate,
+    prev_time: f32,
+    time: f32,
+    frame_delta: f32,
+) -> TimeUpdate {
+    const TOLERANCE: f32 = 1e-4;
+
+    match input {
+        TimeUpdate::Absolute(_) => TimeUpdate::Absolute(time),
+        TimeUpdate::Delta(_) => {
+            let delta = time - prev_time;
+            if (delta - frame_delta).abs() > frame_delta.abs() + TOLERANCE {
+                TimeUpdate::Absolute(time)
+            } else {
+                TimeUpdate::Delta(delta)
+            }
+        }
+    }
+}
+
+/// Durations of the given weighted inputs, if all of them are finite and positive.
+pub(crate) fn input_durations(
+    inputs: &[(&str, f32)],
+    ctx: &mut PassContext,
+) -> Result<Option<Vec<f32>>, GraphError> {
+    let mut durations = Vec::with_capacity(inputs.len());
+    for (pin, _) in inputs {
+        match ctx.duration_back(*pin)? {
+            Some(duration) if duration > 0. => durations.push(duration),
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(durations))
+}
+
+/// Duration of a blend of inputs kept in sync by phase, given their weights and durations.
+pub(crate) fn weighted_duration(inputs: &[(&str, f32)], durations: &[f32]) -> f32 {
+    inputs.iter().zip(durations).map(|((_, w), d)| w * d).sum()
+}
+
+/// Queries the given inputs and blends them using their weights, which should add up to one.
+/// When all inputs have a finite duration, their playback is kept in sync by normalized phase,
+/// led by the input with the highest weight, so that inputs that were not evaluated in a while
+/// resume in phase with the others.
+pub(crate) fn blend_weighted_inputs(
+    inputs: &[(&str, f32)],
+    input: TimeUpdate,
+    ctx: &mut PassContext,
+) -> Result<PoseFrame, GraphError> {
+    let time = input.apply(ctx.prev_time_fwd());
+
+    let mut poses = Vec::with_capacity(inputs.len());
+    if let Some(durations) = input_durations(inputs, ctx)? {
+        let duration = weighted_duration(inputs, &durations);
+
+        // The input with the highest weight leads, the others follow its phase
+        let (leader, _) = inputs
+            .iter()
+            .enumerate()
+            .max_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+            .unwrap();
+        let leader_duration = durations[leader];
+        let leader_prev_time = ctx.prev_time_back(inputs[leader].0);
+        let leader_time = match input {
+            TimeUpdate::Delta(dt) => leader_prev_time + dt * leader_duration / duration,
+            TimeUpdate::Absolute(t) => t * leader_duration / duration,
+        };
+        let phase = leader_time / leader_duration;
+        let phase_delta = (leader_time - leader_prev_time) / leader_duration;
+
+        for ((pin, weight), input_duration) in inputs.iter().zip(durations) {
+            let update = synced_update(
+                input,
+                ctx.prev_time_back(*pin),
+                phase * input_duration,
+                phase_delta * input_duration,
+            );
+            let mut pose = ctx
+                .with_root_motion_weight(*weight)
+                .pose_back(*pin, update)?;
+            let pose_time = pose.timestamp;
+            let scale = duration / input_duration;
+            pose.map_ts(|t| time + (t - pose_time) * scale);
+            poses.push(pose);
+        }
+    } else {
+        for (pin, weight) in inputs {
+            poses.push(
+                ctx.with_root_motion_weight(*weight)
+                    .pose_back(*pin, input)?,
+            );
+        }
+    }
+
+    let mut total_weight = 0.;
+    let mut out: Option<PoseFrame> = None;
+    for (pose, (_, weight)) in poses.into_iter().zip(inputs) {
+        total_weight += weight;
+        out = Some(match out {
+            None => pose,
+            Some(out) => out.interpolate_linear(&pose, weight / total_weight),
+        });
+    }
+
+    Ok(out.unwrap_or_else(|| PoseFrame {
+        timestamp: time,
+        ..default()
+    }))
+}
+
+/// Maps a time in the leader's timeline into the follower's timeline, so that both are at the
+/// same fraction of the way between sync markers with matching names. Markers are assumed to be
+/// sorted by time and to repeat every cycle. Returns `None` if the markers don't match.
+fn sync_marker_time(
+    leader_time: f32,
+    leader_markers: &[SyncMarker],
+    leader_duration: f32,
+    follower_markers: &[SyncMarker],
+    follower_duration: f32,
+) -> Option<f32> {
+    if leader_markers.is_empty() || follower_markers.is_empty() {
+        return None;
+    }
+
+    let cycle = leader_time.div_euclid(leader_duration);
+    let t = leader_time.rem_euclid(leader_duration);
+
+    // Leader segment containing the current time
+    let n = leader_markers.len();
+    let (prev_idx, prev_time) = match leader_markers.iter().rposition(|m| m.time <= t) {
+        Some(i) => (i, leader_markers[i].time),
+        None => (n - 1, leader_markers[n - 1].time - leader_duration),
+    };
+    let next_idx = (prev_idx + 1) % n;
+    let mut next_time = leader_markers[next_idx].time;
+    if next_time <= prev_time {
+        next_time += leader_duration;
+    }
+    let fraction = (t - prev_time) / (next_time - prev_time);
+
+    // Matching follower segment
+    let m = follower_markers.len();
+    let follower_prev_idx = follower_markers
+        .iter()
+        .position(|f| f.name == leader_markers[prev_idx].name)?;
+    let follower_next_idx = (1..=m)
+        .map(|offset| (follower_prev_idx + offset) % m)
+        .find(|i| follower_markers[*i].name == leader_markers[next_idx].name)?;
+    let follower_prev_time = follower_markers[follower_prev_idx].time;
+    let mut follower_next_time = follower_markers[follower_next_idx].time;
+    if follower_next_time <= follower_prev_time {
+        follower_next_time += follower_duration;
+    }
+
+    let local_time = follower_prev_time + fraction * (follower_next_time - follower_prev_time);
+
+    Some(cycle * follower_duration + local_time.rem_euclid(follower_duration))
 }
 
 impl NodeLike for BlendNode {
@@ -32,7 +317,14 @@ impl NodeLike for BlendNode {
         let duration_2 = ctx.duration_back(Self::INPUT_2)?;
 
         let out_duration = match (duration_1, duration_2) {
-            (Some(duration_1), Some(duration_2)) => Some(duration_1.max(duration_2)),
+            (Some(duration_1), Some(duration_2)) => {
+                if self.sync_mode == BlendSyncMode::Disabled {
+                    Some(duration_1.max(duration_2))
+                } else {
+                    let alpha = ctx.parameter_back(Self::FACTOR)?.unwrap_f32();
+                    Some(duration_1 + (duration_2 - duration_1) * alpha.clamp(0., 1.))
+                }
+            }
             (Some(duration_1), None) => Some(duration_1),
             (None, Some(duration_2)) => Some(duration_2),
             (None, None) => None,
@@ -46,10 +338,44 @@ impl NodeLike for BlendNode {
         input: TimeUpdate,
         mut ctx: PassContext,
     ) -> Result<Option<PoseFrame>, GraphError> {
-        let in_frame_1 = ctx.pose_back(Self::INPUT_1, input)?;
-        let in_frame_2 = ctx.pose_back(Self::INPUT_2, input)?;
-
         let alpha = ctx.parameter_back(Self::FACTOR)?.unwrap_f32();
+
+        let durations = if self.sync_mode == BlendSyncMode::Disabled {
+            None
+        } else {
+            match (
+                ctx.duration_back(Self::INPUT_1)?,
+                ctx.duration_back(Self::INPUT_2)?,
+            ) {
+                (Some(duration_1), Some(duration_2)) if duration_1 > 0. && duration_2 > 0. => {
+                    Some((duration_1, duration_2))
+                }
+                _ => {
+                    let state = ctx.node_state_mut::<BlendState>();
+                    if !state.warned_unsynced {
+                        state.warned_unsynced = true;
+                        warn!(
+                            "Blend node {} cannot sync inputs without a finite duration, place \
+                            any loop node after the blend node instead.",
+                            ctx.node_context.unwrap().node_id
+                        );
+                    }
+                    None
+                }
+            }
+        };
+
+        let (in_frame_1, in_frame_2) = if let Some((duration_1, duration_2)) = durations {
+            self.synced_poses(input, alpha, duration_1, duration_2, &mut ctx)?
+        } else {
+            (
+                ctx.with_root_motion_weight(Self::input_weight(Self::INPUT_1, alpha))
+                    .pose_back(Self::INPUT_1, input)?,
+                ctx.with_root_motion_weight(Self::input_weight(Self::INPUT_2, alpha))
+                    .pose_back(Self::INPUT_2, input)?,
+            )
+        };
+
         let out = in_frame_1.interpolate_linear(&in_frame_2, alpha);
 
         Ok(Some(out))
@@ -75,3 +401,54 @@ impl NodeLike for BlendNode {
         "∑ Blend".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markers(markers: &[(f32, &str)]) -> Vec<SyncMarker> {
+        markers
+            .iter()
+            .map(|(time, name)| SyncMarker {
+                time: *time,
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sync_marker_time_aligns_segments() {
+        let leader = markers(&[(0.2, "left"), (0.6, "right")]);
+        let follower = markers(&[(0.1, "right"), (0.5, "left")]);
+
+        // Halfway between "left" and "right" in the leader
+        let t = sync_marker_time(0.4, &leader, 1., &follower, 1.).unwrap();
+        assert!((t - 0.8).abs() < 1e-5);
+
+        // Before the first leader marker, the segment wraps around the end of the cycle
+        let t = sync_marker_time(0.0, &leader, 1., &follower, 1.).unwrap();
+        assert!((t - (0.1 + 0.4 * 2. / 3.)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sync_marker_time_without_match() {
+        let leader = markers(&[(0.2, "left")]);
+        let follower = markers(&[(0.5, "right")]);
+
+        assert_eq!(sync_marker_time(0.4, &leader, 1., &follower, 1.), None);
+        assert_eq!(sync_marker_time(0.4, &leader, 1., &[], 1.), None);
+    }
+
+    #[test]
+    fn synced_update_sends_deltas() {
+        // Regular playback advances by deltas
+        let update = synced_update(TimeUpdate::Delta(0.25), 0.25, 0.5, 0.25);
+        assert!(matches!(update, TimeUpdate::Delta(dt) if dt == 0.25));
+        // Moving further than one frame's advance to stay in sync is a jump
+        let update = synced_update(TimeUpdate::Delta(0.25), 0.25, 1.5, 0.25);
+        assert!(matches!(update, TimeUpdate::Absolute(t) if t == 1.5));
+        // Absolute updates are forwarded as such
+        let update = synced_update(TimeUpdate::Absolute(0.5), 0., 0.5, 0.);
+        assert!(matches!(update, TimeUpdate::Absolute(t) if t == 0.5));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::core::animation_graph::{PinId, PinMap, TimeUpdate};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::duration_data::DurationData;
+use crate::core::errors::GraphError;
+use crate::core::frame::{PoseFrame, PoseSpec};
+use crate::nodes::blend_node::{blend_weighted_inputs, input_durations, weighted_duration};
+use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext};
+use crate::utils::cache::Cached;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Pose input of a [`BlendSpace1DNode`], placed at the given value along the blend axis.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[reflect(Default)]
+pub struct BlendSample1D {
+    pub pin: PinId,
+    pub value: f32,
+}
+
+/// Blends between N pose inputs placed along an axis. Only the two samples closest to the input
+/// position are evaluated, and positions outside the range of samples are clamped.
+///
+/// When both samples involved have a finite duration, their playback is kept in sync by
+/// normalized phase, and the duration of the output is the weighted average of their durations.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct BlendSpace1DNode {
+    pub samples: Vec<BlendSample1D>,
+    /// Sample indices sorted by value, rebuilt when the samples change
+    #[reflect(ignore)]
+    order: Cached<Vec<BlendSample1D>, Vec<usize>>,
+}
+
+impl BlendSpace1DNode {
+    pub const POSITION: &'static str = "Position";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(samples: Vec<BlendSample1D>) -> Self {
+        let order = Cached::new(samples.clone(), sorted_order(&samples));
+        Self { samples, order }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::BlendSpace1D(self))
+    }
+
+    /// Returns the two samples surrounding the given position and the blend factor between them.
+    fn neighbours(&self, position: f32) -> Option<(&BlendSample1D, &BlendSample1D, f32)> {
+        let order = self
+            .order
+            .get_or_update(self.samples.as_slice(), sorted_order);
+        let sample = |i: &usize| &self.samples[*i];
+        let first = sample(order.first()?);
+        let last = sample(order.last()?);
+
+        if position <= first.value {
+            return Some((first, first, 0.));
+        }
+        if position >= last.value {
+            return Some((last, last, 0.));
+        }
+
+        order.windows(2).find_map(|pair| {
+            let (a, b) = (sample(&pair[0]), sample(&pair[1]));
+            if a.value <= position && position <= b.value {
+                let alpha = if b.value > a.value {
+                    (position - a.value) / (b.value - a.value)
+                } else {
+                    0.
+                };
+                Some((a, b, alpha))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Pins of the samples surrounding the input position, and their weights.
+    fn weighted_inputs(&self, ctx: &mut PassContext) -> Result<Vec<(&str, f32)>, GraphError> {
+        let position = ctx.parameter_back(Self::POSITION)?.unwrap_f32();
+        Ok(match self.neighbours(position) {
+            None => vec![],
+            Some((a, b, _)) if a.pin == b.pin => vec![(a.pin.as_str(), 1.)],
+            Some((a, b, alpha)) => vec![(a.pin.as_str(), 1. - alpha), (b.pin.as_str(), alpha)],
+        })
+    }
+}
+
+/// Indices of the samples, sorted by value
+fn sorted_order(samples: &[BlendSample1D]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    order.sort_by(|a, b| samples[*a].value.total_cmp(&samples[*b].value));
+    order
+}
+
+impl NodeLike for BlendSpace1DNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        let inputs = self.weighted_inputs(&mut ctx)?;
+        let duration = input_durations(&inputs, &mut ctx)?
+            .map(|durations| weighted_duration(&inputs, &durations));
+
+        Ok(Some(duration))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let inputs = self.weighted_inputs(&mut ctx)?;
+        Ok(Some(blend_weighted_inputs(&inputs, input, &mut ctx)?))
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::POSITION.into(), ParamSpec::F32.into())].into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        self.samples
+            .iter()
+            .map(|s| (s.pin.clone(), PoseSpec::BoneSpace))
+            .collect()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "↔ Blend Space 1D".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        let sample = |pin: &str, value| BlendSample1D {
+            pin: pin.into(),
+            value,
+        };
+        let mut node = BlendSpace1DNode::new(vec![
+            sample("run", 4.),
+            sample("idle", 0.),
+            sample("walk", 1.),
+        ]);
+        let neighbours = |node: &BlendSpace1DNode, position| {
+            let (a, b, alpha) = node.neighbours(position).unwrap();
+            (a.pin.to_string(), b.pin.to_string(), alpha)
+        };
+        let expected = |a: &str, b: &str, alpha| (a.to_string(), b.to_string(), alpha);
+
+        // Below the range of samples
+        assert_eq!(neighbours(&node, -1.), expected("idle", "idle", 0.));
+        // Exactly on a sample
+        assert_eq!(neighbours(&node, 1.), expected("idle", "walk", 1.));
+        // Between samples
+        assert_eq!(neighbours(&node, 2.), expected("walk", "run", 1. / 3.));
+        // Above the range of samples
+        assert_eq!(neighbours(&node, 5.), expected("run", "run", 0.));
+
+        // Samples edited after construction (e.g. through reflection) don't need to be sorted
+        node.samples.push(sample("jog", 2.));
+        assert_eq!(neighbours(&node, 3.), expected("jog", "run", 0.5));
+
+        assert!(BlendSpace1DNode::default().neighbours(0.).is_none());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::core::animation_graph::{PinId, PinMap, TimeUpdate};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::duration_data::DurationData;
+use crate::core::errors::GraphError;
+use crate::core::frame::{PoseFrame, PoseSpec};
+use crate::nodes::blend_node::{blend_weighted_inputs, input_durations, weighted_duration};
+use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext};
+use crate::utils::cache::Cached;
+use crate::utils::unwrap::Unwrap;
+use bevy::{math::DVec2, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Pose input of a [`BlendSpace2DNode`], placed at the given point of the blend space.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[reflect(Default)]
+pub struct BlendSample2D {
+    pub pin: PinId,
+    pub position: Vec2,
+}
+
+/// Which components of the `Vec3` position input are used as the coordinates in the blend space.
+#[derive(Reflect, Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum BlendSpacePlane {
+    XY,
+    /// Ground plane, useful for blending based on a velocity
+    #[default]
+    XZ,
+    YZ,
+}
+
+impl BlendSpacePlane {
+    pub fn project(&self, v: Vec3) -> Vec2 {
+        match self {
+            BlendSpacePlane::XY => Vec2::new(v.x, v.y),
+            BlendSpacePlane::XZ => Vec2::new(v.x, v.z),
+            BlendSpacePlane::YZ => Vec2::new(v.y, v.z),
+        }
+    }
+}
+
+/// Blends between N pose inputs placed on a plane. The samples are triangulated, and the poses of
+/// the triangle containing the input position are blended using barycentric weights. Positions
+/// outside of the triangulation are clamped to its closest point.
+///
+/// When all samples involved have a finite duration, their playback is kept in sync by normalized
+/// phase, and the duration of the output is the weighted average of their durations.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct BlendSpace2DNode {
+    pub samples: Vec<BlendSample2D>,
+    pub plane: BlendSpacePlane,
+    /// Triangles of sample indices, rebuilt when the samples change
+    #[reflect(ignore)]
+    triangulation: Cached<Vec<BlendSample2D>, Vec<[usize; 3]>>,
+}
+
+impl BlendSpace2DNode {
+    pub const POSITION: &'static str = "Position";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(samples: Vec<BlendSample2D>, plane: BlendSpacePlane) -> Self {
+        let triangulation = Cached::new(samples.clone(), triangulate(&sample_points(&samples)));
+        Self {
+            samples,
+            plane,
+            triangulation,
+        }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::BlendSpace2D(self))
+    }
+
+    /// Sample indices with a non-zero weight, and their weights.
+    fn weights(&self, ctx: &mut PassContext) -> Result<Vec<(usize, f32)>, GraphError> {
+        let position: Vec3 = ctx.parameter_back(Self::POSITION)?.unwrap();
+        let triangles = self
+            .triangulation
+            .get_or_update(self.samples.as_slice(), |samples| {
+                triangulate(&sample_points(samples))
+            });
+        Ok(sample_weights(
+            &self.samples,
+            &triangles,
+            self.plane.project(position),
+        ))
+    }
+
+    /// Pins of the samples with a non-zero weight, and their weights.
+    fn weighted_inputs<'a>(&'a self, weights: &[(usize, f32)]) -> Vec<(&'a str, f32)> {
+        weights
+            .iter()
+            .map(|(i, w)| (self.samples[*i].pin.as_str(), *w))
+            .collect()
+    }
+}
+
+impl NodeLike for BlendSpace2DNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        let weights = self.weights(&mut ctx)?;
+        let inputs = self.weighted_inputs(&weights);
+        let duration = input_durations(&inputs, &mut ctx)?
+            .map(|durations| weighted_duration(&inputs, &durations));
+
+        Ok(Some(duration))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let weights = self.weights(&mut ctx)?;
+        let inputs = self.weighted_inputs(&weights);
+        Ok(Some(blend_weighted_inputs(&inputs, input, &mut ctx)?))
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::POSITION.into(), ParamSpec::Vec3.into())].into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        self.samples
+            .iter()
+            .map(|s| (s.pin.clone(), PoseSpec::BoneSpace))
+            .collect()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "✥ Blend Space 2D".into()
+    }
+}
+
+fn sample_points(samples: &[BlendSample2D]) -> Vec<Vec2> {
+    samples.iter().map(|s| s.position).collect()
+}
+
+/// Whether `p` is strictly inside the circumcircle of the triangle. Points on the circumcircle
+/// are outside, so that cocircular points don't get overlapping triangles. Degenerate triangles
+/// are treated as containing every point.
+fn in_circumcircle(a: DVec2, b: DVec2, c: DVec2, p: DVec2) -> bool {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2. * ab.perp_dot(ac);
+    if d.abs() <= f64::EPSILON {
+        return true;
+    }
+    let center = a + DVec2::new(
+        ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+        ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+    ) / d;
+    center.distance_squared(p) < center.distance_squared(a) * (1. - 1e-9)
+}
+
+/// Delaunay triangulation of the given points, using the Bowyer–Watson algorithm. When four or
+/// more points are cocircular, only one of their triangulations is kept. Degenerate (collinear)
+/// triangles are skipped.
+fn triangulate(points: &[Vec2]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    // Points are followed by the vertices of a triangle containing all of them, which are
+    // removed from the triangulation at the end
+    let mut vertices: Vec<DVec2> = points.iter().map(|p| p.as_dvec2()).collect();
+    let (min, max) = vertices
+        .iter()
+        .fold((DVec2::MAX, DVec2::MIN), |(min, max), p| {
+            (min.min(*p), max.max(*p))
+        });
+    let center = (min + max) / 2.;
+    let size = (max - min).max_element().max(1.) * 100.;
+    vertices.extend([
+        center + DVec2::new(-size, -size),
+        center + DVec2::new(size, -size),
+        center + DVec2::new(0., size),
+    ]);
+
+    let mut triangles = vec![[n, n + 1, n + 2]];
+    for (i, p) in vertices.iter().take(n).enumerate() {
+        let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles
+            .into_iter()
+            .partition(|[a, b, c]| in_circumcircle(vertices[*a], vertices[*b], vertices[*c], *p));
+
+        // The new point is connected to the boundary of the hole left by the removed triangles,
+        // made of the edges that are not shared by two removed triangles
+        let edges = |[a, b, c]: [usize; 3]| [(a, b), (b, c), (c, a)];
+        let shared = |(a, b): (usize, usize)| {
+            bad.iter()
+                .flat_map(|t| edges(*t))
+                .filter(|(c, d)| (a, b) == (*c, *d) || (a, b) == (*d, *c))
+                .count()
+                > 1
+        };
+        triangles = good;
+        for (a, b) in bad.iter().flat_map(|t| edges(*t)) {
+            if !shared((a, b)) {
+                triangles.push([a, b, i]);
+            }
+        }
+    }
+
+    triangles
+        .into_iter()
+        .filter(|t| t.iter().all(|v| *v < n))
+        .filter(|[a, b, c]| {
+            let (a, b, c) = (points[*a], points[*b], points[*c]);
+            (b - a).perp_dot(c - a).abs() > f32::EPSILON
+        })
+        .map(|mut t| {
+            t.sort();
+            t
+        })
+        .collect()
+}
+
+/// Barycentric coordinates of `p` with respect to the triangle `a, b, c`.
+fn barycentric(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> Vec3 {
+    let area = (b - a).perp_dot(c - a);
+    let u = (b - p).perp_dot(c - p) / area;
+    let v = (c - p).perp_dot(a - p) / area;
+    Vec3::new(u, v, 1. - u - v)
+}
+
+/// Closest point to `p` in the segment `a, b`, as the interpolation factor from `a` to `b`.
+fn segment_factor(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    if ab.length_squared() <= f32::EPSILON {
+        0.
+    } else {
+        ((p - a).dot(ab) / ab.length_squared()).clamp(0., 1.)
+    }
+}
+
+/// Computes the blend weights of each sample for the given position, using the triangulation of
+/// the sample positions. Returns only the samples with a non-zero weight.
+fn sample_weights(
+    samples: &[BlendSample2D],
+    triangles: &[[usize; 3]],
+    p: Vec2,
+) -> Vec<(usize, f32)> {
+    match samples.len() {
+        0 => return vec![],
+        1 => return vec![(0, 1.)],
+        _ => {}
+    }
+
+    let point = |i: usize| samples[i].position;
+    for [i, j, k] in triangles.iter().copied() {
+        let weights = barycentric(p, point(i), point(j), point(k));
+        if weights.min_element() >= -1e-5 {
+            let weights = weights.max(Vec3::ZERO);
+            let weights = weights / (weights.x + weights.y + weights.z);
+            return [(i, weights.x), (j, weights.y), (k, weights.z)]
+                .into_iter()
+                .filter(|(_, w)| *w > 0.)
+                .collect();
+        }
+    }
+
+    // Outside of the triangulation (or no triangulation at all), clamp to the closest edge
+    let edges: Vec<(usize, usize)> = if triangles.is_empty() {
+        (0..samples.len())
+            .flat_map(|i| ((i + 1)..samples.len()).map(move |j| (i, j)))
+            .collect()
+    } else {
+        triangles
+            .iter()
+            .flat_map(|[i, j, k]| [(*i, *j), (*j, *k), (*i, *k)])
+            .collect()
+    };
+
+    let mut best = (f32::INFINITY, vec![]);
+    for (i, j) in edges {
+        let f = segment_factor(p, point(i), point(j));
+        let distance = p.distance(point(i).lerp(point(j), f));
+        if distance < best.0 {
+            best = (distance, vec![(i, 1. - f), (j, f)]);
+        }
+    }
+
+    best.1.into_iter().filter(|(_, w)| *w > 0.).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(points: &[Vec2]) -> Vec<BlendSample2D> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, position)| BlendSample2D {
+                pin: i.to_string(),
+                position: *position,
+            })
+            .collect()
+    }
+
+    fn weights_at(points: &[Vec2], p: Vec2) -> Vec<(usize, f32)> {
+        sample_weights(&samples(points), &triangulate(points), p)
+    }
+
+    fn grid() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0., 0.),
+            Vec2::new(1., 0.),
+            Vec2::new(0., 1.),
+            Vec2::new(-1., 0.),
+            Vec2::new(0., -1.),
+        ]
+    }
+
+    #[test]
+    fn sample_weights_inside() {
+        let weights = weights_at(&grid(), Vec2::new(0.25, 0.25));
+        let total: f32 = weights.iter().map(|(_, w)| w).sum();
+        assert!((total - 1.).abs() < 1e-5);
+
+        let weight = |i| {
+            weights
+                .iter()
+                .find(|(j, _)| *j == i)
+                .map_or(0., |(_, w)| *w)
+        };
+        assert!((weight(0) - 0.5).abs() < 1e-5);
+        assert!((weight(1) - 0.25).abs() < 1e-5);
+        assert!((weight(2) - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sample_weights_outside() {
+        // Clamped to the edge between (1, 0) and (0, 1)
+        let weights = weights_at(&grid(), Vec2::new(2., 2.));
+        assert_eq!(weights.len(), 2);
+        assert!(weights.iter().all(|(_, w)| (w - 0.5).abs() < 1e-5));
+
+        // Collinear samples behave like a 1D blend space
+        let line = [Vec2::new(0., 0.), Vec2::new(2., 0.)];
+        let weights = weights_at(&line, Vec2::new(0.5, 1.));
+        assert_eq!(weights, vec![(0, 0.75), (1, 0.25)]);
+    }
+
+    #[test]
+    fn triangulate_cocircular_samples() {
+        // All four corners of a square are on the same circle, but only two of the four
+        // candidate triangles may be used
+        let square = [
+            Vec2::new(0., 0.),
+            Vec2::new(1., 0.),
+            Vec2::new(1., 1.),
+            Vec2::new(0., 1.),
+        ];
+        let triangles = triangulate(&square);
+        assert_eq!(triangles.len(), 2);
+        let area: f32 = triangles
+            .iter()
+            .map(|[i, j, k]| {
+                let (a, b, c) = (square[*i], square[*j], square[*k]);
+                (b - a).perp_dot(c - a).abs() / 2.
+            })
+            .sum();
+        assert!((area - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn triangulation_follows_sample_edits() {
+        let mut node = BlendSpace2DNode::new(samples(&grid()), BlendSpacePlane::XY);
+        let triangulation =
+            |node: &BlendSpace2DNode| node.triangulation.get_or_update(&node.samples, |_| vec![]);
+        assert_eq!(triangulation(&node).len(), 4);
+
+        // Edited samples are triangulated again instead of using the stale triangulation
+        node.samples.truncate(3);
+        assert!(triangulation(&node).is_empty());
+    }
+}
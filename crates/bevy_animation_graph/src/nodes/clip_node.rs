@@ -1,15 +1,17 @@
-use crate::core::animation_clip::{GraphClip, Keyframes};
+use crate::core::animation_clip::{GraphClip, Interpolation, Keyframes, SyncMarker};
 use crate::core::animation_graph::TimeUpdate;
 use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
 use crate::core::errors::GraphError;
 use crate::core::frame::{
-    BoneFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
+    BoneFrame, FrameInterpolation, InnerPoseFrame, PoseEvent, PoseFrame, PoseFrameData, PoseSpec,
+    ValueFrame,
 };
 use crate::core::systems::get_keyframe;
 use crate::prelude::{PassContext, SpecContext};
 use bevy::asset::Handle;
 use bevy::reflect::prelude::*;
+use std::ops::Mul;
 
 #[derive(Reflect, Clone, Debug, Default)]
 #[reflect(Default)]
@@ -64,8 +66,8 @@ impl NodeLike for ClipNode {
         let time = time_update.apply(prev_time);
 
         let mut inner_frame = InnerPoseFrame::default();
-        for (path, bone_id) in &clip.paths {
-            let curves = clip.get_curves(*bone_id).unwrap();
+        for (bone_id, idx) in &clip.ids {
+            let curves = clip.get_curves(*idx).unwrap();
             let mut frame = BoneFrame::default();
             for curve in curves {
                 // Some curves have only one keyframe used to set a transform
@@ -96,14 +98,27 @@ impl NodeLike for ClipNode {
                     next_timestamp += clip_duration;
                 }
 
+                let interval = next_timestamp - prev_timestamp;
+
                 // Apply the keyframe
                 match &curve.keyframes {
                     Keyframes::Rotation(keyframes) => {
-                        let prev = keyframes[step_start];
-                        let mut next = keyframes[step_end];
+                        let (prev, mut next, mut interpolation) = keyframe_pair(
+                            curve.interpolation,
+                            keyframes,
+                            step_start,
+                            step_end,
+                            interval,
+                        );
                         // Choose the smallest angle for the rotation
                         if next.dot(prev) < 0.0 {
                             next = -next;
+                            if let FrameInterpolation::CubicSpline {
+                                next_in_tangent, ..
+                            } = &mut interpolation
+                            {
+                                *next_in_tangent = -*next_in_tangent;
+                            }
                         }
 
                         frame.rotation = Some(ValueFrame {
@@ -113,11 +128,17 @@ impl NodeLike for ClipNode {
                             next_timestamp,
                             prev_is_wrapped,
                             next_is_wrapped,
+                            interpolation,
                         });
                     }
                     Keyframes::Translation(keyframes) => {
-                        let prev = keyframes[step_start];
-                        let next = keyframes[step_end];
+                        let (prev, next, interpolation) = keyframe_pair(
+                            curve.interpolation,
+                            keyframes,
+                            step_start,
+                            step_end,
+                            interval,
+                        );
 
                         frame.translation = Some(ValueFrame {
                             prev,
@@ -126,12 +147,18 @@ impl NodeLike for ClipNode {
                             next_timestamp,
                             prev_is_wrapped,
                             next_is_wrapped,
+                            interpolation,
                         });
                     }
 
                     Keyframes::Scale(keyframes) => {
-                        let prev = keyframes[step_start];
-                        let next = keyframes[step_end];
+                        let (prev, next, interpolation) = keyframe_pair(
+                            curve.interpolation,
+                            keyframes,
+                            step_start,
+                            step_end,
+                            interval,
+                        );
                         frame.scale = Some(ValueFrame {
                             prev,
                             prev_timestamp,
@@ -139,35 +166,85 @@ impl NodeLike for ClipNode {
                             next_timestamp,
                             prev_is_wrapped,
                             next_is_wrapped,
+                            interpolation,
                         });
                     }
 
                     Keyframes::Weights(keyframes) => {
-                        let target_count = keyframes.len() / keyframe_count;
-                        let morph_start = get_keyframe(target_count, keyframes, step_start);
-                        let morph_end = get_keyframe(target_count, keyframes, step_end);
+                        let (prev, next, interpolation) = match curve.interpolation {
+                            Interpolation::Linear | Interpolation::Step => {
+                                let target_count = keyframes.len() / keyframe_count;
+                                let morph_start = get_keyframe(target_count, keyframes, step_start);
+                                let morph_end = get_keyframe(target_count, keyframes, step_end);
+                                let interpolation = if curve.interpolation == Interpolation::Step {
+                                    FrameInterpolation::Step
+                                } else {
+                                    FrameInterpolation::Linear
+                                };
+                                (morph_start.into(), morph_end.into(), interpolation)
+                            }
+                            Interpolation::CubicSpline => {
+                                let target_count = keyframes.len() / keyframe_count / 3;
+                                let scaled = |idx| -> Vec<f32> {
+                                    get_keyframe(target_count, keyframes, idx)
+                                        .iter()
+                                        .map(|w| w * interval)
+                                        .collect()
+                                };
+                                (
+                                    get_keyframe(target_count, keyframes, 3 * step_start + 1)
+                                        .into(),
+                                    get_keyframe(target_count, keyframes, 3 * step_end + 1).into(),
+                                    FrameInterpolation::CubicSpline {
+                                        prev_out_tangent: scaled(3 * step_start + 2),
+                                        next_in_tangent: scaled(3 * step_end),
+                                    },
+                                )
+                            }
+                        };
                         frame.weights = Some(ValueFrame {
-                            prev: morph_start.into(),
+                            prev,
                             prev_timestamp,
-                            next: morph_end.into(),
+                            next,
                             next_timestamp,
                             prev_is_wrapped,
                             next_is_wrapped,
+                            interpolation,
                         });
                     }
                 }
             }
-            inner_frame.add_bone(frame, path.clone());
+            inner_frame.add_bone(frame, *bone_id);
         }
 
+        let node_id = ctx.node_context.unwrap().node_id;
+        let events = clip
+            .events
+            .iter()
+            .map(|event| PoseEvent {
+                timestamp: event.time,
+                name: event.name.clone(),
+                node_path: vec![node_id.clone()],
+            })
+            .collect();
+
         let pose_frame = PoseFrame {
             data: PoseFrameData::BoneSpace(inner_frame.into()),
             timestamp: time,
+            events,
         };
 
         Ok(Some(pose_frame))
     }
 
+    fn sync_markers_pass(&self, ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        Ok(ctx
+            .resources
+            .graph_clip_assets
+            .get(&self.clip)
+            .map_or_else(Vec::new, |clip| clip.sync_markers().to_vec()))
+    }
+
     fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
         Some(PoseSpec::BoneSpace)
     }
@@ -176,3 +253,37 @@ impl NodeLike for ClipNode {
         "⏵ Animation Clip".into()
     }
 }
+
+/// Returns the values at the given keyframes and the interpolation between them. Cubic spline
+/// curves store an in-tangent, a value and an out-tangent for each keyframe.
+fn keyframe_pair<T>(
+    interpolation: Interpolation,
+    keyframes: &[T],
+    step_start: usize,
+    step_end: usize,
+    interval: f32,
+) -> (T, T, FrameInterpolation<T>)
+where
+    T: Copy + Mul<f32, Output = T> + FromReflect + TypePath,
+{
+    match interpolation {
+        Interpolation::Linear => (
+            keyframes[step_start],
+            keyframes[step_end],
+            FrameInterpolation::Linear,
+        ),
+        Interpolation::Step => (
+            keyframes[step_start],
+            keyframes[step_end],
+            FrameInterpolation::Step,
+        ),
+        Interpolation::CubicSpline => (
+            keyframes[3 * step_start + 1],
+            keyframes[3 * step_end + 1],
+            FrameInterpolation::CubicSpline {
+                prev_out_tangent: keyframes[3 * step_start + 2] * interval,
+                next_in_tangent: keyframes[3 * step_end] * interval,
+            },
+        ),
+    }
+}
@@ -0,0 +1,94 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::{
+    cache::Cached,
+    expression::{Expression, ExpressionError},
+};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::sync::Arc;
+
+/// Evaluates a formula over `f32` parameters, e.g. `clamp((speed - start) / (end - start), 0, 1)`.
+/// There is one input pin for every variable used in the formula. See
+/// [`expression`](crate::utils::expression) for the supported syntax.
+#[derive(Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct ExpressionNode {
+    pub expression: String,
+    /// Parsed version of `expression`, reparsed on evaluation if out of date (e.g. after
+    /// editing `expression` through reflection)
+    #[reflect(ignore)]
+    compiled: Cached<String, Result<Expression, ExpressionError>>,
+}
+
+impl Default for ExpressionNode {
+    fn default() -> Self {
+        Self::new("0").unwrap()
+    }
+}
+
+impl ExpressionNode {
+    pub const OUTPUT: &'static str = "F32 Out";
+
+    /// Fails if the expression cannot be parsed.
+    pub fn new(expression: impl Into<String>) -> Result<Self, ExpressionError> {
+        let compiled = Expression::parse(expression)?;
+        Ok(Self {
+            expression: compiled.source().into(),
+            compiled: Cached::new(compiled.source().into(), Ok(compiled)),
+        })
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::Expression(self))
+    }
+
+    fn compiled(&self) -> Arc<Result<Expression, ExpressionError>> {
+        self.compiled
+            .get_or_update(self.expression.as_str(), |source| Expression::parse(source))
+    }
+}
+
+impl NodeLike for ExpressionNode {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let compiled = self.compiled();
+        let expression = compiled
+            .as_ref()
+            .as_ref()
+            .map_err(|err| GraphError::InvalidExpression(err.to_string()))?;
+
+        let values = expression
+            .variables()
+            .iter()
+            .map(|variable| Ok(ctx.parameter_back(variable)?.unwrap_f32()))
+            .collect::<Result<Vec<_>, GraphError>>()?;
+        let value = expression.eval(&values);
+
+        Ok([(Self::OUTPUT.into(), ParamValue::F32(value))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        let compiled = self.compiled();
+        let Ok(expression) = compiled.as_ref() else {
+            return PinMap::default();
+        };
+        expression
+            .variables()
+            .iter()
+            .map(|variable| (variable.clone(), ParamSpec::F32.into()))
+            .collect()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::F32)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Expression".into()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::animation_clip::SyncMarker;
 use crate::core::animation_graph::{PinMap, TimeUpdate};
 use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
@@ -49,7 +50,7 @@ impl NodeLike for FlipLRNode {
 
         ctx.pose_bone_gizmos(Color::RED, bone_frame.inner_ref(), in_pose_frame.timestamp);
 
-        let flipped_pose_frame = bone_frame.flipped(&self.config);
+        let flipped_pose_frame = bone_frame.flipped(&self.config, ctx.skeleton);
 
         ctx.pose_bone_gizmos(
             Color::BLUE,
@@ -60,9 +61,14 @@ impl NodeLike for FlipLRNode {
         Ok(Some(PoseFrame {
             data: PoseFrameData::BoneSpace(flipped_pose_frame),
             timestamp: in_pose_frame.timestamp,
+            events: in_pose_frame.events,
         }))
     }
 
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
     fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
         [(Self::INPUT.into(), PoseSpec::BoneSpace)].into()
     }
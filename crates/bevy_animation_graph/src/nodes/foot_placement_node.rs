@@ -0,0 +1,217 @@
+use bevy::{
+    math::{Quat, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    transform::components::Transform,
+};
+
+use super::twoboneik_node::apply_two_bone_ik;
+use crate::{
+    core::{
+        animation_clip::{EntityPath, SyncMarker},
+        animation_graph::{PinMap, TimeUpdate},
+        animation_node::{AnimationNode, AnimationNodeType, NodeLike},
+        duration_data::DurationData,
+        errors::GraphError,
+        frame::{BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+        pose::BoneId,
+        space_conversion::SpaceConversion,
+    },
+    prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext},
+    utils::unwrap::Unwrap,
+};
+
+/// Adapts the pose to uneven ground: the pelvis is lowered so that both feet can reach the ground,
+/// each leg is solved with two-bone IK and the feet are rotated to match the ground normal.
+///
+/// Ground heights and normals are given per foot in character space, as measured (e.g. with a
+/// raycast) below each foot. The input animation is assumed to be authored on flat ground at
+/// height zero.
+#[derive(Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct FootPlacementNode {
+    /// Maximum vertical offset applied to the pelvis
+    pub max_pelvis_offset: f32,
+}
+
+impl Default for FootPlacementNode {
+    fn default() -> Self {
+        Self {
+            max_pelvis_offset: 0.5,
+        }
+    }
+}
+
+struct FootInput {
+    bone_id: BoneId,
+    ground_height: f32,
+    ground_normal: Vec3,
+    weight: f32,
+}
+
+impl FootPlacementNode {
+    pub const INPUT: &'static str = "Pose In";
+    pub const PELVIS: &'static str = "Pelvis Path";
+    pub const LEFT_FOOT: &'static str = "Left Foot Path";
+    pub const LEFT_HEIGHT: &'static str = "Left Ground Height";
+    pub const LEFT_NORMAL: &'static str = "Left Ground Normal";
+    pub const LEFT_WEIGHT: &'static str = "Left Weight";
+    pub const RIGHT_FOOT: &'static str = "Right Foot Path";
+    pub const RIGHT_HEIGHT: &'static str = "Right Ground Height";
+    pub const RIGHT_NORMAL: &'static str = "Right Ground Normal";
+    pub const RIGHT_WEIGHT: &'static str = "Right Weight";
+
+    const FEET: [[&'static str; 4]; 2] = [
+        [
+            Self::LEFT_FOOT,
+            Self::LEFT_HEIGHT,
+            Self::LEFT_NORMAL,
+            Self::LEFT_WEIGHT,
+        ],
+        [
+            Self::RIGHT_FOOT,
+            Self::RIGHT_HEIGHT,
+            Self::RIGHT_NORMAL,
+            Self::RIGHT_WEIGHT,
+        ],
+    ];
+
+    pub fn new(max_pelvis_offset: f32) -> Self {
+        Self { max_pelvis_offset }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::FootPlacement(self))
+    }
+
+    fn foot_input(ctx: &mut PassContext, pins: [&str; 4]) -> Result<FootInput, GraphError> {
+        let [path, height, normal, weight] = pins;
+        let path: EntityPath = ctx.parameter_back(path)?.unwrap();
+        Ok(FootInput {
+            bone_id: path.id(),
+            ground_height: ctx.parameter_back(height)?.unwrap(),
+            ground_normal: ctx.parameter_back(normal)?.unwrap(),
+            weight: ctx.parameter_back(weight)?.unwrap(),
+        })
+    }
+}
+
+impl NodeLike for FootPlacementNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::INPUT)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let pelvis: EntityPath = ctx.parameter_back(Self::PELVIS)?.unwrap();
+        let pelvis = pelvis.id();
+        let feet = Self::FEET
+            .into_iter()
+            .map(|pins| Self::foot_input(&mut ctx, pins))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pose = ctx.pose_back(Self::INPUT, input)?;
+        let time = pose.timestamp;
+        let mut bone_pose_data: BonePoseFrame = pose.data.unwrap();
+        let inner_pose_data = bone_pose_data.inner_mut();
+
+        // Animated foot transforms, before any adjustment
+        let foot_transforms: Vec<Transform> = feet
+            .iter()
+            .map(|foot| ctx.character_transform_of_bone(inner_pose_data, foot.bone_id, time))
+            .collect();
+        let offsets: Vec<f32> = feet
+            .iter()
+            .map(|foot| foot.ground_height * foot.weight.clamp(0., 1.))
+            .collect();
+
+        // Lower (or raise) the pelvis so that the lowest foot can still reach the ground
+        let pelvis_offset = offsets
+            .iter()
+            .copied()
+            .reduce(f32::min)
+            .unwrap_or(0.)
+            .clamp(-self.max_pelvis_offset, self.max_pelvis_offset);
+        if pelvis_offset != 0. {
+            let pelvis_char = ctx.character_transform_of_bone(inner_pose_data, pelvis, time);
+            let translation = pelvis_char.translation + Vec3::Y * pelvis_offset;
+            let translation = match ctx.skeleton.parent(pelvis) {
+                Some(parent) => {
+                    ctx.root_to_bone_space(
+                        Transform::from_translation(translation),
+                        inner_pose_data,
+                        parent,
+                        time,
+                    )
+                    .translation
+                }
+                None => translation,
+            };
+            inner_pose_data.set_bone_translation(pelvis, translation, time);
+        }
+
+        for ((foot, foot_transform), offset) in feet.iter().zip(&foot_transforms).zip(&offsets) {
+            let target = foot_transform.translation + Vec3::Y * *offset;
+            apply_two_bone_ik(&mut ctx, inner_pose_data, foot.bone_id, target, time);
+
+            // Keep the animated orientation of the foot relative to the ground
+            let normal = foot.ground_normal.normalize_or_zero();
+            let alignment = if normal == Vec3::ZERO {
+                Quat::IDENTITY
+            } else {
+                Quat::IDENTITY.slerp(
+                    Quat::from_rotation_arc(Vec3::Y, normal),
+                    foot.weight.clamp(0., 1.),
+                )
+            };
+            let rotation = alignment * foot_transform.rotation;
+            let parent_rotation =
+                ctx.skeleton
+                    .parent(foot.bone_id)
+                    .map_or(Quat::IDENTITY, |parent| {
+                        ctx.character_transform_of_bone(inner_pose_data, parent, time)
+                            .rotation
+                    });
+            inner_pose_data.set_bone_rotation(
+                foot.bone_id,
+                (parent_rotation.inverse() * rotation).normalize(),
+                time,
+            );
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(bone_pose_data),
+            timestamp: time,
+            events: pose.events,
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        let mut spec = PinMap::new();
+        spec.insert(Self::PELVIS.into(), ParamSpec::EntityPath.into());
+        for [path, height, normal, weight] in Self::FEET {
+            spec.insert(path.into(), ParamSpec::EntityPath.into());
+            spec.insert(height.into(), ParamSpec::F32.into());
+            spec.insert(normal.into(), ParamSpec::Vec3.into());
+            spec.insert(weight.into(), ParamSpec::F32.into());
+        }
+        spec
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [(Self::INPUT.into(), PoseSpec::BoneSpace)].into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "🦶 Foot Placement".into()
+    }
+}
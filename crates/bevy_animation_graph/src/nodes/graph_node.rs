@@ -1,3 +1,4 @@
+use crate::core::animation_clip::SyncMarker;
 use crate::core::animation_graph::{
     AnimationGraph, InputOverlay, PinId, PinMap, TargetPin, TimeUpdate,
 };
@@ -9,6 +10,9 @@ use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecConte
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
+/// Evaluates a nested animation graph. Parameters and poses connected to the node's input pins are
+/// forwarded to the inputs of the nested graph when it requests them. Unconnected input parameters
+/// use the default values of the nested graph.
 #[derive(Reflect, Clone, Debug, Default)]
 #[reflect(Default)]
 pub struct GraphNode {
@@ -79,16 +83,33 @@ impl NodeLike for GraphNode {
 
         if graph.output_pose.is_some() {
             let target_pin = TargetPin::OutputPose;
-            Ok(Some(graph.get_pose(
-                input,
-                target_pin,
-                ctx.child(&input_overlay),
-            )?))
+            let mut pose = graph.get_pose(input, target_pin, ctx.child(&input_overlay))?;
+            let node_id = ctx.node_context.unwrap().node_id;
+            for event in pose.events.iter_mut() {
+                event.node_path.insert(0, node_id.clone());
+            }
+            Ok(Some(pose))
         } else {
             Ok(None)
         }
     }
 
+    fn sync_markers_pass(&self, ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        let graph = ctx
+            .resources
+            .animation_graph_assets
+            .get(&self.graph)
+            .unwrap();
+
+        let input_overlay = InputOverlay::default();
+
+        if graph.output_pose.is_some() {
+            graph.get_sync_markers(TargetPin::OutputPose, ctx.child(&input_overlay))
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
     fn parameter_input_spec(&self, ctx: SpecContext) -> PinMap<OptParamSpec> {
         let Some(graph) = ctx.graph_assets.get(&self.graph) else {
             return Default::default();
@@ -96,7 +117,7 @@ impl NodeLike for GraphNode {
         graph
             .default_parameters
             .iter()
-            .map(|(k, v)| (k.into(), v.into()))
+            .map(|(k, v)| (k.into(), OptParamSpec::from(v).with_optional(true)))
             .collect()
     }
 
@@ -125,3 +146,57 @@ impl NodeLike for GraphNode {
         "📈 Graph".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{animation_graph::SourcePin, errors::GraphError, testing::TestWorld};
+
+    #[test]
+    fn test_nested_graph_parameters() {
+        let mut world = TestWorld::new();
+
+        // Nested graph forwarding an input parameter to its output
+        let mut nested = |input: &str, default: Option<f32>| {
+            let mut graph = AnimationGraph::new();
+            if let Some(default) = default {
+                graph.set_default_parameter(input, ParamValue::F32(default));
+            }
+            graph.add_output_parameter("Out", ParamSpec::F32);
+            graph.add_edge(
+                SourcePin::InputParameter(input.into()),
+                TargetPin::OutputParameter("Out".into()),
+            );
+            world.add_graph(graph)
+        };
+        let with_default = nested("Speed", Some(1.));
+        let without_default = nested("Speed", None);
+
+        let mut parent = |nested: &Handle<AnimationGraph>, connect: bool| {
+            let mut graph = AnimationGraph::new();
+            graph.add_node(GraphNode::new(nested.clone()).wrapped("Nested"));
+            if connect {
+                graph.set_default_parameter("Parent Speed", ParamValue::F32(2.));
+                graph.add_input_parameter_edge("Parent Speed", "Nested", "Speed");
+            }
+            graph.add_output_parameter("Out", ParamSpec::F32);
+            graph.add_output_parameter_edge("Nested", "Out", "Out");
+            world.add_graph(graph)
+        };
+        let connected = parent(&with_default, true);
+        let unconnected = parent(&with_default, false);
+        let missing = parent(&without_default, false);
+
+        let value =
+            |world: &mut TestWorld, graph| world.parameter(graph, "Out").map(|v| v.unwrap_f32());
+
+        // A connected parent parameter overrides the nested default
+        assert_eq!(value(&mut world, &connected).unwrap(), 2.);
+        assert_eq!(value(&mut world, &unconnected).unwrap(), 1.);
+        // Unconnected parameters without a default cannot be evaluated
+        assert!(matches!(
+            value(&mut world, &missing),
+            Err(GraphError::MissingInputParameter(pin)) if pin == "Speed"
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::{
+    math::{Quat, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::color::Color,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        animation_clip::{EntityPath, SyncMarker},
+        animation_graph::{PinMap, TimeUpdate},
+        animation_node::{AnimationNode, AnimationNodeType, NodeLike},
+        duration_data::DurationData,
+        errors::GraphError,
+        frame::{BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+        space_conversion::SpaceConversion,
+    },
+    prelude::{
+        BoneDebugGizmos, DeferredGizmoCommand, OptParamSpec, ParamSpec, PassContext, SpecContext,
+    },
+    utils::unwrap::Unwrap,
+};
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum IkSolver {
+    /// Forward And Backward Reaching Inverse Kinematics
+    #[default]
+    Fabrik,
+    /// Cyclic Coordinate Descent
+    Ccd,
+}
+
+/// Rotates a chain of bones ending at the target bone so that the target bone reaches the target
+/// position (given in character space).
+#[derive(Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct IkChainNode {
+    pub solver: IkSolver,
+    /// Number of bones rotated by the solver, i.e. number of ancestors of the target bone in the
+    /// chain
+    pub chain_length: usize,
+    pub max_iterations: usize,
+    /// Distance to the target below which the solver stops iterating
+    pub tolerance: f32,
+}
+
+impl Default for IkChainNode {
+    fn default() -> Self {
+        Self {
+            solver: IkSolver::Fabrik,
+            chain_length: 2,
+            max_iterations: 10,
+            tolerance: 0.001,
+        }
+    }
+}
+
+impl IkChainNode {
+    pub const INPUT: &'static str = "Pose In";
+    pub const TARGETBONE: &'static str = "Target Path";
+    pub const TARGETPOS: &'static str = "Target Position";
+
+    pub fn new(
+        solver: IkSolver,
+        chain_length: usize,
+        max_iterations: usize,
+        tolerance: f32,
+    ) -> Self {
+        Self {
+            solver,
+            chain_length,
+            max_iterations,
+            tolerance,
+        }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::IkChain(self))
+    }
+
+    /// Moves the joint positions so that the last one reaches the target, keeping the distances
+    /// between consecutive joints.
+    fn solve(&self, joints: &mut [Vec3], target: Vec3) {
+        match self.solver {
+            IkSolver::Fabrik => fabrik(joints, target, self.max_iterations, self.tolerance),
+            IkSolver::Ccd => ccd(joints, target, self.max_iterations, self.tolerance),
+        }
+    }
+}
+
+fn fabrik(joints: &mut [Vec3], target: Vec3, max_iterations: usize, tolerance: f32) {
+    let n = joints.len();
+    let lengths: Vec<f32> = joints.windows(2).map(|w| w[0].distance(w[1])).collect();
+    let root = joints[0];
+
+    // Unreachable target: stretch the chain towards it
+    if root.distance(target) >= lengths.iter().sum() {
+        let direction = (target - root).normalize_or_zero();
+        for i in 1..n {
+            joints[i] = joints[i - 1] + direction * lengths[i - 1];
+        }
+        return;
+    }
+
+    for _ in 0..max_iterations {
+        if joints[n - 1].distance(target) <= tolerance {
+            break;
+        }
+
+        // Backward pass, from the end effector
+        joints[n - 1] = target;
+        for i in (0..n - 1).rev() {
+            let direction = (joints[i] - joints[i + 1]).normalize_or_zero();
+            joints[i] = joints[i + 1] + direction * lengths[i];
+        }
+
+        // Forward pass, from the root
+        joints[0] = root;
+        for i in 1..n {
+            let direction = (joints[i] - joints[i - 1]).normalize_or_zero();
+            joints[i] = joints[i - 1] + direction * lengths[i - 1];
+        }
+    }
+}
+
+fn ccd(joints: &mut [Vec3], target: Vec3, max_iterations: usize, tolerance: f32) {
+    let n = joints.len();
+
+    for _ in 0..max_iterations {
+        if joints[n - 1].distance(target) <= tolerance {
+            break;
+        }
+
+        for i in (0..n - 1).rev() {
+            let to_end = (joints[n - 1] - joints[i]).normalize_or_zero();
+            let to_target = (target - joints[i]).normalize_or_zero();
+            if to_end == Vec3::ZERO || to_target == Vec3::ZERO {
+                continue;
+            }
+            let rotation = Quat::from_rotation_arc(to_end, to_target);
+            let pivot = joints[i];
+            for joint in joints[(i + 1)..].iter_mut() {
+                *joint = pivot + rotation * (*joint - pivot);
+            }
+        }
+    }
+}
+
+impl NodeLike for IkChainNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::INPUT)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let target: EntityPath = ctx.parameter_back(Self::TARGETBONE)?.unwrap();
+        let target_pos_char: Vec3 = ctx.parameter_back(Self::TARGETPOS)?.unwrap();
+        let pose = ctx.pose_back(Self::INPUT, input)?;
+        let time = pose.timestamp;
+        let mut bone_pose_data: BonePoseFrame = pose.data.unwrap();
+        let inner_pose_data = bone_pose_data.inner_mut();
+
+        // Joints from the chain root to the target bone
+        let mut chain = vec![target.id()];
+        while chain.len() <= self.chain_length {
+            let Some(parent) = ctx.skeleton.parent(chain[0]) else {
+                break;
+            };
+            chain.insert(0, parent);
+        }
+
+        if chain.len() > 1 {
+            for bone_id in chain.iter().skip(1) {
+                ctx.bone_gizmo(*bone_id, Color::RED, Some((inner_pose_data, time)));
+            }
+
+            let transforms: Vec<_> = chain
+                .iter()
+                .map(|bone_id| ctx.character_transform_of_bone(inner_pose_data, *bone_id, time))
+                .collect();
+            let mut joints: Vec<Vec3> = transforms.iter().map(|t| t.translation).collect();
+            self.solve(&mut joints, target_pos_char);
+
+            // Rotate each bone so that it points to the new position of its child
+            let mut parent_rotation =
+                ctx.skeleton
+                    .parent(chain[0])
+                    .map_or(Quat::IDENTITY, |parent| {
+                        ctx.character_transform_of_bone(inner_pose_data, parent, time)
+                            .rotation
+                    });
+            for i in 0..(chain.len() - 1) {
+                let old_direction = transforms[i + 1].translation - transforms[i].translation;
+                let new_direction = joints[i + 1] - joints[i];
+                let swing = if old_direction.length_squared() > f32::EPSILON
+                    && new_direction.length_squared() > f32::EPSILON
+                {
+                    Quat::from_rotation_arc(old_direction.normalize(), new_direction.normalize())
+                } else {
+                    Quat::IDENTITY
+                };
+                let rotation = swing * transforms[i].rotation;
+                let local_rotation = (parent_rotation.inverse() * rotation).normalize();
+                inner_pose_data.set_bone_rotation(chain[i], local_rotation, time);
+                parent_rotation = rotation;
+            }
+
+            for bone_id in chain.iter().skip(1) {
+                ctx.bone_gizmo(*bone_id, Color::BLUE, Some((inner_pose_data, time)));
+            }
+            if ctx.will_draw() {
+                let root_transform = ctx
+                    .resources
+                    .transform_query
+                    .get(ctx.root_entity)
+                    .unwrap()
+                    .1
+                    .compute_transform();
+                ctx.gizmo(DeferredGizmoCommand::Sphere(
+                    root_transform * target_pos_char,
+                    Quat::IDENTITY,
+                    0.05,
+                    Color::GREEN,
+                ));
+            }
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(bone_pose_data),
+            timestamp: time,
+            events: pose.events,
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::TARGETBONE.into(), ParamSpec::EntityPath.into()),
+            (Self::TARGETPOS.into(), ParamSpec::Vec3.into()),
+        ]
+        .into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [(Self::INPUT.into(), PoseSpec::BoneSpace)].into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "IK Chain".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> Vec<Vec3> {
+        vec![Vec3::ZERO, Vec3::Y, Vec3::Y * 2., Vec3::Y * 3.]
+    }
+
+    fn assert_solved(joints: &[Vec3], target: Vec3) {
+        assert!(joints[3].distance(target) < 0.01);
+        for (w, original) in joints.windows(2).zip(chain().windows(2)) {
+            let length = w[0].distance(w[1]);
+            assert!((length - original[0].distance(original[1])).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn fabrik_reaches_target() {
+        let target = Vec3::new(1.5, 1.5, 0.);
+        let mut joints = chain();
+        fabrik(&mut joints, target, 50, 0.001);
+        assert_solved(&joints, target);
+        assert_eq!(joints[0], Vec3::ZERO);
+    }
+
+    #[test]
+    fn ccd_reaches_target() {
+        let target = Vec3::new(1.5, 1.5, 0.);
+        let mut joints = chain();
+        ccd(&mut joints, target, 50, 0.001);
+        assert_solved(&joints, target);
+        assert_eq!(joints[0], Vec3::ZERO);
+    }
+}
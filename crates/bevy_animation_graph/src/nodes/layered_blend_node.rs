@@ -0,0 +1,132 @@
+use crate::core::animation_clip::SyncMarker;
+use crate::core::animation_graph::{PinMap, TimeUpdate};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::duration_data::DurationData;
+use crate::core::errors::GraphError;
+use crate::core::frame::{BonePoseFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec};
+use crate::core::parameters::{BoneMask, ResolvedBoneMask};
+use crate::core::pose::BoneId;
+use crate::core::skeleton::Skeleton;
+use crate::interpolation::linear::InterpolateLinear;
+use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext};
+use crate::utils::{cache::Cached, unwrap::Unwrap};
+use bevy::prelude::*;
+
+/// Blends a layer pose on top of a base pose, with a per bone weight given by a bone mask. The
+/// mask weight is multiplied by the blend factor.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct LayeredBlendNode {
+    /// Whether bones not in the bone mask inherit the weight of their closest ancestor in it.
+    pub propagate_weights: bool,
+}
+
+impl LayeredBlendNode {
+    pub const BASE: &'static str = "Base Pose";
+    pub const LAYER: &'static str = "Layer Pose";
+    pub const BONE_MASK: &'static str = "Bone Mask";
+    pub const FACTOR: &'static str = "Factor";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(propagate_weights: bool) -> Self {
+        Self { propagate_weights }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::LayeredBlend(self))
+    }
+
+    fn bone_weight(&self, mask: &ResolvedBoneMask, bone_id: BoneId, skeleton: &Skeleton) -> f32 {
+        if self.propagate_weights {
+            mask.bone_weight_propagated(bone_id, skeleton)
+        } else {
+            mask.bone_weight(bone_id)
+        }
+    }
+}
+
+impl NodeLike for LayeredBlendNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::BASE)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let mask: BoneMask = ctx.parameter_back(Self::BONE_MASK)?.unwrap();
+        // Resolving the mask hashes every bone path, so it is only done when the mask changes
+        let mask = ctx
+            .node_state_mut::<Cached<BoneMask, ResolvedBoneMask>>()
+            .get_or_update(&mask, BoneMask::resolve);
+        let factor = ctx.parameter_back(Self::FACTOR)?.unwrap_f32();
+        let base_pose = ctx.pose_back(Self::BASE, input)?;
+        let layer_pose = ctx.pose_back(Self::LAYER, input)?;
+        let base: BonePoseFrame = base_pose.data.unwrap();
+        let layer: BonePoseFrame = layer_pose.data.unwrap();
+        let base = base.inner_ref();
+        let layer = layer.inner_ref();
+
+        let mut result = InnerPoseFrame::default();
+
+        for (bone_id, idx) in base.ids.iter() {
+            let base_bone = &base.bones[*idx];
+            let weight = self.bone_weight(&mask, *bone_id, ctx.skeleton) * factor;
+            match layer.ids.get(bone_id) {
+                Some(layer_idx) if weight > 0. => result.add_bone(
+                    base_bone.interpolate_linear(&layer.bones[*layer_idx], weight),
+                    *bone_id,
+                ),
+                _ => result.add_bone(base_bone.clone(), *bone_id),
+            }
+        }
+
+        for (bone_id, idx) in layer.ids.iter() {
+            if base.ids.contains_key(bone_id)
+                || self.bone_weight(&mask, *bone_id, ctx.skeleton) * factor <= 0.
+            {
+                continue;
+            }
+            result.add_bone(layer.bones[*idx].clone(), *bone_id);
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(result.into()),
+            timestamp: base_pose.timestamp,
+            events: base_pose
+                .events
+                .into_iter()
+                .chain(layer_pose.events)
+                .collect(),
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::BASE)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::BONE_MASK.into(), ParamSpec::BoneMask.into()),
+            (Self::FACTOR.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [
+            (Self::BASE.into(), PoseSpec::BoneSpace),
+            (Self::LAYER.into(), PoseSpec::BoneSpace),
+        ]
+        .into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "≣ Layered Blend".into()
+    }
+}
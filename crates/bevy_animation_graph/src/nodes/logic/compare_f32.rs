@@ -0,0 +1,60 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{Comparison, OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Compares two `F32` inputs, e.g. outputs `input_1 < input_2` for [`Comparison::Less`].
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct CompareF32 {
+    pub comparison: Comparison,
+}
+
+impl CompareF32 {
+    pub const INPUT_1: &'static str = "F32 In 1";
+    pub const INPUT_2: &'static str = "F32 In 2";
+    pub const OUTPUT: &'static str = "Bool Out";
+
+    pub fn new(comparison: Comparison) -> Self {
+        Self { comparison }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::CompareF32(self))
+    }
+}
+
+impl NodeLike for CompareF32 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input_1: f32 = ctx.parameter_back(Self::INPUT_1)?.unwrap();
+        let input_2: f32 = ctx.parameter_back(Self::INPUT_2)?.unwrap();
+
+        Ok([(
+            Self::OUTPUT.into(),
+            ParamValue::Bool(self.comparison.compare(input_1, input_2)),
+        )]
+        .into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::INPUT_1.into(), ParamSpec::F32.into()),
+            (Self::INPUT_2.into(), ParamSpec::F32.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Bool)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Compare F32".into()
+    }
+}
@@ -0,0 +1,17 @@
+mod and_bool;
+mod compare_f32;
+mod compare_i32;
+mod equal_enum;
+mod not_bool;
+mod or_bool;
+mod select_node;
+mod switch_node;
+
+pub use and_bool::*;
+pub use compare_f32::*;
+pub use compare_i32::*;
+pub use equal_enum::*;
+pub use not_bool::*;
+pub use or_bool::*;
+pub use select_node::*;
+pub use switch_node::*;
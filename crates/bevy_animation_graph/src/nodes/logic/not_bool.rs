@@ -0,0 +1,47 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct NotBool {}
+
+impl NotBool {
+    pub const INPUT: &'static str = "Bool In";
+    pub const OUTPUT: &'static str = "Bool Out";
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::NotBool(self))
+    }
+}
+
+impl NodeLike for NotBool {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let input: bool = ctx.parameter_back(Self::INPUT)?.unwrap();
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Bool(!input))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::Bool.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Bool)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Not".into()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Outputs one of two parameters of the given type depending on a `Bool` condition. Only the
+/// selected input is evaluated.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SelectNode {
+    pub spec: ParamSpec,
+}
+
+impl SelectNode {
+    pub const CONDITION: &'static str = "Condition";
+    pub const IF_TRUE: &'static str = "If True";
+    pub const IF_FALSE: &'static str = "If False";
+    pub const OUTPUT: &'static str = "Out";
+
+    pub fn new(spec: ParamSpec) -> Self {
+        Self { spec }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::Select(self))
+    }
+}
+
+impl NodeLike for SelectNode {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let condition: bool = ctx.parameter_back(Self::CONDITION)?.unwrap();
+        let value = if condition {
+            ctx.parameter_back(Self::IF_TRUE)?
+        } else {
+            ctx.parameter_back(Self::IF_FALSE)?
+        };
+
+        Ok([(Self::OUTPUT.into(), value)].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::CONDITION.into(), ParamSpec::Bool.into()),
+            (Self::IF_TRUE.into(), self.spec.into()),
+            (Self::IF_FALSE.into(), self.spec.into()),
+        ]
+        .into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), self.spec)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Select".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TestWorld;
+    use crate::prelude::AnimationGraph;
+
+    #[test]
+    fn test_only_selected_input_is_evaluated() {
+        let mut world = TestWorld::new();
+        let mut graph = |condition: bool| {
+            let mut graph = AnimationGraph::new();
+            graph.add_node(SelectNode::new(ParamSpec::F32).wrapped("Select"));
+            graph.set_default_parameter("Condition", ParamValue::Bool(condition));
+            graph.set_default_parameter("One", ParamValue::F32(1.));
+            graph.add_input_parameter_edge("Condition", "Select", SelectNode::CONDITION);
+            // The false input is left unconnected
+            graph.add_input_parameter_edge("One", "Select", SelectNode::IF_TRUE);
+            graph.add_output_parameter("Out", ParamSpec::F32);
+            graph.add_output_parameter_edge("Select", SelectNode::OUTPUT, "Out");
+            world.add_graph(graph)
+        };
+        let select_true = graph(true);
+        let select_false = graph(false);
+
+        assert_eq!(
+            world.parameter(&select_true, "Out").unwrap().unwrap_f32(),
+            1.
+        );
+        assert!(matches!(
+            world.parameter(&select_false, "Out"),
+            Err(GraphError::MissingInputEdge(_))
+        ));
+    }
+}
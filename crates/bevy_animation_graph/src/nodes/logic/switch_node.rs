@@ -0,0 +1,95 @@
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Outputs the parameter input named after the current variant of an `Enum` input, or the
+/// default input if the variant has no input of its own. Only the selected input is evaluated.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SwitchNode {
+    pub spec: ParamSpec,
+    /// Enum variants with their own input pin
+    pub variants: Vec<String>,
+}
+
+impl SwitchNode {
+    pub const SELECTOR: &'static str = "Selector";
+    pub const DEFAULT: &'static str = "Default";
+    pub const OUTPUT: &'static str = "Out";
+
+    pub fn new(spec: ParamSpec, variants: Vec<String>) -> Self {
+        Self { spec, variants }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::Switch(self))
+    }
+}
+
+impl NodeLike for SwitchNode {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let selector: String = ctx.parameter_back(Self::SELECTOR)?.unwrap();
+        let value = if self.variants.contains(&selector) {
+            ctx.parameter_back(selector)?
+        } else {
+            ctx.parameter_back(Self::DEFAULT)?
+        };
+
+        Ok([(Self::OUTPUT.into(), value)].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        let mut spec: PinMap<OptParamSpec> =
+            [(Self::SELECTOR.into(), ParamSpec::Enum.into())].into();
+        for variant in &self.variants {
+            spec.insert(variant.clone(), self.spec.into());
+        }
+        spec.insert(Self::DEFAULT.into(), self.spec.into());
+        spec
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), self.spec)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Switch".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::TestWorld;
+    use crate::prelude::AnimationGraph;
+
+    #[test]
+    fn test_unknown_variants_use_default_input() {
+        let mut world = TestWorld::new();
+        let mut graph = |selector: &str| {
+            let mut graph = AnimationGraph::new();
+            graph.add_node(SwitchNode::new(ParamSpec::F32, vec!["Walk".into()]).wrapped("Switch"));
+            graph.set_default_parameter("Selector", ParamValue::Enum(selector.into()));
+            graph.set_default_parameter("One", ParamValue::F32(1.));
+            graph.set_default_parameter("Two", ParamValue::F32(2.));
+            graph.add_input_parameter_edge("Selector", "Switch", SwitchNode::SELECTOR);
+            graph.add_input_parameter_edge("One", "Switch", "Walk");
+            graph.add_input_parameter_edge("Two", "Switch", SwitchNode::DEFAULT);
+            graph.add_output_parameter("Out", ParamSpec::F32);
+            graph.add_output_parameter_edge("Switch", SwitchNode::OUTPUT, "Out");
+            world.add_graph(graph)
+        };
+        let walk = graph("Walk");
+        let run = graph("Run");
+
+        assert_eq!(world.parameter(&walk, "Out").unwrap().unwrap_f32(), 1.);
+        assert_eq!(world.parameter(&run, "Out").unwrap().unwrap_f32(), 2.);
+    }
+}
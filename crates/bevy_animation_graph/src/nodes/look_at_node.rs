@@ -0,0 +1,304 @@
+use bevy::{
+    math::{Quat, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::color::Color,
+};
+
+use crate::{
+    core::{
+        animation_clip::{EntityPath, SyncMarker},
+        animation_graph::{PinMap, TimeUpdate},
+        animation_node::{AnimationNode, AnimationNodeType, NodeLike},
+        duration_data::DurationData,
+        errors::GraphError,
+        frame::{BonePoseFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+        space_conversion::SpaceConversion,
+    },
+    prelude::{BoneDebugGizmos, OptParamSpec, ParamSpec, PassContext, SpecContext},
+    utils::unwrap::Unwrap,
+};
+
+/// Rotates a bone so that one of its local axes points toward a target position (given in
+/// character space). The rotation can be distributed over the parents of the bone.
+#[derive(Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct LookAtNode {
+    /// Axis of the target bone, in its local space, that should point toward the target
+    pub axis: Vec3,
+    /// Weights of the bones in the chain, from the topmost ancestor to the target bone. Each
+    /// weight is the fraction of the remaining rotation applied by that bone, so a last weight of
+    /// `1.0` makes the target bone point exactly at the target. With no weights, the input pose
+    /// is left unchanged.
+    pub bone_weights: Vec<f32>,
+    /// Maximum angle (in radians) the axis can be rotated away from its direction in the input
+    /// pose
+    pub max_angle: f32,
+}
+
+impl Default for LookAtNode {
+    fn default() -> Self {
+        Self {
+            axis: Vec3::Z,
+            bone_weights: vec![1.0],
+            max_angle: std::f32::consts::PI,
+        }
+    }
+}
+
+impl LookAtNode {
+    pub const INPUT: &'static str = "Pose In";
+    pub const TARGETBONE: &'static str = "Target Path";
+    pub const TARGETPOS: &'static str = "Target Position";
+
+    pub fn new(axis: Vec3, bone_weights: Vec<f32>, max_angle: f32) -> Self {
+        Self {
+            axis,
+            bone_weights,
+            max_angle,
+        }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::LookAt(self))
+    }
+}
+
+/// Limits the rotation from `from` to `to` to the given angle, returning the resulting direction.
+fn limit_direction(from: Vec3, to: Vec3, max_angle: f32) -> Vec3 {
+    let angle = from.angle_between(to);
+    if angle <= max_angle {
+        to
+    } else {
+        Quat::IDENTITY.slerp(Quat::from_rotation_arc(from, to), max_angle / angle) * from
+    }
+}
+
+impl NodeLike for LookAtNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::INPUT)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let target: EntityPath = ctx.parameter_back(Self::TARGETBONE)?.unwrap();
+        let target = target.id();
+        let target_pos_char: Vec3 = ctx.parameter_back(Self::TARGETPOS)?.unwrap();
+        let pose = ctx.pose_back(Self::INPUT, input)?;
+        if self.bone_weights.is_empty() {
+            return Ok(Some(pose));
+        }
+        let time = pose.timestamp;
+        let mut bone_pose_data: BonePoseFrame = pose.data.unwrap();
+        let inner_pose_data = bone_pose_data.inner_mut();
+
+        // build bone chain
+        let mut chain = vec![target];
+        while chain.len() < self.bone_weights.len() {
+            let Some(parent) = ctx.skeleton.parent(chain[0]) else {
+                break;
+            };
+            chain.insert(0, parent);
+        }
+        // If the skeleton is shorter than the chain, the topmost weights are dropped
+        let weights = &self.bone_weights[self.bone_weights.len() - chain.len()..];
+
+        let aim_direction = |ctx: &PassContext, inner_pose_data: &InnerPoseFrame| {
+            let transform = ctx.character_transform_of_bone(inner_pose_data, target, time);
+            (
+                transform.rotation * self.axis.normalize_or_zero(),
+                (target_pos_char - transform.translation).normalize_or_zero(),
+            )
+        };
+
+        let (initial_direction, target_direction) = aim_direction(&ctx, inner_pose_data);
+        if initial_direction != Vec3::ZERO && target_direction != Vec3::ZERO {
+            ctx.bone_gizmo(target, Color::RED, Some((inner_pose_data, time)));
+
+            for (bone_id, weight) in chain.iter().zip(weights) {
+                // Rotating the parents moves the target bone, so the direction towards the target
+                // is recomputed from its updated position for every link of the chain
+                let (direction, target_direction) = aim_direction(&ctx, inner_pose_data);
+                if target_direction == Vec3::ZERO {
+                    break;
+                }
+                let goal_direction =
+                    limit_direction(initial_direction, target_direction, self.max_angle);
+                let delta = Quat::IDENTITY.slerp(
+                    Quat::from_rotation_arc(direction, goal_direction),
+                    weight.clamp(0., 1.),
+                );
+
+                let rotation = ctx
+                    .character_transform_of_bone(inner_pose_data, *bone_id, time)
+                    .rotation;
+                let parent_rotation =
+                    ctx.skeleton
+                        .parent(*bone_id)
+                        .map_or(Quat::IDENTITY, |parent| {
+                            ctx.character_transform_of_bone(inner_pose_data, parent, time)
+                                .rotation
+                        });
+                let local_rotation = (parent_rotation.inverse() * delta * rotation).normalize();
+                inner_pose_data.set_bone_rotation(*bone_id, local_rotation, time);
+            }
+
+            ctx.bone_gizmo(target, Color::BLUE, Some((inner_pose_data, time)));
+        }
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(bone_pose_data),
+            timestamp: time,
+            events: pose.events,
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [
+            (Self::TARGETBONE.into(), ParamSpec::EntityPath.into()),
+            (Self::TARGETPOS.into(), ParamSpec::Vec3.into()),
+        ]
+        .into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [(Self::INPUT.into(), PoseSpec::BoneSpace)].into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "👁 Look At".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parameters::ParamValue,
+        testing::{linear_clip, TestWorld, ROOT},
+    };
+    use crate::nodes::{ClipNode, SpeedNode};
+    use crate::prelude::AnimationGraph;
+    use bevy::{
+        core::Name,
+        hierarchy::BuildWorldChildren,
+        transform::components::{GlobalTransform, Transform},
+    };
+
+    /// Builds a graph that applies `node` to a linear clip, aiming the bone at `target` toward
+    /// `position`, and then plays the result at the given speed.
+    fn look_at_graph(
+        world: &mut TestWorld,
+        node: LookAtNode,
+        target: &str,
+        position: Vec3,
+        speed: f32,
+    ) -> AnimationGraph {
+        let clip = world.add_clip(linear_clip(1.));
+        let mut graph = AnimationGraph::new();
+        graph.add_node(ClipNode::new(clip, None).wrapped("Clip"));
+        graph.add_node(node.wrapped("LookAt"));
+        graph.add_node(SpeedNode::new().wrapped("Speed"));
+        graph.set_default_parameter(
+            "Target",
+            ParamValue::EntityPath(EntityPath::from_slashed_string(target.into())),
+        );
+        graph.set_default_parameter("Position", ParamValue::Vec3(position));
+        graph.set_default_parameter("Speed", ParamValue::F32(speed));
+        graph.add_input_parameter_edge("Target", "LookAt", LookAtNode::TARGETBONE);
+        graph.add_input_parameter_edge("Position", "LookAt", LookAtNode::TARGETPOS);
+        graph.add_input_parameter_edge("Speed", "Speed", SpeedNode::SPEED);
+        graph.add_node_pose_edge("Clip", "LookAt", LookAtNode::INPUT);
+        graph.add_node_pose_edge("LookAt", "Speed", SpeedNode::INPUT);
+        graph.add_output_pose_edge("Speed");
+        graph.add_output_pose(PoseSpec::BoneSpace);
+        graph
+    }
+
+    #[test]
+    fn test_limit_direction() {
+        assert_eq!(limit_direction(Vec3::Z, Vec3::X, 1.6), Vec3::X);
+        let limited = limit_direction(Vec3::Z, Vec3::X, std::f32::consts::FRAC_PI_4);
+        assert!((Vec3::Z.angle_between(limited) - std::f32::consts::FRAC_PI_4).abs() < 1e-4);
+        assert!(limited.x > 0. && limited.y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_empty_bone_weights_leave_pose_unchanged() {
+        let mut world = TestWorld::new();
+        let graph = look_at_graph(
+            &mut world,
+            LookAtNode::new(Vec3::Z, vec![], 1.),
+            ROOT,
+            Vec3::X,
+            1.,
+        );
+        let graph = world.add_graph(graph);
+
+        let (pose, _) = world.query(&graph, TimeUpdate::Absolute(0.5)).unwrap();
+        let root = &pose.bones[pose.ids[&EntityPath::from_slashed_string(ROOT.into()).id()]];
+        assert_eq!(root.translation, Some(Vec3::new(0.5, 0., 0.)));
+        assert_eq!(root.rotation, None);
+    }
+
+    #[test]
+    fn test_override_survives_slow_speed() {
+        let mut world = TestWorld::new();
+        let graph = look_at_graph(&mut world, LookAtNode::default(), ROOT, Vec3::X, 0.5);
+        let graph = world.add_graph(graph);
+
+        let (pose, _) = world.query(&graph, TimeUpdate::Absolute(0.5)).unwrap();
+        let root = &pose.bones[pose.ids[&EntityPath::from_slashed_string(ROOT.into()).id()]];
+        assert_eq!(root.translation, Some(Vec3::new(0.25, 0., 0.)));
+        let rotation = root.rotation.unwrap();
+        assert!(rotation.is_finite());
+        assert!((rotation * Vec3::Z).abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn test_chain_aims_from_moved_bone() {
+        let mut world = TestWorld::new();
+        let mut spawn_bone = |name: &str, translation: Vec3, parent| {
+            world
+                .world
+                .spawn((
+                    Name::new(name.to_string()),
+                    Transform::from_translation(translation),
+                    GlobalTransform::default(),
+                ))
+                .set_parent(parent)
+                .id()
+        };
+        let neck = spawn_bone("neck", Vec3::Y, world.root);
+        spawn_bone("head", Vec3::Z, neck);
+
+        let target = Vec3::new(2., 1., 1.);
+        let node = LookAtNode::new(Vec3::Z, vec![0.5, 1.], std::f32::consts::PI);
+        let graph = look_at_graph(&mut world, node, "root/neck/head", target, 1.);
+        let graph = world.add_graph(graph);
+
+        let (pose, _) = world.query(&graph, TimeUpdate::Absolute(0.)).unwrap();
+        let rotation = |path: &str| {
+            let id = EntityPath::from_slashed_string(path.into()).id();
+            pose.bones[pose.ids[&id]].rotation.unwrap()
+        };
+        let neck_rotation = rotation("root/neck");
+        let head_rotation = neck_rotation * rotation("root/neck/head");
+        let head_position = Vec3::Y + neck_rotation * Vec3::Z;
+
+        // The neck takes half of the rotation, so the head moves before aiming at the target
+        assert!(head_position.abs_diff_eq(Vec3::new(0.5f32.sqrt(), 1., 0.5f32.sqrt()), 1e-4));
+        let aim = head_rotation * Vec3::Z;
+        assert!(aim.abs_diff_eq((target - head_position).normalize(), 1e-4));
+    }
+}
@@ -1,8 +1,9 @@
+use crate::core::animation_clip::SyncMarker;
 use crate::core::animation_graph::{PinId, PinMap, TimeUpdate};
 use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
 use crate::core::errors::GraphError;
-use crate::core::frame::{PoseFrame, PoseSpec};
+use crate::core::frame::{PoseEvent, PoseFrame, PoseSpec};
 use crate::prelude::{ParamValue, PassContext, SpecContext};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
@@ -60,13 +61,36 @@ impl NodeLike for LoopNode {
         };
 
         let mut pose = ctx.pose_back(Self::INPUT, fw_upd)?;
-
         let t_extra = curr_time.div_euclid(duration) * duration;
+
+        // Include the events of the neighbouring loop iterations that fall within the time span
+        // of this frame, so that events are not missed when playback wraps around. Only events
+        // of the current iteration are copied, as the input may already contain wrapped events.
+        let span_start = prev_time.min(curr_time) - t_extra;
+        let span_end = prev_time.max(curr_time) - t_extra;
+        let wrapped_events = pose
+            .events
+            .iter()
+            .filter(|e| (0.0..=duration).contains(&e.timestamp))
+            .flat_map(|e| {
+                [-duration, duration].map(|offset| PoseEvent {
+                    timestamp: e.timestamp + offset,
+                    ..e.clone()
+                })
+            })
+            .filter(|e| (span_start..=span_end).contains(&e.timestamp))
+            .collect::<Vec<_>>();
+        pose.events.extend(wrapped_events);
+
         pose.map_ts(|t| t + t_extra);
 
         Ok(Some(pose))
     }
 
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
     fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
         [(Self::INPUT.into(), PoseSpec::Any)].into()
     }
@@ -79,3 +103,61 @@ impl NodeLike for LoopNode {
         "🔄 Loop".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parameters::BoneMask,
+        testing::{linear_clip, TestWorld},
+    };
+    use crate::nodes::{ClipNode, LayeredBlendNode};
+    use crate::prelude::AnimationGraph;
+
+    #[test]
+    fn test_loop_events_passed_once() {
+        let mut world = TestWorld::new();
+        let mut graph = AnimationGraph::new();
+
+        let base_clip = world.add_clip(linear_clip(1.));
+        let mut step_clip = linear_clip(1.);
+        step_clip.add_event(0.5, "step");
+        let step_clip = world.add_clip(step_clip);
+        graph.add_node(ClipNode::new(base_clip, None).wrapped("Base"));
+        graph.add_node(ClipNode::new(step_clip, None).wrapped("Step"));
+
+        // A looped clip layered on top of a clip, looped again
+        graph.add_node(LoopNode::new().wrapped("InnerLoop"));
+        graph.add_node_pose_edge("Step", "InnerLoop", LoopNode::INPUT);
+        graph.add_node(LayeredBlendNode::new(false).wrapped("Layer"));
+        graph.set_default_parameter("Mask", ParamValue::BoneMask(BoneMask::default()));
+        graph.set_default_parameter("Zero", ParamValue::F32(0.));
+        graph.add_input_parameter_edge("Mask", "Layer", LayeredBlendNode::BONE_MASK);
+        graph.add_input_parameter_edge("Zero", "Layer", LayeredBlendNode::FACTOR);
+        graph.add_node_pose_edge("Base", "Layer", LayeredBlendNode::BASE);
+        graph.add_node_pose_edge("InnerLoop", "Layer", LayeredBlendNode::LAYER);
+        graph.add_node(LoopNode::new().wrapped("OuterLoop"));
+        graph.add_node_pose_edge("Layer", "OuterLoop", LoopNode::INPUT);
+        graph.add_output_pose_edge("OuterLoop");
+        graph.add_output_pose(PoseSpec::BoneSpace);
+        let graph = world.add_graph(graph);
+
+        let (_, passed) = world.query(&graph, TimeUpdate::Absolute(0.)).unwrap();
+        assert!(passed.is_empty());
+
+        // Two loop iterations, wrapping around in between
+        let mut events = Vec::new();
+        for _ in 0..8 {
+            let (_, passed) = world.query(&graph, TimeUpdate::Delta(0.25)).unwrap();
+            events.extend(passed);
+        }
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.name == "step"));
+
+        // Jumping over an event does not pass it
+        let (_, passed) = world.query(&graph, TimeUpdate::Absolute(2.7)).unwrap();
+        assert!(passed.is_empty());
+        let (_, passed) = world.query(&graph, TimeUpdate::Delta(-0.3)).unwrap();
+        assert_eq!(passed.len(), 1);
+    }
+}
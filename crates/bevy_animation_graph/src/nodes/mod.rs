@@ -1,25 +1,49 @@
+pub mod additive;
 pub mod arithmetic;
 pub mod blend_node;
+pub mod blend_space_1d_node;
+pub mod blend_space_2d_node;
 pub mod chain_node;
 pub mod clip_node;
 pub mod dummy_node;
+pub mod expression_node;
 pub mod flip_lr_node;
+pub mod foot_placement_node;
 pub mod graph_node;
+pub mod ik_chain_node;
+pub mod layered_blend_node;
+pub mod logic;
+pub mod look_at_node;
 pub mod loop_node;
+pub mod root_motion_node;
 pub mod rotation_node;
+pub mod smoothing;
 pub mod space_conversion;
 pub mod speed_node;
+pub mod state_machine_node;
 pub mod twoboneik_node;
 
+pub use additive::*;
 pub use arithmetic::*;
 pub use blend_node::*;
+pub use blend_space_1d_node::*;
+pub use blend_space_2d_node::*;
 pub use chain_node::*;
 pub use clip_node::*;
 pub use dummy_node::*;
+pub use expression_node::*;
 pub use flip_lr_node::*;
+pub use foot_placement_node::*;
 pub use graph_node::*;
+pub use ik_chain_node::*;
+pub use layered_blend_node::*;
+pub use logic::*;
+pub use look_at_node::*;
 pub use loop_node::*;
+pub use root_motion_node::*;
 pub use rotation_node::*;
+pub use smoothing::*;
 pub use space_conversion::*;
 pub use speed_node::*;
+pub use state_machine_node::*;
 pub use twoboneik_node::*;
@@ -0,0 +1,182 @@
+use crate::core::animation_clip::{EntityPath, SyncMarker};
+use crate::core::animation_graph::{PinMap, TimeUpdate};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::duration_data::DurationData;
+use crate::core::errors::GraphError;
+use crate::core::frame::{BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec, ValueFrame};
+use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SampleLinearAt, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+/// Extracts the motion of the root bone from the input pose, so that it can be applied to the
+/// character by gameplay code or by the [`AnimationGraphPlayer`].
+///
+/// The extracted components are removed from the root bone. The motion is computed from the
+/// velocity of the root bone, so it works across loop boundaries as long as the animation is
+/// continuous. When it is placed below a blend, the extracted motion is scaled by the weight of
+/// its branch, so blending several root motion nodes blends their motions.
+///
+/// [`AnimationGraphPlayer`]: crate::core::animation_graph_player::AnimationGraphPlayer
+#[derive(Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct RootMotionNode {
+    /// Extract the horizontal (XZ) translation of the root bone
+    pub translation: bool,
+    /// Extract the rotation of the root bone around the vertical (Y) axis
+    pub yaw: bool,
+}
+
+impl Default for RootMotionNode {
+    fn default() -> Self {
+        Self {
+            translation: true,
+            yaw: false,
+        }
+    }
+}
+
+impl RootMotionNode {
+    pub const INPUT: &'static str = "Pose In";
+    pub const ROOT_BONE: &'static str = "Root Bone";
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(translation: bool, yaw: bool) -> Self {
+        Self { translation, yaw }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::RootMotion(self))
+    }
+}
+
+/// Rotation angle around the Y axis
+fn yaw(rotation: Quat) -> f32 {
+    2. * rotation.y.atan2(rotation.w)
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2. * PI) - PI
+}
+
+/// Change over the segment of the frame and the duration of the segment. Segments wrapping
+/// around the end of the animation have their timestamps shifted by its duration, so they are
+/// handled like any other.
+fn frame_velocity<T: FromReflect + TypePath>(
+    frame: &ValueFrame<T>,
+    diff: impl Fn(&T, &T) -> T,
+) -> Option<(T, f32)> {
+    let span = frame.next_timestamp - frame.prev_timestamp;
+    if span <= 0. {
+        None
+    } else {
+        Some((diff(&frame.next, &frame.prev), span))
+    }
+}
+
+impl NodeLike for RootMotionNode {
+    fn duration_pass(&self, mut ctx: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(ctx.duration_back(Self::INPUT)?))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let root_bone: EntityPath = ctx.parameter_back(Self::ROOT_BONE)?.unwrap();
+        let in_pose = ctx.pose_back(Self::INPUT, input)?;
+        let mut bone_pose: BonePoseFrame = in_pose.data.unwrap();
+        let dt = match input {
+            TimeUpdate::Delta(dt) => dt,
+            TimeUpdate::Absolute(_) => 0.,
+        };
+
+        let mut motion = Transform::IDENTITY;
+        let inner = bone_pose.inner_mut();
+        if let Some(idx) = inner.ids.get(&root_bone.id()) {
+            let bone = &mut inner.bones[*idx];
+            let current_yaw = bone
+                .rotation
+                .as_ref()
+                .map_or(0., |frame| yaw(frame.sample_linear_at(in_pose.timestamp)));
+
+            if self.yaw {
+                if let Some(frame) = bone.rotation.as_mut() {
+                    if let Some((delta, span)) = frame_velocity(frame, |next, prev| {
+                        Quat::from_rotation_y(wrap_angle(yaw(*next) - yaw(*prev)))
+                    }) {
+                        motion.rotation = Quat::from_rotation_y(yaw(delta) * dt / span);
+                    }
+                    frame.map_mut(|q| Quat::from_rotation_y(-yaw(*q)) * *q);
+                }
+            }
+
+            if self.translation {
+                if let Some(frame) = bone.translation.as_mut() {
+                    if let Some((delta, span)) = frame_velocity(frame, |next, prev| *next - *prev) {
+                        let delta = Vec3::new(delta.x, 0., delta.z) * dt / span;
+                        // Once the yaw is extracted, the pose faces forward, so the translation
+                        // is given relative to the current heading
+                        motion.translation = if self.yaw {
+                            Quat::from_rotation_y(-current_yaw) * delta
+                        } else {
+                            delta
+                        };
+                    }
+                    frame.map_mut(|t| Vec3::new(0., t.y, 0.));
+                }
+            }
+        }
+
+        ctx.add_root_motion(motion);
+
+        Ok(Some(PoseFrame {
+            data: PoseFrameData::BoneSpace(bone_pose),
+            timestamp: in_pose.timestamp,
+            events: in_pose.events,
+        }))
+    }
+
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        ctx.sync_markers_back(Self::INPUT)
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::ROOT_BONE.into(), ParamSpec::EntityPath.into())].into()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        [(Self::INPUT.into(), PoseSpec::BoneSpace)].into()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "⇶ Root Motion".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_segment_velocity() {
+        // Segment from the last keyframe of the previous loop iteration to the first keyframe
+        let frame = ValueFrame {
+            prev: Vec3::new(0.9, 0., 0.),
+            prev_timestamp: -0.1,
+            next: Vec3::new(1., 0., 0.),
+            next_timestamp: 0.,
+            prev_is_wrapped: true,
+            next_is_wrapped: false,
+            interpolation: default(),
+        };
+
+        let (delta, span) = frame_velocity(&frame, |next, prev| *next - *prev).unwrap();
+        assert!((delta / span).abs_diff_eq(Vec3::X, 1e-5));
+    }
+}
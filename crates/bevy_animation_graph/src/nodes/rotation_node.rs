@@ -4,7 +4,7 @@ use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
 use crate::core::errors::GraphError;
 use crate::core::frame::{
-    BoneFrame, BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
+    BoneFrame, BonePoseFrame, FrameInterpolation, PoseFrame, PoseFrameData, PoseSpec, ValueFrame,
 };
 use crate::core::space_conversion::SpaceConversion;
 use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SampleLinearAt, SpecContext};
@@ -95,25 +95,27 @@ impl NodeLike for RotationNode {
         input: TimeUpdate,
         mut ctx: PassContext,
     ) -> Result<Option<PoseFrame>, GraphError> {
-        let mut target: EntityPath = ctx.parameter_back(Self::TARGET)?.unwrap();
+        let target: EntityPath = ctx.parameter_back(Self::TARGET)?.unwrap();
+        let mut target = target.id();
         let rotation: Quat = ctx.parameter_back(Self::ROTATION)?.unwrap();
         let pose = ctx.pose_back(Self::INPUT, input)?;
         let time = pose.timestamp;
+        let events = pose.events;
         let mut pose: BonePoseFrame = pose.data.unwrap();
         let inner_pose = pose.inner_mut();
 
-        if !inner_pose.paths.contains_key(&target) {
-            inner_pose.add_bone(BoneFrame::default(), target.clone());
+        if !inner_pose.ids.contains_key(&target) {
+            inner_pose.add_bone(BoneFrame::default(), target);
         }
 
         // build bone chain
-        let mut chain = vec![target.clone()];
-        while let Some(parent) = target.parent() {
+        let mut chain = vec![target];
+        while let Some(parent) = ctx.skeleton.parent(target) {
             if chain.len() >= self.chain_length {
                 break;
             }
 
-            chain.insert(0, parent.clone());
+            chain.insert(0, parent);
             target = parent;
         }
 
@@ -122,7 +124,7 @@ impl NodeLike for RotationNode {
             let rotation_bone_space = match self.rotation_space {
                 RotationSpace::Local => rotation,
                 RotationSpace::Character => {
-                    if let Some(parent) = target.parent() {
+                    if let Some(parent) = ctx.skeleton.parent(target) {
                         ctx.root_to_bone_space(
                             Transform::from_rotation(rotation),
                             inner_pose,
@@ -135,7 +137,7 @@ impl NodeLike for RotationNode {
                     }
                 }
                 RotationSpace::Global => {
-                    if let Some(parent) = target.parent() {
+                    if let Some(parent) = ctx.skeleton.parent(target) {
                         ctx.global_to_bone_space(
                             Transform::from_rotation(rotation),
                             inner_pose,
@@ -151,9 +153,9 @@ impl NodeLike for RotationNode {
             };
 
             let mut bone_frame = inner_pose
-                .paths
+                .ids
                 .get(&target)
-                .and_then(|bone_id| inner_pose.bones.get_mut(*bone_id).cloned())
+                .and_then(|idx| inner_pose.bones.get_mut(*idx).cloned())
                 .unwrap_or_default();
 
             if let Some(mut rot_frame) = bone_frame.rotation {
@@ -175,6 +177,7 @@ impl NodeLike for RotationNode {
                     next_timestamp: time + 0.1,
                     prev_is_wrapped: false,
                     next_is_wrapped: false,
+                    interpolation: FrameInterpolation::Linear,
                 });
             }
 
@@ -184,6 +187,7 @@ impl NodeLike for RotationNode {
         Ok(Some(PoseFrame {
             data: PoseFrameData::BoneSpace(pose),
             timestamp: time,
+            events,
         }))
     }
 
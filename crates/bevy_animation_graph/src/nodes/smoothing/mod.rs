@@ -0,0 +1,236 @@
+mod smooth_f32;
+mod smooth_quat;
+mod smooth_vec3;
+
+pub use smooth_f32::*;
+pub use smooth_quat::*;
+pub use smooth_vec3::*;
+
+use crate::core::animation_graph::TimeUpdate;
+use bevy::{
+    math::{Quat, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::LN_2,
+    ops::{Add, Mul, Sub},
+};
+
+/// How a smoothing node follows its target value.
+#[derive(Reflect, Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[reflect(Default)]
+pub enum Smoothing {
+    /// Critically damped spring, which follows the target without overshooting and keeps the
+    /// velocity continuous. `halflife` is the time it takes to cover half the distance to a
+    /// still target.
+    Spring { halflife: f32 },
+    /// Covers a fixed fraction of the remaining distance every second, given by the time it takes
+    /// to cover half of it.
+    Exponential { halflife: f32 },
+    /// Moves towards the target at a constant rate, in units (or radians for rotations) per
+    /// second.
+    MaxRate { max_rate: f32 },
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self::Spring { halflife: 0.1 }
+    }
+}
+
+/// Current value of a smoothing node, kept as node state between frames. For rotations, the
+/// velocity is the angular velocity as a scaled axis.
+#[derive(Clone, Copy, Debug)]
+pub struct SmoothingState<T, V> {
+    pub value: T,
+    pub velocity: V,
+}
+
+impl Smoothing {
+    /// Advances the smoothing state towards `target`. Absolute time updates and a missing state
+    /// snap the value to the target.
+    fn step<T: Copy, V: Default>(
+        &self,
+        state: Option<SmoothingState<T, V>>,
+        target: T,
+        frame_update: TimeUpdate,
+        step: impl FnOnce(SmoothingState<T, V>, f32) -> SmoothingState<T, V>,
+    ) -> SmoothingState<T, V> {
+        match (state, frame_update) {
+            (Some(state), TimeUpdate::Delta(dt)) => step(state, dt),
+            _ => SmoothingState {
+                value: target,
+                velocity: V::default(),
+            },
+        }
+    }
+}
+
+/// Fraction of the remaining distance that exponential smoothing covers in `dt`
+fn exponential_alpha(halflife: f32, dt: f32) -> f32 {
+    1. - (-LN_2 * dt / halflife.max(f32::EPSILON)).exp()
+}
+
+/// Critically damped spring step, with `offset` being the difference between the current value
+/// and the target. Returns the new offset and velocity.
+fn spring_step<T>(offset: T, velocity: T, halflife: f32, dt: f32) -> (T, T)
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let y = 2. * LN_2 / halflife.max(f32::EPSILON);
+    let j1 = velocity + offset * y;
+    let eydt = (-y * dt).exp();
+    ((offset + j1 * dt) * eydt, (velocity - j1 * (y * dt)) * eydt)
+}
+
+fn smooth_linear<T>(
+    smoothing: &Smoothing,
+    state: SmoothingState<T, T>,
+    target: T,
+    dt: f32,
+    max_rate_step: impl FnOnce(T, f32) -> T,
+) -> SmoothingState<T, T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    match *smoothing {
+        Smoothing::Spring { halflife } => {
+            let (offset, velocity) =
+                spring_step(state.value - target, state.velocity, halflife, dt);
+            SmoothingState {
+                value: target + offset,
+                velocity,
+            }
+        }
+        Smoothing::Exponential { halflife } => SmoothingState {
+            value: state.value + (target - state.value) * exponential_alpha(halflife, dt),
+            velocity: T::default(),
+        },
+        Smoothing::MaxRate { max_rate } => SmoothingState {
+            value: state.value + max_rate_step(target - state.value, max_rate * dt),
+            velocity: T::default(),
+        },
+    }
+}
+
+pub(crate) fn step_f32(
+    smoothing: &Smoothing,
+    state: Option<SmoothingState<f32, f32>>,
+    target: f32,
+    frame_update: TimeUpdate,
+) -> SmoothingState<f32, f32> {
+    smoothing.step(state, target, frame_update, |state, dt| {
+        smooth_linear(smoothing, state, target, dt, |delta, max| {
+            delta.clamp(-max, max)
+        })
+    })
+}
+
+pub(crate) fn step_vec3(
+    smoothing: &Smoothing,
+    state: Option<SmoothingState<Vec3, Vec3>>,
+    target: Vec3,
+    frame_update: TimeUpdate,
+) -> SmoothingState<Vec3, Vec3> {
+    smoothing.step(state, target, frame_update, |state, dt| {
+        smooth_linear(smoothing, state, target, dt, Vec3::clamp_length_max)
+    })
+}
+
+pub(crate) fn step_quat(
+    smoothing: &Smoothing,
+    state: Option<SmoothingState<Quat, Vec3>>,
+    target: Quat,
+    frame_update: TimeUpdate,
+) -> SmoothingState<Quat, Vec3> {
+    smoothing.step(state, target, frame_update, |state, dt| {
+        // Take the shortest path to the target
+        let value = if state.value.dot(target) < 0. {
+            -state.value
+        } else {
+            state.value
+        };
+        match *smoothing {
+            Smoothing::Spring { halflife } => {
+                let offset = (value * target.inverse()).to_scaled_axis();
+                let (offset, velocity) = spring_step(offset, state.velocity, halflife, dt);
+                SmoothingState {
+                    value: (Quat::from_scaled_axis(offset) * target).normalize(),
+                    velocity,
+                }
+            }
+            Smoothing::Exponential { halflife } => SmoothingState {
+                value: value.slerp(target, exponential_alpha(halflife, dt)),
+                velocity: Vec3::ZERO,
+            },
+            Smoothing::MaxRate { max_rate } => {
+                let angle = value.angle_between(target);
+                let max_angle = max_rate * dt;
+                SmoothingState {
+                    value: if angle <= max_angle {
+                        target
+                    } else {
+                        value.slerp(target, max_angle / angle)
+                    },
+                    velocity: Vec3::ZERO,
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smoothing_reaches_target() {
+        // Exponential smoothing covers half the distance after one halflife
+        let exponential = Smoothing::Exponential { halflife: 0.5 };
+        let mut state = SmoothingState {
+            value: 0.,
+            velocity: 0.,
+        };
+        for _ in 0..10 {
+            state = step_f32(&exponential, Some(state), 1., TimeUpdate::Delta(0.05));
+        }
+        assert!((state.value - 0.5).abs() < 1e-4);
+
+        // Max rate limits the distance covered
+        let max_rate = Smoothing::MaxRate { max_rate: 2. };
+        let state = SmoothingState {
+            value: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+        };
+        let state = step_vec3(
+            &max_rate,
+            Some(state),
+            Vec3::X * 10.,
+            TimeUpdate::Delta(0.5),
+        );
+        assert!(state.value.abs_diff_eq(Vec3::X, 1e-5));
+
+        // Springs settle on the target
+        let spring = Smoothing::Spring { halflife: 0.1 };
+        let target = Quat::from_rotation_y(2.);
+        let mut state = SmoothingState {
+            value: Quat::IDENTITY,
+            velocity: Vec3::ZERO,
+        };
+        for _ in 0..200 {
+            state = step_quat(&spring, Some(state), target, TimeUpdate::Delta(1. / 60.));
+        }
+        assert!(state.value.angle_between(target) < 1e-3);
+        assert!(state.velocity.length() < 1e-3);
+
+        // Absolute time updates snap to the target
+        let state = SmoothingState {
+            value: 0.,
+            velocity: 1.,
+        };
+        let state = step_f32(&spring, Some(state), 3., TimeUpdate::Absolute(0.));
+        assert_eq!(state.value, 3.);
+        assert_eq!(state.velocity, 0.);
+    }
+}
@@ -0,0 +1,55 @@
+use super::{step_f32, Smoothing, SmoothingState};
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Smooths a `f32` parameter over time, following the input value with the chosen
+/// [`Smoothing`]. The smoothed value is kept separately for each graph instance.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SmoothF32 {
+    pub smoothing: Smoothing,
+}
+
+impl SmoothF32 {
+    pub const INPUT: &'static str = "F32 In";
+    pub const OUTPUT: &'static str = "F32 Out";
+
+    pub fn new(smoothing: Smoothing) -> Self {
+        Self { smoothing }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::SmoothF32(self))
+    }
+}
+
+impl NodeLike for SmoothF32 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let target = ctx.parameter_back(Self::INPUT)?.unwrap_f32();
+
+        let state = ctx.node_state::<SmoothingState<f32, f32>>().copied();
+        let state = step_f32(&self.smoothing, state, target, ctx.frame_update);
+        ctx.set_node_state(state);
+
+        Ok([(Self::OUTPUT.into(), ParamValue::F32(state.value))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::F32.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::F32)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Smooth F32".into()
+    }
+}
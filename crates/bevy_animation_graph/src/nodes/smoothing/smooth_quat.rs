@@ -0,0 +1,56 @@
+use super::{step_quat, Smoothing, SmoothingState};
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Smooths a `Quat` parameter over time, following the input value with the chosen
+/// [`Smoothing`]. The smoothed value is kept separately for each graph instance.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SmoothQuat {
+    pub smoothing: Smoothing,
+}
+
+impl SmoothQuat {
+    pub const INPUT: &'static str = "Quat In";
+    pub const OUTPUT: &'static str = "Quat Out";
+
+    pub fn new(smoothing: Smoothing) -> Self {
+        Self { smoothing }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::SmoothQuat(self))
+    }
+}
+
+impl NodeLike for SmoothQuat {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let target: Quat = ctx.parameter_back(Self::INPUT)?.unwrap();
+
+        let state = ctx.node_state::<SmoothingState<Quat, Vec3>>().copied();
+        let state = step_quat(&self.smoothing, state, target, ctx.frame_update);
+        ctx.set_node_state(state);
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Quat(state.value))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::Quat.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Quat)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Smooth Quat".into()
+    }
+}
@@ -0,0 +1,56 @@
+use super::{step_vec3, Smoothing, SmoothingState};
+use crate::core::animation_graph::{PinId, PinMap};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::errors::GraphError;
+use crate::prelude::{OptParamSpec, ParamSpec, ParamValue, PassContext, SpecContext};
+use crate::utils::unwrap::Unwrap;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Smooths a `Vec3` parameter over time, following the input value with the chosen
+/// [`Smoothing`]. The smoothed value is kept separately for each graph instance.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct SmoothVec3 {
+    pub smoothing: Smoothing,
+}
+
+impl SmoothVec3 {
+    pub const INPUT: &'static str = "Vec3 In";
+    pub const OUTPUT: &'static str = "Vec3 Out";
+
+    pub fn new(smoothing: Smoothing) -> Self {
+        Self { smoothing }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::SmoothVec3(self))
+    }
+}
+
+impl NodeLike for SmoothVec3 {
+    fn parameter_pass(
+        &self,
+        mut ctx: PassContext,
+    ) -> Result<HashMap<PinId, ParamValue>, GraphError> {
+        let target: Vec3 = ctx.parameter_back(Self::INPUT)?.unwrap();
+
+        let state = ctx.node_state::<SmoothingState<Vec3, Vec3>>().copied();
+        let state = step_vec3(&self.smoothing, state, target, ctx.frame_update);
+        ctx.set_node_state(state);
+
+        Ok([(Self::OUTPUT.into(), ParamValue::Vec3(state.value))].into())
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        [(Self::INPUT.into(), ParamSpec::Vec3.into())].into()
+    }
+
+    fn parameter_output_spec(&self, _: SpecContext) -> PinMap<ParamSpec> {
+        [(Self::OUTPUT.into(), ParamSpec::Vec3)].into()
+    }
+
+    fn display_name(&self) -> String {
+        "Smooth Vec3".into()
+    }
+}
@@ -40,10 +40,15 @@ impl NodeLike for IntoBoneSpaceNode {
         let in_pose = ctx.pose_back(Self::POSE_IN, time_update)?;
         Ok(Some(PoseFrame {
             timestamp: in_pose.timestamp,
+            events: in_pose.events.clone(),
             data: PoseFrameData::BoneSpace(match &in_pose.data {
                 PoseFrameData::BoneSpace(data) => data.clone(),
                 PoseFrameData::CharacterSpace(data) => ctx.character_to_bone(data),
                 PoseFrameData::GlobalSpace(data) => ctx.global_to_bone(data),
+                // Additive poses must be applied to a base pose before converting them
+                PoseFrameData::Additive(_) => {
+                    return Err(GraphError::UnexpectedPoseSpace(PoseSpec::Additive))
+                }
             }),
         }))
     }
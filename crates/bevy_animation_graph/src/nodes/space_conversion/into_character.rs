@@ -40,10 +40,15 @@ impl NodeLike for IntoCharacterSpaceNode {
         let in_pose = ctx.pose_back(Self::POSE_IN, time_update)?;
         Ok(Some(PoseFrame {
             timestamp: in_pose.timestamp,
+            events: in_pose.events.clone(),
             data: PoseFrameData::CharacterSpace(match &in_pose.data {
                 PoseFrameData::BoneSpace(data) => ctx.bone_to_character(data),
                 PoseFrameData::CharacterSpace(data) => data.clone(),
                 PoseFrameData::GlobalSpace(data) => ctx.global_to_character(data),
+                // Additive poses must be applied to a base pose before converting them
+                PoseFrameData::Additive(_) => {
+                    return Err(GraphError::UnexpectedPoseSpace(PoseSpec::Additive))
+                }
             }),
         }))
     }
@@ -1,3 +1,4 @@
+use crate::core::animation_clip::SyncMarker;
 use crate::core::animation_graph::{PinMap, TimeUpdate};
 use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
 use crate::core::duration_data::DurationData;
@@ -58,6 +59,19 @@ impl NodeLike for SpeedNode {
         Ok(Some(in_pose_frame))
     }
 
+    fn sync_markers_pass(&self, mut ctx: PassContext) -> Result<Vec<SyncMarker>, GraphError> {
+        let speed = ctx.parameter_back(Self::SPEED)?.unwrap_f32();
+
+        if speed == 0. {
+            return Ok(Vec::new());
+        }
+
+        let mut markers = ctx.sync_markers_back(Self::INPUT)?;
+        markers.iter_mut().for_each(|m| m.time /= speed.abs());
+
+        Ok(markers)
+    }
+
     fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
         [(Self::SPEED.into(), ParamSpec::F32.into())].into()
     }
@@ -0,0 +1,445 @@
+use crate::core::animation_graph::{
+    AnimationGraph, InputOverlay, NodeId, PinId, PinMap, TargetPin, TimeUpdate,
+};
+use crate::core::animation_node::{AnimationNode, AnimationNodeType, NodeLike};
+use crate::core::duration_data::DurationData;
+use crate::core::errors::GraphError;
+use crate::core::frame::{PoseFrame, PoseSpec};
+use crate::interpolation::linear::InterpolateLinear;
+use crate::prelude::{OptParamSpec, ParamSpec, PassContext, SpecContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub type StateId = String;
+
+/// Where the pose of a state comes from.
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub enum StateSource {
+    /// The state is a pose input pin of the state machine node, named after the state.
+    #[default]
+    PoseInput,
+    /// The state plays back a nested animation graph.
+    Graph(Handle<AnimationGraph>),
+}
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct AnimationState {
+    pub id: StateId,
+    pub source: StateSource,
+}
+
+#[derive(Reflect, Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    #[default]
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+/// Compares an `F32` parameter input of the state machine node against a constant.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize)]
+#[reflect(Default)]
+pub struct TransitionCondition {
+    pub parameter: PinId,
+    pub comparison: Comparison,
+    pub value: f32,
+}
+
+impl Comparison {
+    /// Returns whether `a` compares to `b` as given by `self`, e.g. `a < b` for `Less`.
+    pub fn compare<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match self {
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Greater => a > b,
+            Comparison::GreaterOrEqual => a >= b,
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+        }
+    }
+}
+
+impl TransitionCondition {
+    pub fn holds(&self, value: f32) -> bool {
+        self.comparison.compare(value, self.value)
+    }
+}
+
+/// How the pose changes from the source state to the target state of a transition.
+#[derive(Reflect, Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum TransitionBlend {
+    /// Both states are evaluated and blended linearly for the duration of the transition.
+    #[default]
+    Crossfade,
+    /// Only the target state is evaluated, and the animation player decays the offset from the
+    /// last pose of the source state. See [`Inertialization`].
+    ///
+    /// [`Inertialization`]: crate::interpolation::inertialization::Inertialization
+    Inertialization,
+}
+
+/// A transition between two states. It is taken as soon as all of its conditions hold (an empty
+/// list of conditions always holds), blending between both states over `duration` seconds.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize)]
+#[reflect(Default)]
+pub struct Transition {
+    pub source: StateId,
+    pub target: StateId,
+    #[serde(default)]
+    pub conditions: Vec<TransitionCondition>,
+    #[serde(default)]
+    pub duration: f32,
+    #[serde(default)]
+    pub blend: TransitionBlend,
+}
+
+/// Transition currently being crossfaded.
+#[derive(Reflect, Clone, Debug, Default)]
+pub struct ActiveTransition {
+    pub source: StateId,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+/// Runtime state of a state machine node, stored per graph instance as node state in the
+/// [`GraphContext`](crate::prelude::GraphContext).
+#[derive(Reflect, Clone, Debug, Default)]
+pub struct StateMachineState {
+    pub active_state: StateId,
+    pub transition: Option<ActiveTransition>,
+}
+
+#[derive(Reflect, Clone, Debug, Default)]
+#[reflect(Default)]
+pub struct StateMachineNode {
+    pub states: Vec<AnimationState>,
+    pub transitions: Vec<Transition>,
+    pub start_state: StateId,
+}
+
+impl StateMachineNode {
+    pub const OUTPUT: &'static str = "Pose Out";
+
+    pub fn new(
+        states: Vec<AnimationState>,
+        transitions: Vec<Transition>,
+        start_state: StateId,
+    ) -> Self {
+        Self {
+            states,
+            transitions,
+            start_state,
+        }
+    }
+
+    pub fn wrapped(self, name: impl Into<String>) -> AnimationNode {
+        AnimationNode::new_from_nodetype(name.into(), AnimationNodeType::StateMachine(self))
+    }
+
+    fn state(&self, state_id: &str) -> Option<&AnimationState> {
+        self.states.iter().find(|s| s.id == state_id)
+    }
+
+    fn transition_ready(
+        &self,
+        transition: &Transition,
+        ctx: &mut PassContext,
+    ) -> Result<bool, GraphError> {
+        for condition in &transition.conditions {
+            let value = ctx.parameter_back(&condition.parameter)?.unwrap_f32();
+            if !condition.holds(value) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluates the pose of a state and shifts its timeline so that it is sampled at `time`.
+    fn state_pose(
+        &self,
+        state: &AnimationState,
+        node_id: &NodeId,
+        time_update: TimeUpdate,
+        time: f32,
+        ctx: &mut PassContext,
+    ) -> Result<PoseFrame, GraphError> {
+        let mut pose = match &state.source {
+            StateSource::PoseInput => ctx.pose_back(&state.id, time_update)?,
+            StateSource::Graph(handle) => {
+                // Graphs that are still loading play an empty pose, like clips
+                let Some(graph) = ctx.resources.animation_graph_assets.get(handle) else {
+                    return Ok(PoseFrame {
+                        timestamp: time,
+                        ..default()
+                    });
+                };
+                let input_overlay = InputOverlay::default();
+                let key = format!("{}/{}", node_id, state.id);
+                let mut pose = graph.get_pose(
+                    time_update,
+                    TargetPin::OutputPose,
+                    ctx.child_with_key(&input_overlay, &key),
+                )?;
+                for event in pose.events.iter_mut() {
+                    event.node_path.insert(0, node_id.clone());
+                }
+                pose
+            }
+        };
+
+        let offset = time - pose.timestamp;
+        pose.map_ts(|t| t + offset);
+
+        Ok(pose)
+    }
+}
+
+impl NodeLike for StateMachineNode {
+    fn duration_pass(&self, _: PassContext) -> Result<Option<DurationData>, GraphError> {
+        Ok(Some(None))
+    }
+
+    fn pose_pass(
+        &self,
+        input: TimeUpdate,
+        mut ctx: PassContext,
+    ) -> Result<Option<PoseFrame>, GraphError> {
+        let node_id = ctx.node_context.unwrap().node_id.clone();
+        let prev_time = ctx.prev_time_fwd();
+        let time = input.apply(prev_time);
+
+        // Absolute time updates reset the state machine
+        let (mut fsm_state, dt) = match (input, ctx.node_state::<StateMachineState>()) {
+            (TimeUpdate::Delta(dt), Some(fsm_state)) => (fsm_state.clone(), dt),
+            _ => (
+                StateMachineState {
+                    active_state: self.start_state.clone(),
+                    transition: None,
+                },
+                0.,
+            ),
+        };
+
+        if let Some(transition) = fsm_state.transition.as_mut() {
+            transition.elapsed += dt;
+            if transition.elapsed >= transition.duration {
+                fsm_state.transition = None;
+            }
+        }
+
+        if self
+            .transitions
+            .iter()
+            .any(|t| t.blend == TransitionBlend::Inertialization)
+        {
+            ctx.context().enable_inertialization();
+        }
+
+        let mut entered_state = false;
+        if fsm_state.transition.is_none() {
+            for transition in self.transitions.iter().filter(|t| {
+                t.source == fsm_state.active_state && t.target != fsm_state.active_state
+            }) {
+                if self.transition_ready(transition, &mut ctx)? {
+                    let source =
+                        std::mem::replace(&mut fsm_state.active_state, transition.target.clone());
+                    if transition.duration > 0. {
+                        match transition.blend {
+                            TransitionBlend::Crossfade => {
+                                fsm_state.transition = Some(ActiveTransition {
+                                    source,
+                                    elapsed: 0.,
+                                    duration: transition.duration,
+                                });
+                            }
+                            TransitionBlend::Inertialization => {
+                                ctx.context().request_inertialization(transition.duration);
+                            }
+                        }
+                    }
+                    entered_state = true;
+                    break;
+                }
+            }
+        }
+
+        let Some(active_state) = self.state(&fsm_state.active_state) else {
+            ctx.set_node_state(fsm_state);
+            return Ok(Some(PoseFrame {
+                timestamp: time,
+                ..default()
+            }));
+        };
+
+        // Newly entered states always start playing from the beginning
+        let active_update = if entered_state {
+            TimeUpdate::Absolute(0.)
+        } else {
+            input
+        };
+        // Source state of the running crossfade, and the weight of the active state
+        let source = fsm_state.transition.as_ref().and_then(|transition| {
+            let source_state = self.state(&transition.source)?;
+            Some((
+                source_state,
+                (transition.elapsed / transition.duration).clamp(0., 1.),
+            ))
+        });
+        let active_weight = source.map_or(1., |(_, alpha)| alpha);
+        let mut pose = self.state_pose(
+            active_state,
+            &node_id,
+            active_update,
+            time,
+            &mut ctx.with_root_motion_weight(active_weight),
+        )?;
+
+        if let Some((source_state, alpha)) = source {
+            let source_pose = self.state_pose(
+                source_state,
+                &node_id,
+                input,
+                time,
+                &mut ctx.with_root_motion_weight(1. - alpha),
+            )?;
+            pose = source_pose.interpolate_linear(&pose, alpha);
+        }
+
+        ctx.set_node_state(fsm_state);
+
+        Ok(Some(pose))
+    }
+
+    fn parameter_input_spec(&self, _: SpecContext) -> PinMap<OptParamSpec> {
+        self.transitions
+            .iter()
+            .flat_map(|t| t.conditions.iter())
+            .map(|c| (c.parameter.clone(), ParamSpec::F32.into()))
+            .collect()
+    }
+
+    fn pose_input_spec(&self, _: SpecContext) -> PinMap<PoseSpec> {
+        self.states
+            .iter()
+            .filter(|s| matches!(s.source, StateSource::PoseInput))
+            .map(|s| (s.id.clone(), PoseSpec::BoneSpace))
+            .collect()
+    }
+
+    fn pose_output_spec(&self, _: SpecContext) -> Option<PoseSpec> {
+        Some(PoseSpec::BoneSpace)
+    }
+
+    fn display_name(&self) -> String {
+        "🔀 State Machine".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::animation_clip::EntityPath;
+    use crate::core::parameters::ParamValue;
+    use crate::core::testing::{linear_clip, TestWorld, ROOT};
+    use crate::nodes::{ClipNode, RootMotionNode};
+
+    #[test]
+    fn test_transition_into_unloaded_graph() {
+        let mut world = TestWorld::new();
+        let mut graph = AnimationGraph::new();
+
+        let clip = world.add_clip(linear_clip(1.));
+        graph.add_node(ClipNode::new(clip, None).wrapped("A"));
+        let states = vec![
+            AnimationState {
+                id: "a".into(),
+                source: StateSource::PoseInput,
+            },
+            AnimationState {
+                id: "b".into(),
+                // Not in the graph assets, as if it was still loading
+                source: StateSource::Graph(Handle::default()),
+            },
+        ];
+        let transition = Transition {
+            source: "a".into(),
+            target: "b".into(),
+            duration: 0.5,
+            ..Default::default()
+        };
+        graph.add_node(
+            StateMachineNode::new(states, vec![transition], "a".into()).wrapped("StateMachine"),
+        );
+        graph.add_node_pose_edge("A", "StateMachine", "a");
+        graph.add_output_pose_edge("StateMachine");
+        graph.add_output_pose(PoseSpec::BoneSpace);
+        let graph = world.add_graph(graph);
+
+        world.query(&graph, TimeUpdate::Absolute(0.)).unwrap();
+        for _ in 0..8 {
+            world.query(&graph, TimeUpdate::Delta(0.1)).unwrap();
+        }
+
+        let fsm_state = world
+            .context
+            .get_node_state::<StateMachineState>("StateMachine")
+            .unwrap();
+        assert_eq!(fsm_state.active_state, "b");
+        assert!(fsm_state.transition.is_none());
+    }
+
+    #[test]
+    fn test_crossfade_weights_root_motion() {
+        let mut world = TestWorld::new();
+        let mut graph = AnimationGraph::new();
+
+        let clip = world.add_clip(linear_clip(1.));
+        graph.set_default_parameter(
+            "Root",
+            ParamValue::EntityPath(EntityPath {
+                parts: vec![ROOT.into()],
+            }),
+        );
+        let mut states = Vec::new();
+        for state in ["a", "b"] {
+            let clip_node = format!("Clip {state}");
+            let motion_node = format!("Motion {state}");
+            graph.add_node(ClipNode::new(clip.clone(), None).wrapped(&clip_node));
+            graph.add_node(RootMotionNode::default().wrapped(&motion_node));
+            graph.add_input_parameter_edge("Root", &motion_node, RootMotionNode::ROOT_BONE);
+            graph.add_node_pose_edge(&clip_node, &motion_node, RootMotionNode::INPUT);
+            graph.add_node_pose_edge(&motion_node, "StateMachine", state);
+            states.push(AnimationState {
+                id: state.into(),
+                source: StateSource::PoseInput,
+            });
+        }
+        let transition = Transition {
+            source: "a".into(),
+            target: "b".into(),
+            duration: 1.,
+            ..Default::default()
+        };
+        graph.add_node(
+            StateMachineNode::new(states, vec![transition], "a".into()).wrapped("StateMachine"),
+        );
+        graph.add_output_pose_edge("StateMachine");
+        graph.add_output_pose(PoseSpec::BoneSpace);
+        let graph = world.add_graph(graph);
+
+        world.query(&graph, TimeUpdate::Absolute(0.)).unwrap();
+        world.context.take_root_motion();
+        world.query(&graph, TimeUpdate::Delta(0.1)).unwrap();
+
+        // Both states move at one unit per second, and so does the crossfade between them
+        let motion = world.context.take_root_motion().unwrap();
+        assert!(motion.translation.abs_diff_eq(Vec3::new(0.1, 0., 0.), 1e-5));
+    }
+}
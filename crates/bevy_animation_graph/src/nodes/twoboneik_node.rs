@@ -12,7 +12,8 @@ use crate::{
         animation_node::{AnimationNode, AnimationNodeType, NodeLike},
         duration_data::DurationData,
         errors::GraphError,
-        frame::{BonePoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+        frame::{BonePoseFrame, InnerPoseFrame, PoseFrame, PoseFrameData, PoseSpec},
+        pose::BoneId,
         space_conversion::SpaceConversion,
     },
     prelude::{BoneDebugGizmos, OptParamSpec, ParamSpec, PassContext, SampleLinearAt, SpecContext},
@@ -54,99 +55,18 @@ impl NodeLike for TwoBoneIKNode {
         let mut bone_pose_data: BonePoseFrame = pose.data.unwrap();
         let inner_pose_data = bone_pose_data.inner_mut();
 
-        if let (Some(bone_id), Some(parent_path), Some(grandparent_path)) = (
-            inner_pose_data.paths.get(&target),
-            target.parent(),
-            target.parent().and_then(|p| p.parent()),
-        ) {
-            // Debug render (if enabled)
-            ctx.bone_gizmo(
-                target.clone(),
-                Color::RED,
-                Some((&inner_pose_data, pose.timestamp)),
-            );
-            ctx.bone_gizmo(
-                parent_path.clone(),
-                Color::RED,
-                Some((&inner_pose_data, pose.timestamp)),
-            );
-
-            let bone = inner_pose_data.bones[*bone_id].clone();
-            let target_gp = ctx.root_to_bone_space(
-                Transform::from_translation(target_pos_char),
-                inner_pose_data,
-                grandparent_path.parent().unwrap().clone(),
-                pose.timestamp,
-            );
-
-            let target_pos_gp = target_gp.translation;
-
-            let parent_id = inner_pose_data.paths.get(&parent_path).unwrap();
-            let parent_frame = {
-                let parent_bone = inner_pose_data.bones.get_mut(*parent_id).unwrap();
-                parent_bone.to_transform_frame_linear()
-            };
-            let parent_transform = parent_frame.sample_linear_at(pose.timestamp);
-
-            let grandparent_id = inner_pose_data.paths.get(&grandparent_path).unwrap();
-            let grandparent_bone = inner_pose_data.bones.get_mut(*grandparent_id).unwrap();
-            let grandparent_frame = grandparent_bone.to_transform_frame_linear();
-            let grandparent_transform = grandparent_frame.sample_linear_at(pose.timestamp);
-
-            let bone_frame = bone.to_transform_frame_linear();
-            let bone_transform = bone_frame.sample_linear_at(pose.timestamp);
-
-            let parent_gp_transform = grandparent_transform * parent_transform;
-            let bone_gp_transform = parent_gp_transform * bone_transform;
-
-            let (bone_gp_transform, parent_gp_transform, grandparent_transform) = two_bone_ik(
-                bone_gp_transform,
-                parent_gp_transform,
-                grandparent_transform,
-                target_pos_gp,
-            );
-
-            let parent_transform =
-                Transform::from_matrix(grandparent_transform.compute_matrix().inverse())
-                    * parent_gp_transform;
-            let bone_transform =
-                Transform::from_matrix(parent_gp_transform.compute_matrix().inverse())
-                    * bone_gp_transform;
-
-            inner_pose_data.bones[*grandparent_id]
-                .rotation
-                .as_mut()
-                .unwrap()
-                .map_mut(|_| grandparent_transform.rotation);
-
-            inner_pose_data.bones[*parent_id]
-                .rotation
-                .as_mut()
-                .unwrap()
-                .map_mut(|_| parent_transform.rotation);
-
-            inner_pose_data.bones[*bone_id]
-                .rotation
-                .as_mut()
-                .unwrap()
-                .map_mut(|_| bone_transform.rotation);
-
-            // Debug render (if enabled)
-            ctx.bone_gizmo(
-                target.clone(),
-                Color::BLUE,
-                Some((&inner_pose_data, pose.timestamp)),
-            );
-            ctx.bone_gizmo(
-                parent_path.clone(),
-                Color::BLUE,
-                Some((&inner_pose_data, pose.timestamp)),
-            );
-        }
+        apply_two_bone_ik(
+            &mut ctx,
+            inner_pose_data,
+            target.id(),
+            target_pos_char,
+            pose.timestamp,
+        );
 
         Ok(Some(PoseFrame {
             data: PoseFrameData::BoneSpace(bone_pose_data),
             timestamp: pose.timestamp,
+            events: pose.events,
         }))
     }
 
@@ -171,6 +91,90 @@ impl NodeLike for TwoBoneIKNode {
     }
 }
 
+/// Rotates the target bone, its parent and its grandparent so that the target bone reaches the
+/// given position (in character space).
+pub(crate) fn apply_two_bone_ik(
+    ctx: &mut PassContext,
+    inner_pose_data: &mut InnerPoseFrame,
+    target: BoneId,
+    target_pos_char: Vec3,
+    timestamp: f32,
+) {
+    let skeleton = ctx.skeleton;
+    if let (Some(bone_idx), Some(parent_id), Some(grandparent_id)) = (
+        inner_pose_data.ids.get(&target).copied(),
+        skeleton.parent(target),
+        skeleton.parent(target).and_then(|p| skeleton.parent(p)),
+    ) {
+        // Debug render (if enabled)
+        ctx.bone_gizmo(target, Color::RED, Some((inner_pose_data, timestamp)));
+        ctx.bone_gizmo(parent_id, Color::RED, Some((inner_pose_data, timestamp)));
+
+        let bone = inner_pose_data.bones[bone_idx].clone();
+        let target_gp = ctx.root_to_bone_space(
+            Transform::from_translation(target_pos_char),
+            inner_pose_data,
+            skeleton.parent(grandparent_id).unwrap(),
+            timestamp,
+        );
+
+        let target_pos_gp = target_gp.translation;
+
+        let parent_idx = *inner_pose_data.ids.get(&parent_id).unwrap();
+        let parent_frame = {
+            let parent_bone = inner_pose_data.bones.get_mut(parent_idx).unwrap();
+            parent_bone.to_transform_frame_linear()
+        };
+        let parent_transform = parent_frame.sample_linear_at(timestamp);
+
+        let grandparent_idx = *inner_pose_data.ids.get(&grandparent_id).unwrap();
+        let grandparent_bone = inner_pose_data.bones.get_mut(grandparent_idx).unwrap();
+        let grandparent_frame = grandparent_bone.to_transform_frame_linear();
+        let grandparent_transform = grandparent_frame.sample_linear_at(timestamp);
+
+        let bone_frame = bone.to_transform_frame_linear();
+        let bone_transform = bone_frame.sample_linear_at(timestamp);
+
+        let parent_gp_transform = grandparent_transform * parent_transform;
+        let bone_gp_transform = parent_gp_transform * bone_transform;
+
+        let (bone_gp_transform, parent_gp_transform, grandparent_transform) = two_bone_ik(
+            bone_gp_transform,
+            parent_gp_transform,
+            grandparent_transform,
+            target_pos_gp,
+        );
+
+        let parent_transform =
+            Transform::from_matrix(grandparent_transform.compute_matrix().inverse())
+                * parent_gp_transform;
+        let bone_transform = Transform::from_matrix(parent_gp_transform.compute_matrix().inverse())
+            * bone_gp_transform;
+
+        inner_pose_data.bones[grandparent_idx]
+            .rotation
+            .as_mut()
+            .unwrap()
+            .map_mut(|_| grandparent_transform.rotation);
+
+        inner_pose_data.bones[parent_idx]
+            .rotation
+            .as_mut()
+            .unwrap()
+            .map_mut(|_| parent_transform.rotation);
+
+        inner_pose_data.bones[bone_idx]
+            .rotation
+            .as_mut()
+            .unwrap()
+            .map_mut(|_| bone_transform.rotation);
+
+        // Debug render (if enabled)
+        ctx.bone_gizmo(target, Color::BLUE, Some((inner_pose_data, timestamp)));
+        ctx.bone_gizmo(parent_id, Color::BLUE, Some((inner_pose_data, timestamp)));
+    }
+}
+
 // Adapted from https://blog.littlepolygon.com/posts/twobone/
 fn two_bone_ik(
     bone: Transform,
@@ -0,0 +1,439 @@
+//! Retargeting of animation clips between skeletons with different bone names, rest
+//! orientations and proportions.
+//!
+//! A [`RetargetMap`] is defined in a `*.retarget.ron` file, which lists pairs of source and
+//! target bone paths and points to a scene for each skeleton from which rest poses are read:
+//! ```ron
+//! (
+//!     source: Scene("models/mocap.glb#Scene0"),
+//!     target: Scene("models/character.glb#Scene0"),
+//!     bones: [
+//!         (source: ["Hips"], target: ["Armature", "hips"]),
+//!         (source: ["Hips", "Spine"], target: ["Armature", "hips", "spine"]),
+//!     ],
+//! )
+//! ```
+//! The rest pose of a skeleton can also be read from a BVH file, using the joint offsets with no
+//! rotation. As in BVH clip sources, the BVH root joint is placed under `root` and offsets are
+//! multiplied by `scale`:
+//! ```ron
+//! source: Bvh(path: "mocap/walk.bvh", root: ["Armature"], scale: 0.01),
+//! ```
+
+use crate::{
+    core::{
+        animation_clip::{EntityPath, GraphClip, Interpolation, Keyframes, VariableCurve},
+        errors::AssetLoaderError,
+    },
+    utils::bvh::{Bvh, BvhError},
+};
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    core::Name,
+    hierarchy::Parent,
+    math::{Quat, Vec3},
+    reflect::Reflect,
+    scene::Scene,
+    transform::components::Transform,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RetargetBoneSerial {
+    pub source: EntityPath,
+    pub target: EntityPath,
+}
+
+/// Asset from which the rest pose of a skeleton is read
+#[derive(Serialize, Deserialize, Clone)]
+pub enum RestPoseSource {
+    /// Asset path of a scene with the skeleton in its rest pose
+    Scene(String),
+    /// BVH file whose joint offsets give the rest pose, with identity rotations
+    Bvh {
+        path: String,
+        /// Path of the entity the BVH root joint is a child of
+        #[serde(default)]
+        root: Vec<String>,
+        /// Factor applied to joint offsets, e.g. `0.01` for mocap recorded in centimeters
+        #[serde(default = "default_bvh_scale")]
+        scale: f32,
+    },
+}
+
+fn default_bvh_scale() -> f32 {
+    1.
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RetargetMapSerial {
+    /// Rest pose of the source skeleton
+    pub source: RestPoseSource,
+    /// Rest pose of the target skeleton
+    pub target: RestPoseSource,
+    pub bones: Vec<RetargetBoneSerial>,
+}
+
+/// Mapping of a source bone onto a target bone, with the corrections derived from the rest
+/// poses of both skeletons.
+#[derive(Reflect, Clone, Debug)]
+pub struct RetargetBone {
+    pub source: EntityPath,
+    pub target: EntityPath,
+    /// Local rest transform of the source bone
+    pub source_rest: Transform,
+    /// Local rest transform of the target bone
+    pub target_rest: Transform,
+    /// Takes rotations from the rest orientation of the source parent bone to the one of the
+    /// target parent bone
+    pub parent_correction: Quat,
+    /// Takes rotations from the rest orientation of the target bone to the one of the source
+    /// bone
+    pub bone_correction: Quat,
+    /// Ratio between the rest offsets from the parent bone (i.e. the parent limb length) of the
+    /// target and source bones
+    pub translation_scale: f32,
+}
+
+impl RetargetBone {
+    fn new(
+        source: EntityPath,
+        target: EntityPath,
+        source_pose: &RestPose,
+        target_pose: &RestPose,
+    ) -> Result<Self, String> {
+        let (source_rest, source_rotation, source_parent_rotation) = source_pose.get(&source)?;
+        let (target_rest, target_rotation, target_parent_rotation) = target_pose.get(&target)?;
+
+        let source_length = source_rest.translation.length();
+        let translation_scale = if source_length > f32::EPSILON {
+            target_rest.translation.length() / source_length
+        } else {
+            1.
+        };
+
+        Ok(Self {
+            source,
+            target,
+            source_rest,
+            target_rest,
+            parent_correction: target_parent_rotation.inverse() * source_parent_rotation,
+            bone_correction: source_rotation.inverse() * target_rotation,
+            translation_scale,
+        })
+    }
+
+    /// Converts a local rotation of the source bone. The rotation is carried over in character
+    /// space relative to the rest pose, so the source rest pose maps onto the target rest pose.
+    pub fn rotation(&self, rotation: Quat) -> Quat {
+        self.parent_correction * rotation * self.bone_correction
+    }
+
+    /// Converts a local translation of the source bone. The offset from the rest pose is rotated
+    /// into the target parent bone space and scaled by the ratio of limb lengths.
+    pub fn translation(&self, translation: Vec3) -> Vec3 {
+        self.target_rest.translation
+            + self.parent_correction
+                * (translation - self.source_rest.translation)
+                * self.translation_scale
+    }
+
+    /// Converts a local scale of the source bone, relative to its rest scale.
+    pub fn scale(&self, scale: Vec3) -> Vec3 {
+        self.target_rest.scale * scale / self.source_rest.scale
+    }
+
+    fn retarget_curve(&self, curve: &VariableCurve) -> VariableCurve {
+        // Cubic spline tangents are derivatives, so they are only transformed linearly
+        let is_value = |i: usize| curve.interpolation != Interpolation::CubicSpline || i % 3 == 1;
+        let keyframes = match &curve.keyframes {
+            Keyframes::Rotation(keyframes) => {
+                Keyframes::Rotation(keyframes.iter().map(|q| self.rotation(*q)).collect())
+            }
+            Keyframes::Translation(keyframes) => Keyframes::Translation(
+                keyframes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| {
+                        if is_value(i) {
+                            self.translation(*t)
+                        } else {
+                            self.parent_correction * *t * self.translation_scale
+                        }
+                    })
+                    .collect(),
+            ),
+            Keyframes::Scale(keyframes) => Keyframes::Scale(
+                keyframes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| {
+                        if is_value(i) {
+                            self.scale(*s)
+                        } else {
+                            self.target_rest.scale * *s / self.source_rest.scale
+                        }
+                    })
+                    .collect(),
+            ),
+            Keyframes::Weights(keyframes) => Keyframes::Weights(keyframes.clone()),
+        };
+
+        VariableCurve {
+            keyframe_timestamps: curve.keyframe_timestamps.clone(),
+            keyframes,
+            interpolation: curve.interpolation,
+        }
+    }
+}
+
+/// Converts clips authored for one skeleton so they can be played on another.
+#[derive(Asset, Reflect, Clone, Debug, Default)]
+pub struct RetargetMap {
+    pub bones: Vec<RetargetBone>,
+}
+
+impl RetargetMap {
+    /// Builds a clip for the target skeleton. Bones missing from the map are dropped, and
+    /// events and sync markers are kept.
+    pub fn retarget_clip(&self, clip: &GraphClip) -> GraphClip {
+        let mut retargeted = GraphClip::default();
+        for bone in &self.bones {
+            let Some(curves) = clip.get_curves_by_path(&bone.source) else {
+                continue;
+            };
+            for curve in curves {
+                retargeted.add_curve_to_path(bone.target.clone(), bone.retarget_curve(curve));
+            }
+        }
+        retargeted.duration = clip.duration;
+        retargeted.events = clip.events.clone();
+        retargeted.sync_markers = clip.sync_markers.clone();
+
+        retargeted
+    }
+}
+
+/// Rest transforms of the named entities in a scene, keyed by their path from the outermost
+/// named entity.
+#[derive(Default)]
+struct RestPose {
+    local: HashMap<EntityPath, Transform>,
+    /// Rotation relative to the outermost named entity, including its own rotation
+    character_rotations: HashMap<EntityPath, Quat>,
+}
+
+impl RestPose {
+    fn from_scene(scene: &Scene) -> Self {
+        let world = &scene.world;
+        let mut pose = Self::default();
+
+        for entity in world.iter_entities() {
+            let (Some(name), Some(transform)) = (entity.get::<Name>(), entity.get::<Transform>())
+            else {
+                continue;
+            };
+
+            let mut parts = vec![name.clone()];
+            let mut rotation = transform.rotation;
+            let mut current = entity.get::<Parent>().map(Parent::get);
+            while let Some(parent) = current.and_then(|e| world.get_entity(e)) {
+                let Some(name) = parent.get::<Name>() else {
+                    break;
+                };
+                parts.push(name.clone());
+                if let Some(transform) = parent.get::<Transform>() {
+                    rotation = transform.rotation * rotation;
+                }
+                current = parent.get::<Parent>().map(Parent::get);
+            }
+            parts.reverse();
+
+            let path = EntityPath { parts };
+            pose.local.insert(path.clone(), *transform);
+            pose.character_rotations.insert(path, rotation);
+        }
+
+        pose
+    }
+
+    /// Rest pose given by the joint offsets of a BVH file, with no rotations.
+    fn from_bvh(bvh: &Bvh, root: &EntityPath, scale: f32) -> Self {
+        let mut pose = Self::default();
+        for (joint, path) in bvh.joints.iter().zip(bvh.joint_paths(root)) {
+            pose.local.insert(
+                path.clone(),
+                Transform::from_translation(joint.offset * scale),
+            );
+            pose.character_rotations.insert(path, Quat::IDENTITY);
+        }
+        pose
+    }
+
+    /// Local rest transform, character space rotation and character space rotation of the
+    /// parent for the given bone. Fails with the path of the bone if it is not in the scene.
+    fn get(&self, path: &EntityPath) -> Result<(Transform, Quat, Quat), String> {
+        let (Some(local), Some(rotation)) =
+            (self.local.get(path), self.character_rotations.get(path))
+        else {
+            return Err(path.to_slashed_string());
+        };
+        let parent_rotation = path
+            .parent()
+            .and_then(|parent| self.character_rotations.get(&parent))
+            .copied()
+            .unwrap_or(Quat::IDENTITY);
+
+        Ok((*local, *rotation, parent_rotation))
+    }
+
+    async fn load(
+        source: &RestPoseSource,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self, AssetLoaderError> {
+        match source {
+            RestPoseSource::Scene(path) => Self::load_scene(path, load_context).await,
+            RestPoseSource::Bvh { path, root, scale } => {
+                let bvh_bytes = load_context.read_asset_bytes(path.clone()).await?;
+                let source = std::str::from_utf8(&bvh_bytes).map_err(|_| BvhError::InvalidUtf8)?;
+                let root = EntityPath {
+                    parts: root.iter().cloned().map(Name::new).collect(),
+                };
+                Ok(Self::from_bvh(&Bvh::parse(source)?, &root, *scale))
+            }
+        }
+    }
+
+    async fn load_scene(
+        path: &str,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self, AssetLoaderError> {
+        let asset_path = AssetPath::parse(path);
+        let loaded_asset = load_context
+            .load_direct(asset_path.without_label().into_owned())
+            .await?;
+        let scene = match asset_path.label() {
+            Some(label) => loaded_asset
+                .get_labeled(label.to_string())
+                .and_then(|asset| asset.get::<Scene>()),
+            None => loaded_asset.get::<Scene>(),
+        };
+        let Some(scene) = scene else {
+            return Err(AssetLoaderError::RetargetSceneMissing(path.to_string()));
+        };
+
+        Ok(Self::from_scene(scene))
+    }
+}
+
+#[derive(Default)]
+pub struct RetargetMapLoader;
+
+impl AssetLoader for RetargetMapLoader {
+    type Asset = RetargetMap;
+    type Settings = ();
+    type Error = AssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+            let serial: RetargetMapSerial = ron::de::from_bytes(&bytes)?;
+
+            let source_pose = RestPose::load(&serial.source, load_context).await?;
+            let target_pose = RestPose::load(&serial.target, load_context).await?;
+
+            let bones = serial
+                .bones
+                .into_iter()
+                .map(|bone| RetargetBone::new(bone.source, bone.target, &source_pose, &target_pose))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(AssetLoaderError::RetargetBoneMissing)?;
+
+            Ok(RetargetMap { bones })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["retarget.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retarget_rest_pose_maps_onto_target_rest_pose() {
+        let path = |parts: &[&str]| EntityPath {
+            parts: parts.iter().map(|p| Name::new(p.to_string())).collect(),
+        };
+        let pose = |root: Transform, bone: Transform| RestPose {
+            local: [(path(&["root"]), root), (path(&["root", "bone"]), bone)].into(),
+            character_rotations: [
+                (path(&["root"]), root.rotation),
+                (path(&["root", "bone"]), root.rotation * bone.rotation),
+            ]
+            .into(),
+        };
+
+        let source_bone = Transform::from_xyz(0., 1., 0.);
+        let source = pose(Transform::IDENTITY, source_bone);
+        // Target skeleton is twice as large and its bones are oriented differently
+        let target_bone = Transform::from_xyz(0., 0., 2.).with_rotation(Quat::from_rotation_x(1.));
+        let target = pose(
+            Transform::from_rotation(Quat::from_rotation_y(0.5)),
+            target_bone,
+        );
+
+        let bone = RetargetBone::new(
+            path(&["root", "bone"]),
+            path(&["root", "bone"]),
+            &source,
+            &target,
+        )
+        .unwrap();
+        assert!(bone
+            .rotation(source_bone.rotation)
+            .abs_diff_eq(target_bone.rotation, 1e-6));
+        assert!(bone
+            .translation(source_bone.translation)
+            .abs_diff_eq(target_bone.translation, 1e-6));
+        assert_eq!(bone.translation_scale, 2.);
+        // Moving the source bone up by one moves the target bone twice as far, in the target
+        // parent's space
+        let moved = bone.translation(source_bone.translation + Vec3::Y);
+        assert!((moved - target_bone.translation)
+            .abs_diff_eq(Quat::from_rotation_y(-0.5) * Vec3::new(0., 2., 0.), 1e-6));
+
+        assert_eq!(
+            RetargetBone::new(path(&["missing"]), path(&["root"]), &source, &target).unwrap_err(),
+            "missing"
+        );
+    }
+
+    #[test]
+    fn test_bvh_rest_pose() {
+        let bvh = Bvh::parse(
+            "HIERARCHY ROOT Hips { OFFSET 0 5 0 CHANNELS 0 JOINT Spine { OFFSET 0 10 0 CHANNELS 0 } }
+            MOTION Frames: 0 Frame Time: 0.1",
+        )
+        .unwrap();
+        let root = EntityPath {
+            parts: vec![Name::new("Armature")],
+        };
+        let pose = RestPose::from_bvh(&bvh, &root, 0.1);
+
+        let spine = root.child(Name::new("Hips")).child(Name::new("Spine"));
+        let (local, rotation, parent_rotation) = pose.get(&spine).unwrap();
+        assert_eq!(local, Transform::from_xyz(0., 1., 0.));
+        assert_eq!(rotation, Quat::IDENTITY);
+        assert_eq!(parent_rotation, Quat::IDENTITY);
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
     core::{
-        frame::{BoneFrame, BonePoseFrame, InnerPoseFrame, ValueFrame},
+        frame::{BoneFrame, BonePoseFrame, FrameInterpolation, InnerPoseFrame, ValueFrame},
         pose::{BonePose, Pose},
     },
-    interpolation::linear::InterpolateLinear,
+    interpolation::{cubic::InterpolateCubicSpline, linear::InterpolateLinear},
 };
 use bevy::prelude::*;
 
@@ -12,7 +12,11 @@ pub trait SampleLinearAt {
     fn sample_linear_at(&self, time: f32) -> Self::Output;
 }
 
-impl<T: InterpolateLinear + FromReflect + TypePath> SampleLinearAt for ValueFrame<T> {
+/// Samples the frame between its keyframes. Despite the name, step and cubic spline frames are
+/// sampled according to their interpolation mode.
+impl<T: InterpolateLinear + InterpolateCubicSpline + FromReflect + TypePath + Clone> SampleLinearAt
+    for ValueFrame<T>
+{
     type Output = T;
 
     fn sample_linear_at(&self, time: f32) -> Self::Output {
@@ -28,7 +32,17 @@ impl<T: InterpolateLinear + FromReflect + TypePath> SampleLinearAt for ValueFram
             (time - self.prev_timestamp) / (self.next_timestamp - self.prev_timestamp)
         };
 
-        self.prev.interpolate_linear(&self.next, f)
+        match &self.interpolation {
+            FrameInterpolation::Linear => self.prev.interpolate_linear(&self.next, f),
+            FrameInterpolation::Step => self.prev.clone(),
+            FrameInterpolation::CubicSpline {
+                prev_out_tangent,
+                next_in_tangent,
+            } => {
+                self.prev
+                    .interpolate_cubic_spline(prev_out_tangent, &self.next, next_in_tangent, f)
+            }
+        }
     }
 }
 
@@ -50,7 +64,7 @@ impl SampleLinearAt for InnerPoseFrame {
 
     fn sample_linear_at(&self, time: f32) -> Self::Output {
         Pose {
-            paths: self.paths.clone(),
+            ids: self.ids.clone(),
             bones: self
                 .bones
                 .iter()
@@ -67,3 +81,47 @@ impl SampleLinearAt for BonePoseFrame {
         self.inner_ref().sample_linear_at(time)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(interpolation: FrameInterpolation<Vec3>) -> ValueFrame<Vec3> {
+        ValueFrame {
+            prev: Vec3::ZERO,
+            prev_timestamp: 1.,
+            next: Vec3::ONE,
+            next_timestamp: 3.,
+            prev_is_wrapped: false,
+            next_is_wrapped: false,
+            interpolation,
+        }
+    }
+
+    #[test]
+    fn test_sample_step_and_cubic_spline() {
+        let step = frame(FrameInterpolation::Step);
+        assert_eq!(step.sample_linear_at(1.), Vec3::ZERO);
+        assert_eq!(step.sample_linear_at(2.9), Vec3::ZERO);
+
+        // With zero tangents the spline eases in and out, but still crosses the midpoint halfway
+        let flat = frame(FrameInterpolation::CubicSpline {
+            prev_out_tangent: Vec3::ZERO,
+            next_in_tangent: Vec3::ZERO,
+        });
+        assert!(flat.sample_linear_at(1.5).x < 0.25);
+        assert!((flat.sample_linear_at(2.) - Vec3::splat(0.5)).length() < 1e-6);
+        assert!((flat.sample_linear_at(3.) - Vec3::ONE).length() < 1e-6);
+
+        // Tangents matching the slope of the segment reproduce linear interpolation
+        let straight = frame(FrameInterpolation::CubicSpline {
+            prev_out_tangent: Vec3::ONE,
+            next_in_tangent: Vec3::ONE,
+        });
+        let linear = frame(FrameInterpolation::Linear);
+        for time in [1., 1.3, 2.2, 2.9] {
+            let diff = straight.sample_linear_at(time) - linear.sample_linear_at(time);
+            assert!(diff.length() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Parser for BVH (Biovision Hierarchy) motion capture files, and conversion of their motion
+//! data into a [`GraphClip`].
+//!
+//! A BVH file has two sections:
+//! - `HIERARCHY`: a tree of joints starting at `ROOT`, each with an `OFFSET` from its parent,
+//!   a list of `CHANNELS` and any number of child `JOINT`s or `End Site`s.
+//! - `MOTION`: the number of frames, the frame time and one line of channel values per frame,
+//!   in the order the channels were declared in the hierarchy.
+//!
+//! Rotations are in degrees and applied in the order the channels are listed.
+
+use crate::core::animation_clip::{EntityPath, GraphClip, Interpolation, Keyframes, VariableCurve};
+use bevy::{
+    core::Name,
+    math::{Quat, Vec3},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum BvhError {
+    #[error("BVH file is not valid UTF-8")]
+    InvalidUtf8,
+    #[error("Expected '{expected}' in BVH file, found '{found}'")]
+    UnexpectedToken { expected: String, found: String },
+    #[error("Unexpected end of BVH file")]
+    UnexpectedEnd,
+    #[error("Invalid number in BVH file: '{0}'")]
+    InvalidNumber(String),
+    #[error("Unknown BVH channel: '{0}'")]
+    UnknownChannel(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BvhChannel {
+    Xposition,
+    Yposition,
+    Zposition,
+    Xrotation,
+    Yrotation,
+    Zrotation,
+}
+
+impl BvhChannel {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Xposition" => Self::Xposition,
+            "Yposition" => Self::Yposition,
+            "Zposition" => Self::Zposition,
+            "Xrotation" => Self::Xrotation,
+            "Yrotation" => Self::Yrotation,
+            "Zrotation" => Self::Zrotation,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhJoint {
+    pub name: String,
+    /// `None` for the root joint
+    pub parent: Option<usize>,
+    pub offset: Vec3,
+    pub channels: Vec<BvhChannel>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bvh {
+    /// Joints in the order they are declared, so every joint comes after its parent
+    pub joints: Vec<BvhJoint>,
+    pub frame_time: f32,
+    /// Channel values of each frame, for all joints in declaration order
+    pub frames: Vec<Vec<f32>>,
+}
+
+struct Parser<'a> {
+    tokens: std::iter::Peekable<std::str::SplitWhitespace<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Result<&'a str, BvhError> {
+        self.tokens.next().ok_or(BvhError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), BvhError> {
+        let found = self.next()?;
+        if found == expected {
+            Ok(())
+        } else {
+            Err(BvhError::UnexpectedToken {
+                expected: expected.into(),
+                found: found.into(),
+            })
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, BvhError> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| BvhError::InvalidNumber(token.into()))
+    }
+
+    fn vec3(&mut self) -> Result<Vec3, BvhError> {
+        Ok(Vec3::new(self.number()?, self.number()?, self.number()?))
+    }
+
+    /// Parses the body of a joint after its name, including the braces
+    fn joint(
+        &mut self,
+        name: String,
+        parent: Option<usize>,
+        joints: &mut Vec<BvhJoint>,
+    ) -> Result<(), BvhError> {
+        self.expect("{")?;
+        self.expect("OFFSET")?;
+        let offset = self.vec3()?;
+        self.expect("CHANNELS")?;
+        let count: usize = self.number()?;
+        let channels = (0..count)
+            .map(|_| {
+                let name = self.next()?;
+                BvhChannel::from_name(name).ok_or_else(|| BvhError::UnknownChannel(name.into()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let index = joints.len();
+        joints.push(BvhJoint {
+            name,
+            parent,
+            offset,
+            channels,
+        });
+
+        loop {
+            match self.next()? {
+                "JOINT" => {
+                    let name = self.next()?.to_string();
+                    self.joint(name, Some(index), joints)?;
+                }
+                "End" => {
+                    // End sites only carry the length of the last bone
+                    self.expect("Site")?;
+                    self.expect("{")?;
+                    self.expect("OFFSET")?;
+                    self.vec3()?;
+                    self.expect("}")?;
+                }
+                "}" => return Ok(()),
+                found => {
+                    return Err(BvhError::UnexpectedToken {
+                        expected: "}".into(),
+                        found: found.into(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl Bvh {
+    pub fn parse(source: &str) -> Result<Self, BvhError> {
+        let mut parser = Parser {
+            tokens: source.split_whitespace().peekable(),
+        };
+
+        parser.expect("HIERARCHY")?;
+        parser.expect("ROOT")?;
+        let mut joints = Vec::new();
+        let root_name = parser.next()?.to_string();
+        parser.joint(root_name, None, &mut joints)?;
+
+        parser.expect("MOTION")?;
+        parser.expect("Frames:")?;
+        let frame_count: usize = parser.number()?;
+        parser.expect("Frame")?;
+        parser.expect("Time:")?;
+        let frame_time: f32 = parser.number()?;
+
+        let channel_count = joints.iter().map(|j| j.channels.len()).sum();
+        let frames = (0..frame_count)
+            .map(|_| (0..channel_count).map(|_| parser.number()).collect())
+            .collect::<Result<Vec<Vec<f32>>, _>>()?;
+
+        if let Some(found) = parser.tokens.peek() {
+            return Err(BvhError::UnexpectedToken {
+                expected: "end of file".into(),
+                found: found.to_string(),
+            });
+        }
+
+        Ok(Self {
+            joints,
+            frame_time,
+            frames,
+        })
+    }
+
+    /// Entity paths of all joints in declaration order, with the BVH root joint placed as a
+    /// child of `root`.
+    pub fn joint_paths(&self, root: &EntityPath) -> Vec<EntityPath> {
+        let mut paths: Vec<EntityPath> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let parent_path = joint.parent.map_or(root, |parent| &paths[parent]);
+            paths.push(parent_path.child(Name::new(joint.name.clone())));
+        }
+        paths
+    }
+
+    /// Builds a clip animating each joint with rotation channels and each joint with position
+    /// channels. The BVH root joint is placed as a child of `root`, and positions are multiplied
+    /// by `scale` to convert them to scene units.
+    pub fn to_graph_clip(&self, root: &EntityPath, scale: f32) -> GraphClip {
+        let paths = self.joint_paths(root);
+
+        let keyframe_timestamps: Vec<f32> = (0..self.frames.len())
+            .map(|i| i as f32 * self.frame_time)
+            .collect();
+
+        let mut clip = GraphClip::default();
+        let mut first_channel = 0;
+        for (joint, path) in self.joints.iter().zip(paths) {
+            let channels = first_channel..first_channel + joint.channels.len();
+            first_channel = channels.end;
+
+            let mut rotations = Vec::with_capacity(self.frames.len());
+            let mut translations = Vec::with_capacity(self.frames.len());
+            for frame in &self.frames {
+                let mut rotation = Quat::IDENTITY;
+                let mut translation = joint.offset;
+                for (channel, value) in joint.channels.iter().zip(&frame[channels.clone()]) {
+                    match channel {
+                        BvhChannel::Xposition => translation.x = *value,
+                        BvhChannel::Yposition => translation.y = *value,
+                        BvhChannel::Zposition => translation.z = *value,
+                        BvhChannel::Xrotation => {
+                            rotation *= Quat::from_rotation_x(value.to_radians())
+                        }
+                        BvhChannel::Yrotation => {
+                            rotation *= Quat::from_rotation_y(value.to_radians())
+                        }
+                        BvhChannel::Zrotation => {
+                            rotation *= Quat::from_rotation_z(value.to_radians())
+                        }
+                    }
+                }
+                rotations.push(rotation);
+                translations.push(translation * scale);
+            }
+
+            let has_channel =
+                |channels: &[BvhChannel]| joint.channels.iter().any(|c| channels.contains(c));
+            if has_channel(&[
+                BvhChannel::Xposition,
+                BvhChannel::Yposition,
+                BvhChannel::Zposition,
+            ]) {
+                clip.add_curve_to_path(
+                    path.clone(),
+                    VariableCurve {
+                        keyframe_timestamps: keyframe_timestamps.clone(),
+                        keyframes: Keyframes::Translation(translations),
+                        interpolation: Interpolation::Linear,
+                    },
+                );
+            }
+            if has_channel(&[
+                BvhChannel::Xrotation,
+                BvhChannel::Yrotation,
+                BvhChannel::Zrotation,
+            ]) {
+                clip.add_curve_to_path(
+                    path,
+                    VariableCurve {
+                        keyframe_timestamps: keyframe_timestamps.clone(),
+                        keyframes: Keyframes::Rotation(rotations),
+                        interpolation: Interpolation::Linear,
+                    },
+                );
+            }
+        }
+
+        clip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "
+HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT Spine
+    {
+        OFFSET 0.0 10.0 0.0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        End Site
+        {
+            OFFSET 0.0 5.0 0.0
+        }
+    }
+}
+MOTION
+Frames: 2
+Frame Time: 0.5
+1.0 90.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+2.0 90.0 0.0 90.0 0.0 0.0 0.0 90.0 0.0
+";
+
+    #[test]
+    fn test_bvh_to_graph_clip() {
+        let bvh = Bvh::parse(SOURCE).unwrap();
+        assert_eq!(bvh.joints.len(), 2);
+        assert_eq!(bvh.joints[1].parent, Some(0));
+        assert_eq!(bvh.frames.len(), 2);
+
+        let root = EntityPath {
+            parts: vec![Name::new("Armature")],
+        };
+        let clip = bvh.to_graph_clip(&root, 0.01);
+        assert_eq!(clip.duration(), 0.5);
+
+        let hips = root.child(Name::new("Hips"));
+        let hips_curves = clip.get_curves_by_path(&hips).unwrap();
+        let Keyframes::Translation(translations) = &hips_curves[0].keyframes else {
+            panic!("Expected translation curve first");
+        };
+        assert!((translations[1] - Vec3::new(0.02, 0.9, 0.)).length() < 1e-6);
+        let Keyframes::Rotation(rotations) = &hips_curves[1].keyframes else {
+            panic!("Expected rotation curve second");
+        };
+        assert!(rotations[1].abs_diff_eq(Quat::from_rotation_z(90f32.to_radians()), 1e-6));
+
+        // Joints without position channels only get a rotation curve
+        let spine_curves = clip
+            .get_curves_by_path(&hips.child(Name::new("Spine")))
+            .unwrap();
+        assert_eq!(spine_curves.len(), 1);
+
+        assert_eq!(
+            Bvh::parse("HIERARCHY ROOT Hips { OFFSET 0 0 0 CHANNELS 1 Wrotation }"),
+            Err(BvhError::UnknownChannel("Wrotation".into()))
+        );
+    }
+}
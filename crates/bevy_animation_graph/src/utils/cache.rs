@@ -0,0 +1,58 @@
+use std::{
+    borrow::Borrow,
+    sync::{Arc, RwLock},
+};
+
+/// Value derived from a key, kept until the key changes. Used by nodes to store data computed
+/// from their fields, which can be edited through reflection at any time, without recomputing it
+/// every frame. An up to date value is returned after only comparing the keys.
+pub struct Cached<K, V> {
+    entry: RwLock<Option<(K, Arc<V>)>>,
+}
+
+impl<K, V> Cached<K, V> {
+    pub fn new(key: K, value: V) -> Self {
+        Self {
+            entry: RwLock::new(Some((key, Arc::new(value)))),
+        }
+    }
+
+    /// Returns the cached value if it was computed for `key`, otherwise computes and stores it.
+    pub fn get_or_update<Q>(&self, key: &Q, update: impl FnOnce(&Q) -> V) -> Arc<V>
+    where
+        K: Borrow<Q>,
+        Q: PartialEq + ToOwned<Owned = K> + ?Sized,
+    {
+        if let Some((cached_key, value)) = self.entry.read().unwrap().as_ref() {
+            if cached_key.borrow() == key {
+                return value.clone();
+            }
+        }
+
+        let value = Arc::new(update(key));
+        *self.entry.write().unwrap() = Some((key.to_owned(), value.clone()));
+        value
+    }
+}
+
+impl<K, V> Default for Cached<K, V> {
+    fn default() -> Self {
+        Self {
+            entry: RwLock::new(None),
+        }
+    }
+}
+
+impl<K: Clone, V> Clone for Cached<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entry: RwLock::new(self.entry.read().unwrap().clone()),
+        }
+    }
+}
+
+impl<K, V> std::fmt::Debug for Cached<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cached")
+    }
+}
@@ -0,0 +1,474 @@
+//! Parser and evaluator for the formulas used by the
+//! [`ExpressionNode`](crate::nodes::ExpressionNode).
+//!
+//! Supported syntax:
+//! - Number literals (`1`, `0.5`, `1e-3`)
+//! - Variables: any identifier that is not a function or constant name
+//! - Constants: `pi`
+//! - Binary operators `+`, `-`, `*`, `/`, `%` and `^` (power, right associative), and unary `-`
+//! - Functions: `abs`, `sign`, `sqrt`, `exp`, `ln`, `floor`, `ceil`, `sin`, `cos`, `tan`,
+//!   `asin`, `acos`, `atan`, `atan2`, `pow`, `min`, `max`, `clamp`, `lerp` and `smoothstep`
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ExpressionError {
+    #[error("Unexpected character '{0}' at position {1}")]
+    UnexpectedCharacter(char, usize),
+    #[error("Unexpected token at position {0}")]
+    UnexpectedToken(usize),
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+    #[error("Function {function} takes {expected} arguments, but {found} were given")]
+    WrongArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Expression is nested more than {0} levels deep")]
+    TooDeep(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Abs,
+    Sign,
+    Sqrt,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Pow,
+    Min,
+    Max,
+    Clamp,
+    Lerp,
+    Smoothstep,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "abs" => Self::Abs,
+            "sign" => Self::Sign,
+            "sqrt" => Self::Sqrt,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "asin" => Self::Asin,
+            "acos" => Self::Acos,
+            "atan" => Self::Atan,
+            "atan2" => Self::Atan2,
+            "pow" => Self::Pow,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "clamp" => Self::Clamp,
+            "lerp" => Self::Lerp,
+            "smoothstep" => Self::Smoothstep,
+            _ => return None,
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Self::Atan2 | Self::Pow | Self::Min | Self::Max => 2,
+            Self::Clamp | Self::Lerp | Self::Smoothstep => 3,
+            _ => 1,
+        }
+    }
+
+    fn apply(&self, args: &[f32]) -> f32 {
+        match self {
+            Self::Abs => args[0].abs(),
+            Self::Sign => args[0].signum(),
+            Self::Sqrt => args[0].sqrt(),
+            Self::Exp => args[0].exp(),
+            Self::Ln => args[0].ln(),
+            Self::Floor => args[0].floor(),
+            Self::Ceil => args[0].ceil(),
+            Self::Sin => args[0].sin(),
+            Self::Cos => args[0].cos(),
+            Self::Tan => args[0].tan(),
+            Self::Asin => args[0].asin(),
+            Self::Acos => args[0].acos(),
+            Self::Atan => args[0].atan(),
+            Self::Atan2 => args[0].atan2(args[1]),
+            Self::Pow => args[0].powf(args[1]),
+            Self::Min => args[0].min(args[1]),
+            Self::Max => args[0].max(args[1]),
+            // Not using f32::clamp, which panics when min > max
+            Self::Clamp => args[0].max(args[1]).min(args[2]),
+            Self::Lerp => args[0] + (args[1] - args[0]) * args[2],
+            Self::Smoothstep => {
+                let t = ((args[2] - args[0]) / (args[1] - args[0])).clamp(0., 1.);
+                t * t * (3. - 2. * t)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f32),
+    /// Index of the variable in [`Expression::variables`]
+    Variable(usize),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, variables: &[f32]) -> f32 {
+        match self {
+            Expr::Number(x) => *x,
+            Expr::Variable(index) => variables[*index],
+            Expr::Neg(expr) => -expr.eval(variables),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(variables);
+                let rhs = rhs.eval(variables);
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div => lhs / rhs,
+                    BinaryOp::Rem => lhs % rhs,
+                    BinaryOp::Pow => lhs.powf(rhs),
+                }
+            }
+            Expr::Call(function, args) => {
+                // Functions take at most three arguments
+                let mut values = [0.; 3];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(variables);
+                }
+                function.apply(&values[..args.len()])
+            }
+        }
+    }
+}
+
+/// A parsed formula, together with the names of the variables it uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Expr,
+    variables: Vec<String>,
+}
+
+impl Expression {
+    pub fn parse(source: impl Into<String>) -> Result<Self, ExpressionError> {
+        let source = source.into();
+        let tokens = tokenize(&source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            variables: vec![],
+            depth: 0,
+        };
+        let root = parser.expr()?;
+        if let Some((_, pos)) = parser.tokens.get(parser.pos) {
+            return Err(ExpressionError::UnexpectedToken(*pos));
+        }
+
+        Ok(Self {
+            source,
+            root,
+            variables: parser.variables,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Variables used in the expression, in order of first appearance.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Evaluates the expression with the given variable values, in the same order as
+    /// [`Expression::variables`].
+    pub fn eval(&self, variables: &[f32]) -> f32 {
+        self.root.eval(variables)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = pos;
+            let mut prev = c;
+            while let Some(&(i, c)) = chars.peek() {
+                let exponent_sign = (c == '-' || c == '+') && (prev == 'e' || prev == 'E');
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                    end = i + c.len_utf8();
+                    prev = c;
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let number = source[pos..end]
+                .parse()
+                .map_err(|_| ExpressionError::UnexpectedCharacter(c, pos))?;
+            tokens.push((Token::Number(number), pos));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = pos;
+            while let Some(&(i, c)) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    end = i + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((Token::Ident(source[pos..end].into()), pos));
+        } else {
+            let token = match c {
+                '+' | '-' | '*' | '/' | '%' | '^' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                _ => return Err(ExpressionError::UnexpectedCharacter(c, pos)),
+            };
+            tokens.push((token, pos));
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Maximum nesting depth of a parsed expression, so that parsing, evaluating and dropping it
+/// cannot overflow the stack
+const MAX_DEPTH: usize = 256;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Variables found so far, in order of first appearance
+    variables: Vec<String>,
+    /// Nesting depth of the expression being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), ExpressionError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(ExpressionError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ExpressionError> {
+        let (token, pos) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(ExpressionError::UnexpectedToken(pos))
+        }
+    }
+
+    /// Enters one more level of nesting, failing if the expression is too deep.
+    fn nest(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            Err(ExpressionError::TooDeep(MAX_DEPTH))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, ExpressionError> {
+        let depth = self.depth;
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            self.pos += 1;
+            // Every operator in the chain nests the previous ones one level deeper
+            self.nest()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    /// term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Expr, ExpressionError> {
+        let depth = self.depth;
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek() {
+            let op = match op {
+                '*' => BinaryOp::Mul,
+                '/' => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            self.pos += 1;
+            self.nest()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    /// unary := '-' unary | power
+    fn unary(&mut self) -> Result<Expr, ExpressionError> {
+        self.nest()?;
+        let expr = if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            Expr::Neg(Box::new(self.unary()?))
+        } else {
+            self.power()?
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    /// power := atom ('^' unary)?
+    fn power(&mut self) -> Result<Expr, ExpressionError> {
+        let base = self.atom()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            return Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// atom := number | identifier | identifier '(' args ')' | '(' expr ')'
+    fn atom(&mut self) -> Result<Expr, ExpressionError> {
+        match self.next()? {
+            (Token::Number(x), _) => Ok(Expr::Number(x)),
+            (Token::LParen, _) => {
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            (Token::Ident(name), _) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| ExpressionError::UnknownFunction(name.clone()))?;
+                let mut args = vec![];
+                if self.peek() != Some(&Token::RParen) {
+                    args.push(self.expr()?);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect(Token::RParen)?;
+                if args.len() != function.arity() {
+                    return Err(ExpressionError::WrongArgumentCount {
+                        function: name,
+                        expected: function.arity(),
+                        found: args.len(),
+                    });
+                }
+                Ok(Expr::Call(function, args))
+            }
+            (Token::Ident(name), _) if name == "pi" => Ok(Expr::Number(std::f32::consts::PI)),
+            (Token::Ident(name), _) => {
+                let index = match self.variables.iter().position(|v| *v == name) {
+                    Some(index) => index,
+                    None => {
+                        self.variables.push(name);
+                        self.variables.len() - 1
+                    }
+                };
+                Ok(Expr::Variable(index))
+            }
+            (_, pos) => Err(ExpressionError::UnexpectedToken(pos)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_eval() {
+        let expression =
+            Expression::parse("clamp((abs(speed) - start) / (end - start), 0, 1) * 2^-1").unwrap();
+        assert_eq!(expression.variables(), ["speed", "start", "end"]);
+
+        assert_eq!(expression.eval(&[-2., 1., 3.]), 0.25);
+
+        assert_eq!(
+            Expression::parse("min(1)"),
+            Err(ExpressionError::WrongArgumentCount {
+                function: "min".into(),
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            Expression::parse("1 + (2"),
+            Err(ExpressionError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Expression::parse("1 2"),
+            Err(ExpressionError::UnexpectedToken(2))
+        );
+
+        let too_deep = Err(ExpressionError::TooDeep(MAX_DEPTH));
+        let nested = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+        assert_eq!(Expression::parse(nested), too_deep);
+        assert_eq!(Expression::parse("-".repeat(10_000) + "1"), too_deep);
+        assert_eq!(Expression::parse(["1"; 10_000].join("+")), too_deep);
+        assert!(Expression::parse(["1"; 100].join("+")).is_ok());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod bvh;
+pub mod cache;
+pub mod expression;
 pub mod ordered_map;
 pub mod unwrap;
@@ -146,7 +146,7 @@ where
         self.deref().len()
     }
 
-    fn iter(&self) -> MapIter {
+    fn iter(&self) -> MapIter<'_> {
         MapIter::new(self)
     }
 
@@ -254,11 +254,11 @@ where
         Ok(())
     }
 
-    fn reflect_ref(&self) -> ReflectRef {
+    fn reflect_ref(&self) -> ReflectRef<'_> {
         ReflectRef::Map(self)
     }
 
-    fn reflect_mut(&mut self) -> ReflectMut {
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
         ReflectMut::Map(self)
     }
 
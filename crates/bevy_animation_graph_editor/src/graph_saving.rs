@@ -21,7 +21,13 @@ pub fn save_graph_system(
 ) {
     for ev in evr_save_graph.read() {
         let graph = graph_assets.get(ev.graph).unwrap();
-        let graph_serial = AnimationGraphSerial::from(graph);
+        let graph_serial = match AnimationGraphSerial::try_from(graph) {
+            Ok(graph_serial) => graph_serial,
+            Err(err) => {
+                error!("Could not save graph with id {:?}: {}", ev.graph, err);
+                continue;
+            }
+        };
         let source = asset_server.get_source(AssetSourceId::Default).unwrap();
         let reader = source.reader();
         // HACK: Ideally we would not be doing this, but using bevy's dyanmic asset
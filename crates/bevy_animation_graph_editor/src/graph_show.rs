@@ -42,6 +42,21 @@ fn param_spec_to_colors(spec: ParamSpec) -> (Color32, Color32, Color32) {
             Color32::from_rgb(158, 114, 98),
             Color32::from_rgb(158, 114, 98),
         ),
+        ParamSpec::Bool => (
+            Color32::from_rgb(150, 20, 70),
+            Color32::from_rgb(190, 96, 130),
+            Color32::from_rgb(190, 96, 130),
+        ),
+        ParamSpec::I32 => (
+            Color32::from_rgb(20, 120, 120),
+            Color32::from_rgb(96, 165, 165),
+            Color32::from_rgb(96, 165, 165),
+        ),
+        ParamSpec::Enum => (
+            Color32::from_rgb(110, 40, 140),
+            Color32::from_rgb(155, 110, 180),
+            Color32::from_rgb(155, 110, 180),
+        ),
     }
 }
 
@@ -625,8 +625,13 @@ impl TabViewer<'_> {
         };
         let mut env = InspectorUi::for_bevy(&type_registry, &mut cx);
 
-        let inner = node.node.inner_reflect();
-        let changed = env.ui_for_reflect(inner, ui);
+        let changed = match node.node.inner_reflect() {
+            Some(inner) => env.ui_for_reflect(inner, ui),
+            None => {
+                ui.label("This node is shared with other graphs and cannot be edited here");
+                false
+            }
+        };
 
         if changed {
             changes.push(GraphChange {